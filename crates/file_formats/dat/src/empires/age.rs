@@ -28,7 +28,7 @@ use chariot_io_tools::{ReadExt, ReadArrayExt};
 
 use std::io::prelude::{Seek, Read};
 
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum UnitAttributeId {
     HitPoints,
     LineOfSight,
@@ -74,14 +74,14 @@ impl UnitAttributeId {
     }
 }
 
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug)]
 pub enum ResearchEffectValue {
     SetTo(f32),
     Add(f32),
     MultiplyBy(f32),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum ResearchEffect {
     UnitAttribute {
         target_unit_id: Option<UnitId>,
@@ -27,6 +27,7 @@ use error::Result;
 use identifier::{SoundGroupId, CivilizationId, AgeId, UnitId, ResearchId};
 use chariot_io_tools::{ReadArrayExt, ReadExt};
 use std::collections::{BTreeMap, HashMap};
+use std::collections::hash_map::Values;
 
 use std::io::prelude::{Seek, Read};
 
@@ -78,6 +79,11 @@ impl Civilization {
     pub fn unit<'a>(&'a self, unit_id: UnitId) -> &'a Unit {
         return &self.units[&unit_id];
     }
+
+    /// Iterates over all of the civilization's units, in no particular order
+    pub fn units<'a>(&'a self) -> Values<'a, UnitId, Unit> {
+        self.units.values()
+    }
 }
 
 pub fn read_civs<R: Read + Seek>(stream: &mut R) -> Result<Vec<Civilization>> {
@@ -35,22 +35,26 @@ mod terrain_restrictions;
 mod unit;
 
 
-use empires::age::{ResearchEffectGroup, read_ages};
+pub use empires::age::{ResearchEffect, ResearchEffectGroup, ResearchEffectValue, UnitAttributeId};
+use empires::age::read_ages;
 pub use empires::civ::Civilization;
 use empires::civ::read_civs;
 pub use empires::graphic::Graphic;
 use empires::graphic::read_graphics;
 use empires::player_color::{PlayerColor, read_player_colors};
 use empires::random_map::{RandomMap, read_random_maps};
-use empires::research::{Research, read_research};
-use empires::sound::{SoundEffectGroup, read_sound_effect_groups};
+pub use empires::research::Research;
+use empires::research::read_research;
+pub use empires::sound::{SoundEffect, SoundEffectGroup};
+use empires::sound::read_sound_effect_groups;
 pub use empires::terrain_block::Terrain;
 
 pub use empires::terrain_block::TerrainBlock;
 pub use empires::terrain_block::TerrainBorder;
 use empires::terrain_block::read_terrain_block;
 use empires::terrain_restrictions::{TerrainRestriction, read_terrain_restrictions};
-pub use empires::unit::{InteractionMode, Unit};
+pub use empires::resource::ResourceType;
+pub use empires::unit::{BattleParams, DamageGraphic, InteractionMode, ProjectileParams, Unit, UnitCommand};
 use error::{Result, ErrorKind};
 
 use identifier::{UnitTerrainRestrictionId, TerrainId, CivilizationId, ResearchId, TerrainBorderId, PlayerColorId, UnitId, GraphicId, SoundGroupId, AgeId};
@@ -154,6 +158,12 @@ impl EmpiresDb {
         &self.research[*research_id as usize]
     }
 
+    /// All of the research in the game, in ID order
+    #[inline]
+    pub fn all_research<'a>(&'a self) -> &'a [Research] {
+        &self.research
+    }
+
     /// Retrieve a sound effect group by ID
     #[inline]
     pub fn sound_effect_group<'a>(&'a self, sound_group_id: SoundGroupId) -> &'a SoundEffectGroup {
@@ -36,7 +36,11 @@ pub type ResearchCost = ResourceCost<i16, u8>;
 #[derive(Default, Debug)]
 pub struct Research {
     pub id: ResearchId,
+    /// Research that can unlock this research; unused slots are -1
     pub required_techs: Vec<i16>,
+
+    /// How many of the required techs have to be completed (any of them will do)
+    pub required_tech_count: usize,
     pub resource_costs: Vec<ResearchCost>,
 
     /// Unit id of the location this research can be performed
@@ -75,11 +79,9 @@ fn read_single_research<R: Read + Seek>(stream: &mut R) -> Result<Research> {
     research.required_techs = try!(stream.read_array(MAX_REQUIRED_TECHS, |c| c.read_i16()));
     research.resource_costs = read_resource_costs!(i16, u8, stream, RESOURCE_COST_COUNT);
 
-    let actual_required_techs = try!(stream.read_u16()) as usize;
-    if actual_required_techs > MAX_REQUIRED_TECHS {
+    research.required_tech_count = try!(stream.read_u16()) as usize;
+    if research.required_tech_count > MAX_REQUIRED_TECHS {
         return Err(ErrorKind::BadFile("more required techs than possible").into());
-    } else {
-        research.required_techs.resize(actual_required_techs, Default::default());
     }
 
     research.location = optional_id!(try!(stream.read_i16()));
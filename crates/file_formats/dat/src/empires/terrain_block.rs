@@ -79,7 +79,10 @@ pub struct Terrain {
     short_name: String,
     pub slp_id: Option<SlpFileId>,
     sound_group_id: Option<SoundGroupId>,
-    colors: [u8; 3],
+
+    /// Palette indices of the terrain's minimap colors; the first is its main color
+    pub colors: [u8; 3],
+
     cliff_colors: [u8; 2],
 
     /// ID of the equivalent terrain (same everything) that is passable
@@ -33,8 +33,10 @@ type UnitResourceCost = ResourceCost<i16, i16>;
 
 #[derive(Default, Debug)]
 pub struct DamageGraphic {
-    graphic_id: GraphicId,
-    damage_percent: u8,
+    pub graphic_id: GraphicId,
+
+    /// The graphic is shown once the unit has lost at least this percent of its hit points
+    pub damage_percent: u8,
     old_apply_mode: u8,
     apply_mode: u8,
 }
@@ -159,13 +161,13 @@ impl UnitType {
 pub struct UnitCommand {
     id: UnitCommandId,
     enabled: bool,
-    type_id: i16,
-    class_id: i16,
-    unit_id: Option<UnitId>,
+    pub type_id: i16,
+    pub class_id: i16,
+    pub unit_id: Option<UnitId>,
     terrain_id: Option<TerrainId>,
-    resource_in: i16,
+    pub resource_in: i16,
     resource_productivity_multiplier: i16,
-    resource_out: i16,
+    pub resource_out: i16,
     resource: i16,
     quantity: f32,
     execution_radius: f32,
@@ -176,10 +178,10 @@ pub struct UnitCommand {
     right_click_mode: i8,
     tool_graphic_id: Option<GraphicId>,
     proceeding_graphic_id: Option<GraphicId>,
-    action_graphic_id: Option<GraphicId>,
-    carrying_graphic_id: Option<GraphicId>,
+    pub action_graphic_id: Option<GraphicId>,
+    pub carrying_graphic_id: Option<GraphicId>,
     execution_sound_id: Option<SoundGroupId>,
-    resource_deposit_sound_id: Option<SoundGroupId>,
+    pub resource_deposit_sound_id: Option<SoundGroupId>,
 }
 
 #[derive(Default, Debug)]
@@ -196,32 +198,35 @@ pub struct MotionParams {
 pub struct CommandableParams {
     action_when_discovered_id: i16,
     search_radius: f32,
-    work_rate: f32,
-    drop_sites: [i16; 2],
+    pub work_rate: f32,
+    pub drop_sites: [i16; 2],
     task_swap_id: i8,
-    attack_sound: i16,
-    move_sound: i16,
+    /// Played when the unit is told to attack
+    pub attack_sound: Option<SoundGroupId>,
+
+    /// Played when the unit is told to move
+    pub move_sound: Option<SoundGroupId>,
     animal_mode: i8,
-    commands: Vec<UnitCommand>,
+    pub commands: Vec<UnitCommand>,
 }
 
 #[derive(Default, Debug)]
 pub struct BattleParams {
-    default_armor: u8,
-    attacks: Vec<(i16, i16)>, // class, amount
-    armors: Vec<(i16, i16)>, // class, amount
+    pub default_armor: u8,
+    pub attacks: Vec<(i16, i16)>, // class, amount
+    pub armors: Vec<(i16, i16)>, // class, amount
     terrain_restriction_for_damage_multiplier: i16,
-    max_range: f32,
-    blast_width: f32,
-    reload_time: f32,
-    projectile_unit_id: Option<UnitId>,
-    accuracy_percent: i16,
+    pub max_range: f32,
+    pub blast_width: f32,
+    pub reload_time: f32,
+    pub projectile_unit_id: Option<UnitId>,
+    pub accuracy_percent: i16,
     tower_mode: i8,
-    frame_delay: i16,
-    graphic_displacements: [f32; 3],
-    blast_attack_level: i8,
-    min_range: f32,
-    attack_graphic_id: Option<GraphicId>,
+    pub frame_delay: i16,
+    pub graphic_displacements: [f32; 3],
+    pub blast_attack_level: i8,
+    pub min_range: f32,
+    pub attack_graphic_id: Option<GraphicId>,
     displayed_melee_armour: i16,
     displayed_attack: i16,
     displayed_range: f32,
@@ -230,32 +235,37 @@ pub struct BattleParams {
 
 #[derive(Default, Debug)]
 pub struct BuildingParams {
-    construction_graphic_id: GraphicId,
+    pub construction_graphic_id: GraphicId,
     adjacent_mode: i8,
     graphics_angle: i16,
     disappears_when_built: bool,
     stack_unit_id: Option<UnitId>,
     foundation_terrain_id: Option<TerrainId>,
     old_terrain_id: Option<TerrainId>,
-    research_id: Option<ResearchId>,
-    construction_sound: i16,
+    /// Research that's applied for the player once the building is finished
+    pub research_id: Option<ResearchId>,
+    pub construction_sound: Option<SoundGroupId>,
 }
 
 #[derive(Default, Debug)]
 pub struct ProjectileParams {
     stretch_mode: i8,
-    smart_mode: i8,
+    pub smart_mode: i8,
     drop_animation_mode: i8,
     penetration_mode: i8,
-    projectile_arc: f32,
+    pub projectile_arc: f32,
 }
 
 #[derive(Default, Debug)]
 pub struct TrainableParams {
-    resource_costs: Vec<UnitResourceCost>,
-    train_time: i16,
-    train_location_id: Option<UnitId>,
-    button_id: i8,
+    pub resource_costs: Vec<UnitResourceCost>,
+    pub train_time: i16,
+
+    /// The building the unit is trained at
+    pub train_location_id: Option<UnitId>,
+
+    /// Position of the unit's button in the building's command panel
+    pub button_id: i8,
     displayed_pierce_armor: i16,
 }
 
@@ -270,7 +280,7 @@ pub struct Unit {
     name: String,
     name_id: Option<LocalizationId>,
     creation_id: LocalizationId,
-    class_id: i16,
+    pub class_id: i16,
     pub standing_graphic: Option<GraphicId>,
 
     /// Graphic IDs for when unit is dying; second one is never used
@@ -279,18 +289,20 @@ pub struct Unit {
     /// Always zero; use unknown
     death_mode: i8,
 
-    hit_points: i16,
-    line_of_sight: f32,
+    pub hit_points: i16,
+
+    /// How far the unit can see, in tiles
+    pub line_of_sight: f32,
     garrison_capability: i8,
     pub collision_size_x: f32,
     pub collision_size_y: f32,
     pub collision_size_z: f32,
 
     /// Sound played when unit is trained
-    train_sound_id: Option<SoundGroupId>,
+    pub train_sound_id: Option<SoundGroupId>,
 
     /// Replacement unit id for when the unit is dead and dying animation is completed
-    dead_unit_id: Option<UnitId>,
+    pub dead_unit_id: Option<UnitId>,
 
     /// 0 = unit can be placed on other units in the map editor, 5 = it can't
     placement_mode: i8,
@@ -308,32 +320,40 @@ pub struct Unit {
 
     /// The terrain type of one of the tiles near the placement location of the unit
     /// (in both editor and in-game); think docks next to water
-    placement_side_terrain_ids: [Option<TerrainId>; 2],
+    pub placement_side_terrain_ids: [Option<TerrainId>; 2],
 
     /// When placing the unit in the editor, the unit must be placed on a tile that has a
     /// terrain type with the same ID as one of these values
-    placement_terrain_ids: [Option<TerrainId>; 2],
+    pub placement_terrain_ids: [Option<TerrainId>; 2],
 
     clearance_size_x: f32,
     clearance_size_y: f32,
     hill_mode: i8,
-    visible_in_fog: bool,
+
+    /// Whether the unit stays visible in explored areas that aren't in sight (e.g., trees and mines)
+    pub visible_in_fog: bool,
 
     /// Which terrains the unit can walk on
     pub terrain_restriction: UnitTerrainRestrictionId,
 
     fly_mode: bool,
-    resource_capacity: i16,
+    pub resource_capacity: i16,
     resource_decay: f32,
 
     /// Unit would only be affected by a blast attack with the same or lower level
-    blast_defense_level: i8,
+    pub blast_defense_level: i8,
 
     sub_type: i8,
     pub interaction_mode: InteractionMode,
-    minimap_mode: i8,
+
+    /// How the unit is drawn on the minimap (0 means it isn't)
+    pub minimap_mode: i8,
+
     command_attribute: i8,
-    minimap_color: u8,
+
+    /// Palette index of the unit's minimap dot when it isn't drawn in its player's color
+    pub minimap_color: u8,
+
     help_id: Option<LocalizationId>,
     hotkey_text_id: Option<LocalizationId>,
     hotkey: i32,
@@ -348,21 +368,21 @@ pub struct Unit {
     pub selection_shape_size_y: f32,
     pub selection_shape_size_z: f32,
 
-    resource_storage: Vec<UnitResourceStorage>,
-    damage_graphics: Vec<DamageGraphic>,
+    pub resource_storage: Vec<UnitResourceStorage>,
+    pub damage_graphics: Vec<DamageGraphic>,
 
-    selection_sound: i16,
-    dying_sound: i16,
+    pub selection_sound: Option<SoundGroupId>,
+    pub dying_sound: Option<SoundGroupId>,
     attack_mode: i8,
 
     id2: i16,
 
     pub motion_params: Option<MotionParams>,
-    commandable_params: Option<CommandableParams>,
-    battle_params: Option<BattleParams>,
-    projectile_params: Option<ProjectileParams>,
-    trainable_params: Option<TrainableParams>,
-    building_params: Option<BuildingParams>,
+    pub commandable_params: Option<CommandableParams>,
+    pub battle_params: Option<BattleParams>,
+    pub projectile_params: Option<ProjectileParams>,
+    pub trainable_params: Option<TrainableParams>,
+    pub building_params: Option<BuildingParams>,
 }
 
 pub fn read_unit<R: Read + Seek>(stream: &mut R) -> Result<Unit> {
@@ -431,8 +451,8 @@ pub fn read_unit<R: Read + Seek>(stream: &mut R) -> Result<Unit> {
     let damage_graphic_count = try!(stream.read_u8()) as usize;
     unit.damage_graphics = try!(stream.read_array(damage_graphic_count, |c| read_damage_graphic(c)));
 
-    unit.selection_sound = try!(stream.read_i16());
-    unit.dying_sound = try!(stream.read_i16());
+    unit.selection_sound = optional_id!(try!(stream.read_i16()));
+    unit.dying_sound = optional_id!(try!(stream.read_i16()));
     unit.attack_mode = try!(stream.read_i8());
     try!(stream.read_u8()); // Unknown
 
@@ -505,8 +525,8 @@ fn read_commandable_params<R: Read>(stream: &mut R) -> Result<CommandableParams>
     params.drop_sites[0] = try!(stream.read_i16());
     params.drop_sites[1] = try!(stream.read_i16());
     params.task_swap_id = try!(stream.read_i8());
-    params.attack_sound = try!(stream.read_i16());
-    params.move_sound = try!(stream.read_i16());
+    params.attack_sound = optional_id!(try!(stream.read_i16()));
+    params.move_sound = optional_id!(try!(stream.read_i16()));
     params.animal_mode = try!(stream.read_i8());
 
     let command_count = try!(stream.read_u16()) as usize;
@@ -614,6 +634,6 @@ fn read_building_params<R: Read>(stream: &mut R) -> Result<BuildingParams> {
     params.foundation_terrain_id = optional_id!(try!(stream.read_i16()));
     params.old_terrain_id = optional_id!(try!(stream.read_i16()));
     params.research_id = optional_id!(try!(stream.read_i16()));
-    params.construction_sound = try!(stream.read_i16());
+    params.construction_sound = optional_id!(try!(stream.read_i16()));
     Ok(params)
 }
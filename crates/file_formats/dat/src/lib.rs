@@ -33,9 +33,12 @@ mod tileedge;
 mod error;
 
 pub use empires::{EmpiresDb, EmpiresDbRef};
-pub use empires::{InteractionMode, Unit};
+pub use empires::{BattleParams, DamageGraphic, InteractionMode, ProjectileParams, Unit, UnitCommand};
+pub use empires::ResourceType;
 pub use empires::Civilization;
 pub use empires::Graphic;
+pub use empires::{Research, ResearchEffect, ResearchEffectGroup, ResearchEffectValue, UnitAttributeId};
+pub use empires::{SoundEffect, SoundEffectGroup};
 pub use empires::Terrain;
 pub use empires::TerrainBlock;
 pub use empires::TerrainBorder;
@@ -108,7 +108,9 @@ pub struct VictoryConditions {
     unused1: u32,
     required_relic_count: u32,
     unused2: u32,
-    required_exploration_percent: u32,
+
+    /// Percentage of the map a player has to explore to win (zero if exploring isn't a condition)
+    pub required_exploration_percent: u32,
     unused3: u32,
     all_conditions_required: bool,
     victory_mode: u32,
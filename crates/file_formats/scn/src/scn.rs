@@ -43,10 +43,10 @@ pub struct Scenario {
 }
 
 impl Scenario {
-    /// Retrieves player resources by player ID
+    /// Retrieves player resources by player ID, if the scenario has any for that player
     #[inline]
-    pub fn player_resources<'a>(&'a self, player_id: PlayerId) -> &'a PlayerResources {
-        &self.player_resources[*player_id as usize]
+    pub fn player_resources<'a>(&'a self, player_id: PlayerId) -> Option<&'a PlayerResources> {
+        self.player_resources.get(*player_id as usize)
     }
 
     /// Retrieves a list of units by player ID
@@ -71,6 +71,9 @@ create_id_type!(SlpFrameId, u32);
 /// ID for a WAV file in a DRS package
 create_id_type!(WavFileId, u32);
 
+/// ID for a palette (binary file) in the interfac.drs package
+create_id_type!(PaletteId, u32);
+
 /// ID for a graphic in the empires.dat file
 create_id_type!(GraphicId, u32);
 
@@ -0,0 +1,99 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::Result;
+
+use mixer::{DEFAULT_MAX_VOICES, MIXER_SAMPLE_RATE, Mixer};
+use nalgebra::Vector2;
+use sound::Sound;
+use std::sync::{Arc, Mutex};
+use types::Fixed;
+
+/// Where mixed audio goes: an audio device, a file, or nowhere
+pub trait AudioBackend {
+    /// Starts output. The backend pulls samples from the mixer whenever it needs them,
+    /// possibly from another thread.
+    fn start(&mut self, mixer: Arc<Mutex<Mixer>>) -> Result<()>;
+
+    /// Called every game update with how many sample frames of game time have passed.
+    /// Backends that are driven by a device clock can ignore this.
+    fn advance(&mut self, frames: usize) -> Result<()>;
+}
+
+/// Plays sounds through the mixer and sends the result to a backend
+pub struct Audio {
+    mixer: Arc<Mutex<Mixer>>,
+    backend: Box<AudioBackend>,
+    pending_frames: f64,
+}
+
+impl Audio {
+    pub fn new(backend: Box<AudioBackend>) -> Result<Audio> {
+        let mixer = Arc::new(Mutex::new(Mixer::new(MIXER_SAMPLE_RATE, DEFAULT_MAX_VOICES)));
+        let mut audio = Audio {
+            mixer: mixer,
+            backend: backend,
+            pending_frames: 0.0,
+        };
+        let mixer = audio.mixer.clone();
+        try!(audio.backend.start(mixer));
+        Ok(audio)
+    }
+
+    /// Switches to another backend (e.g., to record to a file instead of playing on a device)
+    pub fn set_backend(&mut self, mut backend: Box<AudioBackend>) -> Result<()> {
+        try!(backend.start(self.mixer.clone()));
+        self.backend = backend;
+        Ok(())
+    }
+
+    /// Plays a sound at a position (in the same space as the listener), or everywhere if
+    /// there's no position. Returns false if the sound won't be heard.
+    pub fn play(&mut self, sound: Arc<Sound>, position: Option<Vector2<i32>>, volume: f32) -> bool {
+        self.mixer().play(sound, position, volume)
+    }
+
+    /// Moves the listener, which positional sounds are panned and attenuated relative to
+    pub fn set_listener(&mut self, position: Vector2<i32>, hearing_radius: i32) {
+        self.mixer().set_listener(position, hearing_radius);
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.mixer().set_volume(volume);
+    }
+
+    pub fn voice_count(&self) -> usize {
+        self.mixer().voice_count()
+    }
+
+    pub fn update(&mut self, time_step: Fixed) -> Result<()> {
+        let sample_rate = self.mixer().sample_rate();
+        self.pending_frames += f64::from(time_step) * sample_rate as f64;
+        let frames = self.pending_frames as usize;
+        self.pending_frames -= frames as f64;
+        self.backend.advance(frames)
+    }
+
+    fn mixer(&self) -> ::std::sync::MutexGuard<Mixer> {
+        // The mixer is only locked while mixing, which can't panic, so it can't be poisoned
+        self.mixer.lock().unwrap()
+    }
+}
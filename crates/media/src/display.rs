@@ -0,0 +1,132 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use nalgebra::Vector2;
+use std::cmp;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum WindowMode {
+    Windowed,
+
+    /// Changes the display's resolution to the game's resolution
+    Fullscreen,
+
+    /// Covers the whole display at whatever resolution it's already at
+    Borderless,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ScalingMode {
+    /// Draws at the window's size, so bigger windows show more of the map
+    None,
+
+    /// Scales the game up by the largest whole number that fits the resolution in the window
+    Integer,
+
+    /// Scales the game up as much as fits the resolution in the window
+    Fractional,
+}
+
+impl FromStr for ScalingMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> ::std::result::Result<ScalingMode, String> {
+        match s {
+            "none" => Ok(ScalingMode::None),
+            "integer" => Ok(ScalingMode::Integer),
+            "fractional" => Ok(ScalingMode::Fractional),
+            _ => Err(format!("unknown scaling mode \"{}\"", s)),
+        }
+    }
+}
+
+/// How the game's window is set up
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DisplaySettings {
+    /// The game's resolution; the size of the window when windowed
+    pub resolution: Vector2<u32>,
+    pub window_mode: WindowMode,
+    pub scaling: ScalingMode,
+}
+
+impl DisplaySettings {
+    pub fn new(width: u32, height: u32) -> DisplaySettings {
+        DisplaySettings {
+            resolution: Vector2::new(width, height),
+            window_mode: WindowMode::Windowed,
+            scaling: ScalingMode::None,
+        }
+    }
+
+    /// How much to scale drawing up by to fit the resolution into a window of the given size
+    pub fn scale_for(&self, window_size: Vector2<u32>) -> f32 {
+        if self.resolution.x == 0 || self.resolution.y == 0 {
+            return 1.0;
+        }
+        let fit = (window_size.x as f32 / self.resolution.x as f32)
+            .min(window_size.y as f32 / self.resolution.y as f32);
+        match self.scaling {
+            ScalingMode::None => 1.0,
+            ScalingMode::Integer => fit.floor().max(1.0),
+            ScalingMode::Fractional => fit.max(1.0),
+        }
+    }
+}
+
+/// Size of the area the game draws in before it gets scaled up to the window's size. The game fills
+/// the window, so one side can be larger than the resolution when the aspect ratios don't match.
+pub fn logical_size(window_size: Vector2<u32>, scale_x: f32, scale_y: f32) -> Vector2<u32> {
+    Vector2::new(cmp::max(1, (window_size.x as f32 / scale_x) as u32),
+                 cmp::max(1, (window_size.y as f32 / scale_y) as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector2;
+    use super::{DisplaySettings, ScalingMode, logical_size};
+
+    #[test]
+    fn test_scale_for() {
+        let mut settings = DisplaySettings::new(640, 480);
+        assert_eq!(1.0, settings.scale_for(Vector2::new(1920, 1080)));
+
+        settings.scaling = ScalingMode::Integer;
+        assert_eq!(2.0, settings.scale_for(Vector2::new(1920, 1080)));
+        assert_eq!(1.0, settings.scale_for(Vector2::new(800, 600)));
+        assert_eq!(1.0, settings.scale_for(Vector2::new(320, 240)));
+
+        settings.scaling = ScalingMode::Fractional;
+        assert_eq!(2.25, settings.scale_for(Vector2::new(1920, 1080)));
+        assert_eq!(1.25, settings.scale_for(Vector2::new(800, 600)));
+        assert_eq!(1.0, settings.scale_for(Vector2::new(320, 240)));
+
+        assert_eq!(Ok(ScalingMode::Integer), "integer".parse());
+        assert!("huge".parse::<ScalingMode>().is_err());
+    }
+
+    #[test]
+    fn test_logical_size() {
+        assert_eq!(Vector2::new(960, 540), logical_size(Vector2::new(1920, 1080), 2.0, 2.0));
+        assert_eq!(Vector2::new(853, 480), logical_size(Vector2::new(1920, 1080), 2.25, 2.25));
+        assert_eq!(Vector2::new(800, 600), logical_size(Vector2::new(800, 600), 1.0, 1.0));
+    }
+}
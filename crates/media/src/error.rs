@@ -20,6 +20,7 @@
 // SOFTWARE.
 //
 
+use png;
 use sdl2;
 
 use std::io;
@@ -37,8 +38,18 @@ error_chain! {
         sdl2::video::WindowBuildError, WindowBuildError;
         sdl2::IntegerOrSdlError, IntegerOrSdlError;
         sdl2::render::TextureValueError, TextureValueError;
+        png::EncodingError, PngEncodingError;
+        png::DecodingError, PngDecodingError;
     }
 
     errors {
+        InvalidWav(message: String) {
+            description("invalid WAV file")
+            display("invalid WAV file: {}", message)
+        }
+        InvalidInputScript(line: usize, message: String) {
+            description("invalid input script")
+            display("invalid input script on line {}: {}", line, message)
+        }
     }
 }
@@ -0,0 +1,325 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use audio::Audio;
+use error::{ErrorKind, Result};
+use key::{Key, KeyStates, MouseButton};
+use media::{Media, update_key_states};
+
+use nalgebra::Vector2;
+use renderer::Renderer;
+use sink_audio_backend::NullAudioBackend;
+use software_renderer::SoftwareRenderer;
+
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum InputEvent {
+    KeyDown(Key),
+    KeyUp(Key),
+    TextInput(String),
+    MouseMove(Vector2<i32>),
+    MouseDown(MouseButton),
+    MouseUp(MouseButton),
+    MouseWheel(Vector2<i32>),
+    Quit,
+}
+
+/// Input events to play back at specific frames (counted in calls to `Media::update`)
+#[derive(Clone, Debug, Default)]
+pub struct InputScript {
+    events: Vec<(u64, InputEvent)>,
+}
+
+impl InputScript {
+    pub fn new() -> InputScript {
+        Default::default()
+    }
+
+    pub fn push(&mut self, frame: u64, event: InputEvent) {
+        // Keep the events sorted by frame, but in the order they were added within a frame
+        let index = self.events.iter().position(|&(f, _)| f > frame).unwrap_or(self.events.len());
+        self.events.insert(index, (frame, event));
+    }
+
+    pub fn read_from_file<P: AsRef<Path>>(file_name: P) -> Result<InputScript> {
+        let mut text = String::new();
+        try!(try!(File::open(file_name.as_ref())).read_to_string(&mut text));
+        InputScript::parse(&text)
+    }
+
+    /// Parses a script with one event per line in the form `<frame> <event> [arguments]`.
+    /// Comments start with `#`, except in `text` events, where the rest of the line is the text:
+    ///
+    /// ```text
+    /// # Scroll right for a second, then click on something
+    /// 0 key_down Right
+    /// 60 key_up Right
+    /// 61 mouse_move 320 240
+    /// 62 mouse_down Left
+    /// 63 mouse_up Left
+    /// 64 mouse_wheel 0 -1
+    /// 90 text gg wp
+    /// 120 quit
+    /// ```
+    pub fn parse(text: &str) -> Result<InputScript> {
+        let mut script = InputScript::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = if line.split_whitespace().nth(1) == Some("text") {
+                line
+            } else {
+                line.split('#').next().unwrap().trim()
+            };
+            let (frame, event) = try!(parse_line(line)
+                .map_err(|message| ErrorKind::InvalidInputScript(index + 1, message)));
+            script.push(frame, event);
+        }
+        Ok(script)
+    }
+}
+
+fn parse_line(line: &str) -> ::std::result::Result<(u64, InputEvent), String> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let frame: u64 = try!(parts[0].parse().map_err(|_| format!("invalid frame number \"{}\"", parts[0])));
+    let arg = |index: usize| -> ::std::result::Result<&str, String> {
+        parts.get(index).cloned().ok_or(format!("missing argument for \"{}\"", parts.get(1).unwrap_or(&"")))
+    };
+    let coord = |index: usize| -> ::std::result::Result<i32, String> {
+        let value = try!(arg(index));
+        value.parse().map_err(|_| format!("invalid coordinate \"{}\"", value))
+    };
+
+    let event = match try!(arg(1)) {
+        "text" => {
+            // Everything after the event name (and the space following it) is the text
+            let start = line.find("text").unwrap() + "text".len() + 1;
+            InputEvent::TextInput(if start < line.len() { line[start..].into() } else { String::new() })
+        }
+        "key_down" => InputEvent::KeyDown(try!(try!(arg(2)).parse())),
+        "key_up" => InputEvent::KeyUp(try!(try!(arg(2)).parse())),
+        "mouse_move" => InputEvent::MouseMove(Vector2::new(try!(coord(2)), try!(coord(3)))),
+        "mouse_down" => InputEvent::MouseDown(try!(try!(arg(2)).parse())),
+        "mouse_up" => InputEvent::MouseUp(try!(try!(arg(2)).parse())),
+        "mouse_wheel" => InputEvent::MouseWheel(Vector2::new(try!(coord(2)), try!(coord(3)))),
+        "quit" => InputEvent::Quit,
+        other => return Err(format!("unknown event \"{}\"", other)),
+    };
+    Ok((frame, event))
+}
+
+/// Media that runs without a window, plays back scripted input, and renders with the software renderer
+pub struct HeadlessMedia {
+    renderer: SoftwareRenderer,
+    audio: Audio,
+    input_script: InputScript,
+    next_event: usize,
+    frame: u64,
+    max_frames: Option<u64>,
+    open: bool,
+    keys_pressed: HashSet<Key>,
+    key_states: KeyStates<Key>,
+    text_input: String,
+    mouse_position: Vector2<i32>,
+    mouse_buttons_pressed: HashSet<MouseButton>,
+    mouse_button_states: KeyStates<MouseButton>,
+    mouse_wheel: Vector2<i32>,
+}
+
+impl HeadlessMedia {
+    pub fn new(width: u32, height: u32, input_script: InputScript, max_frames: Option<u64>) -> HeadlessMedia {
+        HeadlessMedia {
+            renderer: SoftwareRenderer::new(width, height),
+            audio: Audio::new(Box::new(NullAudioBackend::new())).expect("failed to create null audio"),
+            input_script: input_script,
+            next_event: 0,
+            frame: 0,
+            max_frames: max_frames,
+            open: true,
+            keys_pressed: HashSet::new(),
+            key_states: KeyStates::new(HashMap::new()),
+            text_input: String::new(),
+            mouse_position: Vector2::new(0, 0),
+            mouse_buttons_pressed: HashSet::new(),
+            mouse_button_states: KeyStates::new(HashMap::new()),
+            mouse_wheel: Vector2::new(0, 0),
+        }
+    }
+
+    pub fn software_renderer<'a>(&'a self) -> &'a SoftwareRenderer {
+        &self.renderer
+    }
+
+    fn apply_event(&mut self, event: InputEvent) {
+        use self::InputEvent::*;
+        match event {
+            KeyDown(key) => {
+                self.keys_pressed.insert(key);
+            }
+            KeyUp(key) => {
+                self.keys_pressed.remove(&key);
+            }
+            TextInput(text) => self.text_input.push_str(&text),
+            MouseMove(position) => self.mouse_position = position,
+            MouseDown(button) => {
+                self.mouse_buttons_pressed.insert(button);
+            }
+            MouseUp(button) => {
+                self.mouse_buttons_pressed.remove(&button);
+            }
+            MouseWheel(delta) => {
+                self.mouse_wheel.x += delta.x;
+                self.mouse_wheel.y += delta.y;
+            }
+            Quit => self.open = false,
+        }
+    }
+}
+
+impl Media for HeadlessMedia {
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn update(&mut self) {
+        self.text_input.clear();
+        self.mouse_wheel = Vector2::new(0, 0);
+        while self.next_event < self.input_script.events.len() {
+            let (frame, event) = self.input_script.events[self.next_event].clone();
+            if frame > self.frame {
+                break;
+            }
+            self.apply_event(event);
+            self.next_event += 1;
+        }
+
+        self.key_states = update_key_states(&self.key_states, &self.keys_pressed);
+        self.mouse_button_states = update_key_states(&self.mouse_button_states, &self.mouse_buttons_pressed);
+
+        self.frame += 1;
+        if self.max_frames.map_or(false, |max_frames| self.frame >= max_frames) {
+            self.open = false;
+        }
+    }
+
+    fn key_states(&self) -> &KeyStates<Key> {
+        &self.key_states
+    }
+
+    fn text_input(&self) -> &str {
+        &self.text_input
+    }
+
+    fn mouse_position(&self) -> Vector2<i32> {
+        self.mouse_position
+    }
+
+    fn mouse_button_states<'a>(&'a self) -> &'a KeyStates<MouseButton> {
+        &self.mouse_button_states
+    }
+
+    fn mouse_wheel(&self) -> Vector2<i32> {
+        self.mouse_wheel
+    }
+
+    fn renderer<'a>(&'a mut self) -> &'a mut Renderer {
+        &mut self.renderer
+    }
+
+    fn viewport_size(&self) -> Vector2<u32> {
+        self.renderer.viewport_size()
+    }
+
+    fn audio<'a>(&'a mut self) -> &'a mut Audio {
+        &mut self.audio
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use key::{Key, KeyState, MouseButton};
+    use media::Media;
+    use nalgebra::Vector2;
+    use super::{HeadlessMedia, InputEvent, InputScript};
+
+    #[test]
+    fn test_parse_input_script() {
+        let script = InputScript::parse("# comment\n\n2 mouse_move 10 20\n0 key_down Space # hold\n3 quit\n\
+                                         2 text gg wp\n2 mouse_wheel 0 -1\n")
+            .unwrap();
+        assert_eq!(vec![(0, InputEvent::KeyDown(Key::Space)),
+                        (2, InputEvent::MouseMove(Vector2::new(10, 20))),
+                        (2, InputEvent::TextInput("gg wp".into())),
+                        (2, InputEvent::MouseWheel(Vector2::new(0, -1))),
+                        (3, InputEvent::Quit)],
+                   script.events);
+
+        assert!(InputScript::parse("0 key_down Escapade").is_err());
+        assert!(InputScript::parse("0 mouse_move 10").is_err());
+        assert!(InputScript::parse("soon quit").is_err());
+        assert!(InputScript::parse("0 dance").is_err());
+    }
+
+    #[test]
+    fn test_parse_input_script_text_with_hash() {
+        let script = InputScript::parse("# 0 text commented out
+1 text #1 team # gg
+2 quit # done
+").unwrap();
+        assert_eq!(vec![(1, InputEvent::TextInput("#1 team # gg".into())), (2, InputEvent::Quit)],
+                   script.events);
+    }
+
+    #[test]
+    fn test_headless_media_plays_back_input() {
+        let mut script = InputScript::new();
+        script.push(0, InputEvent::KeyDown(Key::Space));
+        script.push(1, InputEvent::MouseDown(MouseButton::Left));
+        script.push(2, InputEvent::KeyUp(Key::Space));
+        script.push(2, InputEvent::TextInput("hi".into()));
+        script.push(2, InputEvent::MouseWheel(Vector2::new(0, 2)));
+
+        let mut media = HeadlessMedia::new(64, 32, script, Some(4));
+        assert_eq!(Vector2::new(64, 32), media.viewport_size());
+
+        media.update();
+        assert_eq!(KeyState::TransitionDown, media.key_states().key_state(Key::Space));
+        media.update();
+        assert_eq!(KeyState::Down, media.key_states().key_state(Key::Space));
+        assert_eq!(KeyState::TransitionDown,
+                   media.mouse_button_states().key_state(MouseButton::Left));
+        media.update();
+        assert_eq!(KeyState::TransitionUp, media.key_states().key_state(Key::Space));
+        assert_eq!("hi", media.text_input());
+        assert_eq!(Vector2::new(0, 2), media.mouse_wheel());
+        assert!(media.is_open());
+        media.update();
+        assert_eq!("", media.text_input());
+        assert_eq!(Vector2::new(0, 0), media.mouse_wheel());
+        assert!(!media.is_open());
+    }
+}
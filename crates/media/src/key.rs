@@ -24,34 +24,136 @@ use sdl2;
 
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::str::FromStr;
 
-#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
-pub enum Key {
-    Up,
-    Down,
-    Left,
-    Right,
-    Space,
-    ShiftLeft,
-    CtrlLeft,
-    // Add keys as necessary
+macro_rules! keys {
+    ( $( $key:ident => $keycode:ident, )* ) => {
+        #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+        pub enum Key {
+            $( $key, )*
+        }
+
+        impl Key {
+            pub fn from_sdl(scancode: sdl2::keyboard::Scancode) -> Option<Key> {
+                sdl2::keyboard::Keycode::from_scancode(scancode).and_then(|keycode| {
+                    use sdl2::keyboard::Keycode as K;
+                    Some(match keycode {
+                        $( K::$keycode => Key::$key, )*
+                        _ => return None,
+                    })
+                })
+            }
+
+            /// The key's variant name, which is also what `from_str` accepts
+            pub fn name(&self) -> &'static str {
+                match *self {
+                    $( Key::$key => stringify!($key), )*
+                }
+            }
+        }
+
+        impl FromStr for Key {
+            type Err = String;
+
+            /// Parses the key from its variant name (e.g. "Space" or "ShiftLeft")
+            fn from_str(s: &str) -> Result<Key, String> {
+                match s {
+                    $( stringify!($key) => Ok(Key::$key), )*
+                    _ => Err(format!("unknown key \"{}\"", s)),
+                }
+            }
+        }
+    }
+}
+
+keys! {
+    A => A, B => B, C => C, D => D, E => E, F => F, G => G, H => H, I => I,
+    J => J, K => K, L => L, M => M, N => N, O => O, P => P, Q => Q, R => R,
+    S => S, T => T, U => U, V => V, W => W, X => X, Y => Y, Z => Z,
+
+    Num0 => Num0, Num1 => Num1, Num2 => Num2, Num3 => Num3, Num4 => Num4,
+    Num5 => Num5, Num6 => Num6, Num7 => Num7, Num8 => Num8, Num9 => Num9,
+
+    F1 => F1, F2 => F2, F3 => F3, F4 => F4, F5 => F5, F6 => F6,
+    F7 => F7, F8 => F8, F9 => F9, F10 => F10, F11 => F11, F12 => F12,
+
+    Escape => Escape,
+    Return => Return,
+    Backspace => Backspace,
+    Tab => Tab,
+    Space => Space,
+    Minus => Minus,
+    Equals => Equals,
+    LeftBracket => LeftBracket,
+    RightBracket => RightBracket,
+    Backslash => Backslash,
+    Semicolon => Semicolon,
+    Quote => Quote,
+    Backquote => Backquote,
+    Comma => Comma,
+    Period => Period,
+    Slash => Slash,
+    CapsLock => CapsLock,
+
+    PrintScreen => PrintScreen,
+    ScrollLock => ScrollLock,
+    Pause => Pause,
+    Insert => Insert,
+    Delete => Delete,
+    Home => Home,
+    End => End,
+    PageUp => PageUp,
+    PageDown => PageDown,
+
+    Up => Up,
+    Down => Down,
+    Left => Left,
+    Right => Right,
+
+    NumLock => NumLockClear,
+    Keypad0 => Kp0, Keypad1 => Kp1, Keypad2 => Kp2, Keypad3 => Kp3, Keypad4 => Kp4,
+    Keypad5 => Kp5, Keypad6 => Kp6, Keypad7 => Kp7, Keypad8 => Kp8, Keypad9 => Kp9,
+    KeypadDivide => KpDivide,
+    KeypadMultiply => KpMultiply,
+    KeypadMinus => KpMinus,
+    KeypadPlus => KpPlus,
+    KeypadEnter => KpEnter,
+    KeypadPeriod => KpPeriod,
+
+    ShiftLeft => LShift,
+    ShiftRight => RShift,
+    CtrlLeft => LCtrl,
+    CtrlRight => RCtrl,
+    AltLeft => LAlt,
+    AltRight => RAlt,
+    GuiLeft => LGui,
+    GuiRight => RGui,
 }
 
 impl Key {
-    pub fn from_sdl(scancode: sdl2::keyboard::Scancode) -> Option<Key> {
-        sdl2::keyboard::Keycode::from_scancode(scancode).and_then(|keycode| {
-            use sdl2::keyboard::Keycode as K;
-            Some(match keycode {
-                K::Up => Key::Up,
-                K::Down => Key::Down,
-                K::Left => Key::Left,
-                K::Right => Key::Right,
-                K::Space => Key::Space,
-                K::LShift => Key::ShiftLeft,
-                K::LCtrl => Key::CtrlLeft,
-                _ => return None,
-            })
-        })
+    /// The digit keys in order from 0 to 9 (used for control groups and menu hotkeys)
+    pub fn digit(digit: u8) -> Option<Key> {
+        use self::Key::*;
+        [Num0, Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9].get(digit as usize).cloned()
+    }
+}
+
+/// Which modifier keys are held down, regardless of side
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyModifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub gui: bool,
+}
+
+impl KeyModifiers {
+    pub fn none() -> KeyModifiers {
+        Default::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == KeyModifiers::none()
     }
 }
 
@@ -62,6 +164,19 @@ pub enum MouseButton {
     Right,
 }
 
+impl FromStr for MouseButton {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<MouseButton, String> {
+        Ok(match s {
+            "Left" => MouseButton::Left,
+            "Middle" => MouseButton::Middle,
+            "Right" => MouseButton::Right,
+            _ => return Err(format!("unknown mouse button \"{}\"", s)),
+        })
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum KeyState {
     TransitionDown,
@@ -115,3 +230,48 @@ impl<K: Eq + Hash> KeyStates<K> {
         }
     }
 }
+
+impl KeyStates<Key> {
+    pub fn modifiers(&self) -> KeyModifiers {
+        KeyModifiers {
+            shift: self.is_down(Key::ShiftLeft) || self.is_down(Key::ShiftRight),
+            ctrl: self.is_down(Key::CtrlLeft) || self.is_down(Key::CtrlRight),
+            alt: self.is_down(Key::AltLeft) || self.is_down(Key::AltRight),
+            gui: self.is_down(Key::GuiLeft) || self.is_down(Key::GuiRight),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::{Key, KeyModifiers, KeyState, KeyStates};
+
+    #[test]
+    fn test_key_names() {
+        for name in &["A", "Num7", "F12", "KeypadEnter", "ShiftLeft", "Space"] {
+            let key: Key = name.parse().unwrap();
+            assert_eq!(*name, key.name());
+        }
+        assert!("Shift".parse::<Key>().is_err());
+        assert_eq!(Some(Key::Num3), Key::digit(3));
+        assert_eq!(None, Key::digit(10));
+    }
+
+    #[test]
+    fn test_modifiers() {
+        let mut states = HashMap::new();
+        states.insert(Key::ShiftRight, KeyState::TransitionDown);
+        states.insert(Key::CtrlLeft, KeyState::TransitionUp);
+        states.insert(Key::AltLeft, KeyState::Down);
+        let modifiers = KeyStates::new(states).modifiers();
+        assert_eq!(KeyModifiers {
+                       shift: true,
+                       ctrl: false,
+                       alt: true,
+                       gui: false,
+                   },
+                   modifiers);
+        assert!(KeyStates::new(HashMap::new()).modifiers().is_empty());
+    }
+}
@@ -26,27 +26,51 @@ extern crate error_chain;
 
 extern crate sdl2;
 extern crate nalgebra;
+extern crate png;
 
 extern crate chariot_types as types;
 
+mod audio;
+mod display;
 mod error;
+mod headless_media;
 mod key;
 mod media;
+mod mixer;
+mod primitives;
 mod renderer;
+mod rgba_image;
+mod sdl_audio_backend;
+mod sdl_renderer;
+mod sink_audio_backend;
+mod software_renderer;
+mod sound;
 mod texture;
 mod texture_builder;
 
+pub use audio::{Audio, AudioBackend};
+pub use display::{DisplaySettings, ScalingMode, WindowMode};
 pub use error::ChainErr;
 pub use error::Error;
 pub use error::ErrorKind;
 pub use error::Result;
 
-pub use key::{Key, KeyState, KeyStates, MouseButton};
+pub use key::{Key, KeyModifiers, KeyState, KeyStates, MouseButton};
+pub use headless_media::{HeadlessMedia, InputEvent, InputScript};
 pub use media::{Media, MediaRef};
 
-pub use media::create_media;
+pub use media::{create_headless_media, create_media};
 
+pub use mixer::{DEFAULT_MAX_VOICES, MIXER_CHANNELS, MIXER_SAMPLE_RATE, Mixer};
+
+pub use primitives::{ellipse_polygon, polygon_outline, polygon_spans};
 pub use renderer::Renderer;
+pub use rgba_image::RgbaImage;
+pub use sdl_audio_backend::SdlAudioBackend;
+pub use sdl_renderer::SdlRenderer;
+pub use sink_audio_backend::{NullAudioBackend, WavAudioBackend};
+pub use software_renderer::{DrawCall, SoftwareRenderer};
+pub use sound::Sound;
 
 pub use texture::Texture;
-pub use texture_builder::TextureBuilder;
+pub use texture_builder::{TextureBuilder, to_rgba};
@@ -19,13 +19,19 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use audio::Audio;
+use display::DisplaySettings;
 use error::Result;
-use key::{Key, KeyState, KeyStates, MouseButton};
+use key::{Key, KeyModifiers, KeyState, KeyStates, MouseButton};
 
+use headless_media::{HeadlessMedia, InputScript};
 use nalgebra::Vector2;
 use renderer::Renderer;
 
 use sdl2;
+use sdl_audio_backend::SdlAudioBackend;
+use sdl_renderer::SdlRenderer;
+use sink_audio_backend::NullAudioBackend;
 use std::cell::RefCell;
 
 use std::collections::{HashMap, HashSet};
@@ -38,50 +44,75 @@ pub trait Media {
 
     fn key_states(&self) -> &KeyStates<Key>;
 
+    fn key_modifiers(&self) -> KeyModifiers {
+        self.key_states().modifiers()
+    }
+
+    /// UTF-8 text typed since the last update (with keyboard layout and IME composition applied)
+    fn text_input(&self) -> &str;
+
+    /// Position of the mouse in the same (unscaled) coordinates as the viewport
     fn mouse_position(&self) -> Vector2<i32>;
     fn mouse_button_states<'a>(&'a self) -> &'a KeyStates<MouseButton>;
 
+    /// How far the mouse wheel was scrolled since the last update; positive y is away from the user
+    fn mouse_wheel(&self) -> Vector2<i32>;
+
     fn renderer<'a>(&'a mut self) -> &'a mut Renderer;
+
+    /// Size of the area the game draws in; this changes when the window is resized
     fn viewport_size(&self) -> Vector2<u32>;
+
+    fn audio<'a>(&'a mut self) -> &'a mut Audio;
 }
 
 pub type MediaRef = Rc<RefCell<Box<Media>>>;
 
-pub fn create_media(width: u32, height: u32, title: &str) -> Result<MediaRef> {
-    SdlMedia::new(width, height, title).map(|m| Rc::new(RefCell::new(Box::new(m) as Box<Media>)))
+pub fn create_media(settings: &DisplaySettings, title: &str) -> Result<MediaRef> {
+    SdlMedia::new(settings, title).map(|m| Rc::new(RefCell::new(Box::new(m) as Box<Media>)))
+}
+
+/// Creates media that doesn't need a display: rendering goes to an offscreen buffer and
+/// input comes from the script. It closes after `max_frames` updates if that's given.
+pub fn create_headless_media(width: u32,
+                             height: u32,
+                             input_script: InputScript,
+                             max_frames: Option<u64>)
+                             -> MediaRef {
+    let media = HeadlessMedia::new(width, height, input_script, max_frames);
+    Rc::new(RefCell::new(Box::new(media) as Box<Media>))
 }
 
 struct SdlMedia {
     context: sdl2::Sdl,
-    renderer: Renderer,
+    renderer: SdlRenderer,
+    audio: Audio,
     open: bool,
     keys_pressed: HashSet<Key>,
     key_states: KeyStates<Key>,
+    text_input: String,
     mouse_position: Vector2<i32>,
     mouse_button_states: KeyStates<MouseButton>,
-    initial_width: u32,
-    initial_height: u32,
-    scale_x: f32,
-    scale_y: f32,
+    mouse_wheel: Vector2<i32>,
 }
 
 impl SdlMedia {
-    fn new(width: u32, height: u32, title: &str) -> Result<SdlMedia> {
+    fn new(settings: &DisplaySettings, title: &str) -> Result<SdlMedia> {
         let mut context = try!(sdl2::init());
-        let renderer = try!(Renderer::new(&mut context, width, height, title));
+        let renderer = try!(SdlRenderer::new(&mut context, settings, title));
+        let audio = create_audio(&context);
 
         Ok(SdlMedia {
             context: context,
             renderer: renderer,
+            audio: audio,
             open: true,
             keys_pressed: HashSet::new(),
             key_states: KeyStates::new(HashMap::new()),
+            text_input: String::new(),
             mouse_position: Vector2::new(0, 0),
             mouse_button_states: KeyStates::new(HashMap::new()),
-            initial_width: width,
-            initial_height: height,
-            scale_x: 1f32,
-            scale_y: 1f32,
+            mouse_wheel: Vector2::new(0, 0),
         })
     }
 }
@@ -103,15 +134,23 @@ impl Media for SdlMedia {
             }
         };
 
+        self.text_input.clear();
+        self.mouse_wheel = Vector2::new(0, 0);
         for event in event_pump.poll_iter() {
             match event {
                 Event::Quit { .. } => {
                     self.open = false;
                 }
-                Event::Window { win_event: WindowEvent::Resized(data1, data2), .. } => {
-                    self.scale_x = data1 as f32 / self.initial_width as f32;
-                    self.scale_y = data2 as f32 / self.initial_height as f32;
-                    self.renderer.set_scale(self.scale_x, self.scale_y);
+                Event::Window { win_event: WindowEvent::Resized(..), .. } |
+                Event::Window { win_event: WindowEvent::SizeChanged(..), .. } => {
+                    self.renderer.update_scale();
+                }
+                Event::TextInput { text, .. } => {
+                    self.text_input.push_str(&text);
+                }
+                Event::MouseWheel { x, y, .. } => {
+                    self.mouse_wheel.x += x;
+                    self.mouse_wheel.y += y;
                 }
                 _ => {}
             }
@@ -134,15 +173,24 @@ impl Media for SdlMedia {
         &self.key_states
     }
 
+    fn text_input(&self) -> &str {
+        &self.text_input
+    }
+
     fn mouse_position(&self) -> Vector2<i32> {
-        Vector2::new((self.mouse_position.x as f32 / self.scale_x) as i32,
-                     (self.mouse_position.y as f32 / self.scale_y) as i32)
+        let (scale_x, scale_y) = self.renderer.scale();
+        Vector2::new((self.mouse_position.x as f32 / scale_x) as i32,
+                     (self.mouse_position.y as f32 / scale_y) as i32)
     }
 
     fn mouse_button_states<'a>(&'a self) -> &'a KeyStates<MouseButton> {
         &self.mouse_button_states
     }
 
+    fn mouse_wheel(&self) -> Vector2<i32> {
+        self.mouse_wheel
+    }
+
     fn renderer<'a>(&'a mut self) -> &'a mut Renderer {
         &mut self.renderer
     }
@@ -150,11 +198,24 @@ impl Media for SdlMedia {
     fn viewport_size(&self) -> Vector2<u32> {
         self.renderer.viewport_size()
     }
+
+    fn audio<'a>(&'a mut self) -> &'a mut Audio {
+        &mut self.audio
+    }
+}
+
+/// Plays audio on the default device, or nowhere if there isn't one that works
+fn create_audio(context: &sdl2::Sdl) -> Audio {
+    let device_audio = SdlAudioBackend::new(context).and_then(|backend| Audio::new(Box::new(backend)));
+    device_audio.unwrap_or_else(|err| {
+        println!("Audio is disabled since the audio device failed to open: {}", err);
+        Audio::new(Box::new(NullAudioBackend::new())).expect("failed to create null audio")
+    })
 }
 
-fn update_key_states<K: Eq + Hash + Copy>(key_states: &KeyStates<K>,
-                                          pressed_keys: &HashSet<K>)
-                                          -> KeyStates<K> {
+pub fn update_key_states<K: Eq + Hash + Copy>(key_states: &KeyStates<K>,
+                                              pressed_keys: &HashSet<K>)
+                                              -> KeyStates<K> {
     use KeyState::*;
 
     let mut new_states: HashMap<K, KeyState> = HashMap::new();
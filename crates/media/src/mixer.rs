@@ -0,0 +1,241 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use nalgebra::Vector2;
+
+use sound::Sound;
+use std::cmp;
+use std::sync::Arc;
+
+/// Sample rate the mixer outputs at (and most of the original game's sounds are recorded at)
+pub const MIXER_SAMPLE_RATE: u32 = 22050;
+
+/// The mixer always outputs interleaved stereo
+pub const MIXER_CHANNELS: u16 = 2;
+
+/// How many sounds can play at once before quieter ones start getting dropped
+pub const DEFAULT_MAX_VOICES: usize = 16;
+
+struct Voice {
+    sound: Arc<Sound>,
+    position: f64,
+    step: f64,
+    left_gain: f32,
+    right_gain: f32,
+}
+
+impl Voice {
+    fn loudness(&self) -> f32 {
+        self.left_gain + self.right_gain
+    }
+}
+
+/// Mixes playing sounds into a stereo stream. Positional sounds are panned and attenuated based
+/// on how far they are from the listener (the center of the screen), and fall silent beyond the
+/// hearing radius.
+pub struct Mixer {
+    sample_rate: u32,
+    max_voices: usize,
+    voices: Vec<Voice>,
+    listener: Vector2<i32>,
+    hearing_radius: i32,
+    volume: f32,
+    mix_buffer: Vec<i32>,
+}
+
+impl Mixer {
+    pub fn new(sample_rate: u32, max_voices: usize) -> Mixer {
+        Mixer {
+            sample_rate: sample_rate,
+            max_voices: max_voices,
+            voices: Vec::new(),
+            listener: Vector2::new(0, 0),
+            hearing_radius: 1024,
+            volume: 1.0,
+            mix_buffer: Vec::new(),
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn voice_count(&self) -> usize {
+        self.voices.len()
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+    }
+
+    pub fn set_listener(&mut self, position: Vector2<i32>, hearing_radius: i32) {
+        self.listener = position;
+        self.hearing_radius = cmp::max(1, hearing_radius);
+    }
+
+    /// Starts playing a sound, optionally at a position (in the same space as the listener).
+    /// Returns false if the sound won't be heard, either because it's out of hearing range or
+    /// because all the voices are taken by louder sounds.
+    pub fn play(&mut self, sound: Arc<Sound>, position: Option<Vector2<i32>>, volume: f32) -> bool {
+        let (left_gain, right_gain) = self.gains(position, volume);
+        if left_gain + right_gain <= 0.0 || sound.frame_count() == 0 {
+            return false;
+        }
+
+        let voice = Voice {
+            step: sound.sample_rate as f64 / self.sample_rate as f64,
+            sound: sound,
+            position: 0.0,
+            left_gain: left_gain,
+            right_gain: right_gain,
+        };
+
+        if self.voices.len() < self.max_voices {
+            self.voices.push(voice);
+            return true;
+        }
+
+        // Steal the quietest voice if the new sound is louder than it
+        let quietest = self.voices
+            .iter()
+            .enumerate()
+            .fold(None, |quietest: Option<(usize, f32)>, (index, v)| match quietest {
+                Some((_, loudness)) if loudness <= v.loudness() => quietest,
+                _ => Some((index, v.loudness())),
+            });
+        match quietest {
+            Some((index, loudness)) if loudness < voice.loudness() => {
+                self.voices[index] = voice;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn stop_all(&mut self) {
+        self.voices.clear();
+    }
+
+    /// Fills the buffer with interleaved stereo samples, advancing every playing sound
+    pub fn mix(&mut self, out: &mut [i16]) {
+        let frames = out.len() / MIXER_CHANNELS as usize;
+        self.mix_buffer.clear();
+        self.mix_buffer.resize(frames * 2, 0);
+
+        for voice in &mut self.voices {
+            let channels = voice.sound.channels as usize;
+            let frame_count = voice.sound.frame_count();
+            for frame in 0..frames {
+                let src_frame = voice.position as usize;
+                if src_frame >= frame_count {
+                    break;
+                }
+                let left = voice.sound.samples[src_frame * channels] as f32;
+                let right = if channels > 1 {
+                    voice.sound.samples[src_frame * channels + 1] as f32
+                } else {
+                    left
+                };
+                self.mix_buffer[frame * 2] += (left * voice.left_gain) as i32;
+                self.mix_buffer[frame * 2 + 1] += (right * voice.right_gain) as i32;
+                voice.position += voice.step;
+            }
+        }
+        self.voices.retain(|voice| (voice.position as usize) < voice.sound.frame_count());
+
+        for (dst, &src) in out.iter_mut().zip(self.mix_buffer.iter()) {
+            *dst = cmp::max(-32768, cmp::min(32767, src)) as i16;
+        }
+        for dst in out.iter_mut().skip(frames * 2) {
+            *dst = 0;
+        }
+    }
+
+    fn gains(&self, position: Option<Vector2<i32>>, volume: f32) -> (f32, f32) {
+        let volume = volume * self.volume;
+        match position {
+            None => (volume, volume),
+            Some(position) => {
+                let offset = position - self.listener;
+                let radius = self.hearing_radius as f32;
+                let distance = ((offset.x as f32).powi(2) + (offset.y as f32).powi(2)).sqrt();
+                let attenuation = (1.0 - distance / radius).max(0.0);
+                let pan = (offset.x as f32 / radius).max(-1.0).min(1.0);
+                (volume * attenuation * (1.0 - pan.max(0.0)), volume * attenuation * (1.0 + pan.min(0.0)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector2;
+    use sound::Sound;
+    use std::sync::Arc;
+    use super::Mixer;
+
+    fn sound(sample_rate: u32, samples: Vec<i16>) -> Arc<Sound> {
+        Arc::new(Sound {
+            sample_rate: sample_rate,
+            channels: 1,
+            samples: samples,
+        })
+    }
+
+    #[test]
+    fn test_mix_and_resample() {
+        let mut mixer = Mixer::new(100, 4);
+        assert!(mixer.play(sound(50, vec![1000, 2000]), None, 1.0));
+        assert!(mixer.play(sound(100, vec![10, 10, 10, 10, 10]), None, 1.0));
+
+        let mut out = [0i16; 12];
+        mixer.mix(&mut out);
+        assert_eq!([1010, 1010, 1010, 1010, 2010, 2010, 2010, 2010, 10, 10, 0, 0], out);
+        assert_eq!(0, mixer.voice_count());
+    }
+
+    #[test]
+    fn test_positional_sounds() {
+        let mut mixer = Mixer::new(100, 4);
+        mixer.set_listener(Vector2::new(100, 100), 100);
+
+        assert!(!mixer.play(sound(100, vec![1000]), Some(Vector2::new(300, 100)), 1.0));
+        assert!(mixer.play(sound(100, vec![1000]), Some(Vector2::new(150, 100)), 1.0));
+
+        let mut out = [0i16; 2];
+        mixer.mix(&mut out);
+        assert_eq!([250, 500], out);
+    }
+
+    #[test]
+    fn test_voice_limit_keeps_loudest() {
+        let mut mixer = Mixer::new(100, 2);
+        assert!(mixer.play(sound(100, vec![100]), None, 0.5));
+        assert!(mixer.play(sound(100, vec![100]), None, 0.25));
+        assert!(!mixer.play(sound(100, vec![100]), None, 0.1));
+        assert!(mixer.play(sound(100, vec![100]), None, 1.0));
+        assert_eq!(2, mixer.voice_count());
+
+        let mut out = [0i16; 2];
+        mixer.mix(&mut out);
+        assert_eq!([150, 150], out);
+    }
+}
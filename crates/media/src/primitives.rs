@@ -0,0 +1,129 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Rasterisation of the primitives that renderers don't support directly into ones they do
+
+use nalgebra::Vector2;
+use std::cmp;
+use std::f32::consts::PI;
+use types::Rect;
+
+/// Fills a polygon (even-odd rule) with one rect per row. A pixel is filled if its center
+/// is inside of the polygon.
+pub fn polygon_spans(points: &[Vector2<i32>]) -> Vec<Rect> {
+    let mut spans = Vec::new();
+    if points.len() < 3 {
+        return spans;
+    }
+
+    let min_y = points.iter().map(|p| p.y).min().unwrap();
+    let max_y = points.iter().map(|p| p.y).max().unwrap();
+    let mut crossings = Vec::new();
+    for y in min_y..max_y {
+        let center_y = y as f32 + 0.5;
+        crossings.clear();
+        for i in 0..points.len() {
+            let (first, second) = (points[i], points[(i + 1) % points.len()]);
+            let (top, bottom) = if first.y < second.y { (first, second) } else { (second, first) };
+            if center_y >= top.y as f32 && center_y < bottom.y as f32 {
+                let t = (center_y - top.y as f32) / (bottom.y - top.y) as f32;
+                crossings.push(top.x as f32 + t * (bottom.x - top.x) as f32);
+            }
+        }
+        crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        for pair in crossings.chunks(2) {
+            if pair.len() == 2 {
+                let left = (pair[0] - 0.5).ceil() as i32;
+                let right = (pair[1] - 0.5).ceil() as i32;
+                if right > left {
+                    spans.push(Rect::of(left, y, right - left, 1));
+                }
+            }
+        }
+    }
+    spans
+}
+
+/// Approximates an ellipse with a polygon, using more points for bigger ellipses
+pub fn ellipse_polygon(center: Vector2<i32>, radii: Vector2<i32>) -> Vec<Vector2<i32>> {
+    let point_count = cmp::max(8, cmp::min(64, (radii.x.abs() + radii.y.abs()) / 2));
+    (0..point_count)
+        .map(|i| {
+            let angle = 2.0 * PI * i as f32 / point_count as f32;
+            Vector2::new(center.x + (radii.x as f32 * angle.cos()).round() as i32,
+                         center.y + (radii.y as f32 * angle.sin()).round() as i32)
+        })
+        .collect()
+}
+
+/// Line segments around the outside of a polygon
+pub fn polygon_outline(points: &[Vector2<i32>]) -> Vec<[Vector2<i32>; 2]> {
+    match points.len() {
+        0 | 1 => Vec::new(),
+        2 => vec![[points[0], points[1]]],
+        _ => (0..points.len()).map(|i| [points[i], points[(i + 1) % points.len()]]).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector2;
+    use super::{ellipse_polygon, polygon_outline, polygon_spans};
+    use types::Rect;
+
+    #[test]
+    fn test_polygon_spans() {
+        let square = [Vector2::new(0, 0), Vector2::new(4, 0), Vector2::new(4, 4), Vector2::new(0, 4)];
+        assert_eq!(vec![Rect::of(0, 0, 4, 1), Rect::of(0, 1, 4, 1), Rect::of(0, 2, 4, 1),
+                        Rect::of(0, 3, 4, 1)],
+                   polygon_spans(&square));
+
+        // An iso tile shaped diamond: widest in the middle, symmetric top to bottom
+        let diamond = [Vector2::new(0, 2), Vector2::new(8, -2), Vector2::new(16, 2), Vector2::new(8, 6)];
+        let spans = polygon_spans(&diamond);
+        assert_eq!(8, spans.len());
+        assert_eq!(Rect::of(7, -2, 2, 1), spans[0]);
+        assert_eq!(Rect::of(1, 1, 14, 1), spans[3]);
+        assert_eq!(Rect::of(1, 2, 14, 1), spans[4]);
+        assert_eq!(Rect::of(7, 5, 2, 1), spans[7]);
+
+        assert!(polygon_spans(&square[0..2]).is_empty());
+    }
+
+    #[test]
+    fn test_ellipse_polygon() {
+        let points = ellipse_polygon(Vector2::new(10, 20), Vector2::new(8, 4));
+        assert_eq!(8, points.len());
+        assert_eq!(Vector2::new(18, 20), points[0]);
+        assert_eq!(Vector2::new(10, 24), points[2]);
+        assert_eq!(Vector2::new(2, 20), points[4]);
+        assert_eq!(Vector2::new(10, 16), points[6]);
+    }
+
+    #[test]
+    fn test_polygon_outline() {
+        let triangle = [Vector2::new(0, 0), Vector2::new(4, 0), Vector2::new(0, 4)];
+        assert_eq!(3, polygon_outline(&triangle).len());
+        assert_eq!([triangle[2], triangle[0]], polygon_outline(&triangle)[2]);
+        assert!(polygon_outline(&triangle[0..1]).is_empty());
+    }
+}
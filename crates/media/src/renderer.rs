@@ -22,108 +22,51 @@
 use error::Result;
 
 use nalgebra::Vector2;
+use rgba_image::RgbaImage;
 
-use sdl2;
-use texture::{self, SdlTexture, Texture};
+use texture::Texture;
 use types::{Color, Rect};
 
-// Separate so that it's not exported with the crate
-pub trait SdlRenderer {
-    fn create_texture_from_surface(&mut self, surface: sdl2::surface::Surface) -> Result<Texture>;
-}
-
-pub struct Renderer {
-    camera_pos: Vector2<i32>,
-    _video: sdl2::VideoSubsystem,
-    renderer: sdl2::render::Renderer<'static>,
-}
-
-impl Renderer {
-    pub fn new(sdl_context: &mut sdl2::Sdl, width: u32, height: u32, title: &str) -> Result<Renderer> {
-        let video = try!(sdl_context.video());
-        let mut window = try!(video.window(title, width, height)
-            .position_centered()
-            .resizable()
-            .opengl()
-            .build());
-        window.set_minimum_size(width, height).expect("set window min size");
-
-        let renderer = try!(window.renderer().present_vsync().build());
-        println!("Renderer initialized with {:#?}", renderer.info());
-
-        Ok(Renderer {
-            camera_pos: Vector2::new(0, 0),
-            _video: video,
-            renderer: renderer,
-        })
-    }
-
-    pub fn present(&mut self) {
-        self.set_render_color(Color::rgba(0, 0, 0, 0));
-        self.renderer.present();
-        self.renderer.clear();
-    }
-
-    pub fn viewport_size(&self) -> Vector2<u32> {
-        let size = self.renderer.window().unwrap().size();
-        Vector2::new(size.0, size.1)
-    }
-
-    pub fn set_scale(&mut self, scale_x: f32, scale_y: f32) {
-        self.renderer.set_scale(scale_x, scale_y).expect("set render scale");
-    }
-
-    pub fn set_camera_position(&mut self, position: &Vector2<i32>) {
-        self.camera_pos = *position;
+/// Draws textures and primitives to the screen (or an offscreen buffer). All positions
+/// are in world space and get offset by the camera position.
+pub trait Renderer {
+    /// Creates a blank (fully transparent) RGBA texture that can be updated later
+    fn create_texture(&mut self, width: u32, height: u32) -> Result<Texture>;
+
+    fn present(&mut self);
+    fn viewport_size(&self) -> Vector2<u32>;
+    fn set_scale(&mut self, scale_x: f32, scale_y: f32);
+    fn set_camera_position(&mut self, position: &Vector2<i32>);
+
+    fn render_texture(&mut self,
+                      texture: &Texture,
+                      src_rect: Option<Rect>,
+                      dst_rect: Rect,
+                      flip_horizontal: bool,
+                      flip_vertical: bool);
+
+    /// Multiplies the color (and alpha) of textures rendered after this by the tint;
+    /// white leaves them as they are
+    fn set_texture_tint(&mut self, tint: Color);
+
+    /// Sets the color primitives are drawn with; they're alpha blended with what's under them
+    fn set_render_color(&mut self, color: Color);
+    fn render_rect(&mut self, rect: Rect);
+    fn render_line(&mut self, first: Vector2<i32>, second: Vector2<i32>);
+    fn fill_rects(&mut self, rects: &[Rect]);
+
+    fn render_rects(&mut self, rects: &[Rect]) {
+        for rect in rects {
+            self.render_rect(*rect);
+        }
     }
 
-    pub fn render_texture(&mut self,
-                          texture: &Texture,
-                          src_rect: Option<Rect>,
-                          mut dst_rect: Rect,
-                          flip_horizontal: bool,
-                          flip_vertical: bool) {
-        dst_rect.x -= self.camera_pos.x;
-        dst_rect.y -= self.camera_pos.y;
-        self.renderer
-            .copy_ex(texture.sdl_texture(),
-                     src_rect.map(|r| r.into()),
-                     Some(dst_rect.into()),
-                     0.0,
-                     None,
-                     flip_horizontal,
-                     flip_vertical)
-            .unwrap_or_else(|err| {
-                println!("Failed to render texture: {}", err);
-            });
+    fn render_lines(&mut self, lines: &[[Vector2<i32>; 2]]) {
+        for line in lines {
+            self.render_line(line[0], line[1]);
+        }
     }
 
-    pub fn set_render_color(&mut self, color: Color) {
-        self.renderer.set_draw_color(color.into());
-    }
-
-    pub fn render_rect(&mut self, mut rect: Rect) {
-        rect.x -= self.camera_pos.x;
-        rect.y -= self.camera_pos.y;
-        self.renderer.draw_rect(rect.into()).expect("Failed to draw rect");
-    }
-
-    pub fn render_line(&mut self, mut first: Vector2<i32>, mut second: Vector2<i32>) {
-        first.x -= self.camera_pos.x;
-        first.y -= self.camera_pos.y;
-        second.x -= self.camera_pos.x;
-        second.y -= self.camera_pos.y;
-        self.renderer
-            .draw_line(sdl2::rect::Point::new(first.x, first.y),
-                       sdl2::rect::Point::new(second.x, second.y))
-            .expect("Failed to draw line");
-    }
-}
-
-impl SdlRenderer for Renderer {
-    fn create_texture_from_surface(&mut self, surface: sdl2::surface::Surface) -> Result<Texture> {
-        let (width, height) = (surface.width(), surface.height());
-        let sdl_texture = try!(self.renderer.create_texture_from_surface(surface));
-        Ok(texture::create_texture(sdl_texture, width, height))
-    }
+    /// Reads back everything rendered since the last present (call it before presenting)
+    fn capture(&mut self) -> Result<RgbaImage>;
 }
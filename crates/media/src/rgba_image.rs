@@ -0,0 +1,138 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::Result;
+
+use png::{self, HasParameters};
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::mem;
+use std::path::Path;
+use types::Color;
+
+/// An image with four bytes per pixel in R, G, B, A order, such as a captured frame
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RgbaImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> RgbaImage {
+        assert_eq!((width * height * 4) as usize, pixels.len());
+        RgbaImage {
+            width: width,
+            height: height,
+            pixels: pixels,
+        }
+    }
+
+    /// Creates an image from pixels in the RGBA8888 texture format (native endian packed u32s)
+    pub fn from_rgba8888(width: u32, height: u32, packed_pixels: &[u8]) -> RgbaImage {
+        let mut pixels = Vec::with_capacity(packed_pixels.len());
+        for texel in packed_pixels.chunks(4) {
+            let bytes = [texel[0], texel[1], texel[2], texel[3]];
+            let packed = unsafe { mem::transmute::<[u8; 4], u32>(bytes) };
+            pixels.extend_from_slice(&[(packed >> 24) as u8,
+                                       (packed >> 16) as u8,
+                                       (packed >> 8) as u8,
+                                       packed as u8]);
+        }
+        RgbaImage::new(width, height, pixels)
+    }
+
+    pub fn read_png<P: AsRef<Path>>(file_name: P) -> Result<RgbaImage> {
+        let decoder = png::Decoder::new(try!(File::open(file_name.as_ref())));
+        let (info, mut reader) = try!(decoder.read_info());
+        if info.color_type != png::ColorType::RGBA || info.bit_depth != png::BitDepth::Eight {
+            return Err(format!("{} isn't an 8-bit RGBA PNG", file_name.as_ref().display()).into());
+        }
+        let mut pixels = vec![0u8; info.buffer_size()];
+        try!(reader.next_frame(&mut pixels));
+        Ok(RgbaImage::new(info.width, info.height, pixels))
+    }
+
+    pub fn write_png<P: AsRef<Path>>(&self, file_name: P) -> Result<()> {
+        let file = try!(File::create(file_name.as_ref()));
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set(png::ColorType::RGBA).set(png::BitDepth::Eight);
+        let mut writer = try!(encoder.write_header());
+        try!(writer.write_image_data(&self.pixels));
+        Ok(())
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels<'a>(&'a self) -> &'a [u8] {
+        &self.pixels
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        let index = ((y * self.width + x) * 4) as usize;
+        let p = &self.pixels[index..index + 4];
+        Color::rgba(p[0], p[1], p[2], p[3])
+    }
+
+    /// Counts the pixels that differ from the other image by more than `tolerance` in any channel.
+    /// Returns None if the images aren't the same size.
+    pub fn count_differing_pixels(&self, other: &RgbaImage, tolerance: u8) -> Option<usize> {
+        if self.width != other.width || self.height != other.height {
+            return None;
+        }
+        let differs = |(a, b): (&[u8], &[u8])| {
+            a.iter().zip(b.iter()).any(|(&a, &b)| (a as i16 - b as i16).abs() > tolerance as i16)
+        };
+        Some(self.pixels.chunks(4).zip(other.pixels.chunks(4)).filter(|&pair| differs(pair)).count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem;
+    use super::RgbaImage;
+    use types::Color;
+
+    #[test]
+    fn test_from_rgba8888() {
+        let packed = unsafe { mem::transmute::<u32, [u8; 4]>(0x11223344) };
+        let image = RgbaImage::from_rgba8888(1, 1, &packed);
+        assert_eq!(&[0x11, 0x22, 0x33, 0x44], image.pixels());
+        assert_eq!(Color::rgba(0x11, 0x22, 0x33, 0x44), image.pixel(0, 0));
+    }
+
+    #[test]
+    fn test_count_differing_pixels() {
+        let a = RgbaImage::new(2, 1, vec![10, 10, 10, 255, 0, 0, 0, 0]);
+        let b = RgbaImage::new(2, 1, vec![12, 10, 10, 255, 0, 0, 50, 0]);
+        assert_eq!(Some(0), a.count_differing_pixels(&a, 0));
+        assert_eq!(Some(2), a.count_differing_pixels(&b, 0));
+        assert_eq!(Some(1), a.count_differing_pixels(&b, 2));
+        assert_eq!(None, a.count_differing_pixels(&RgbaImage::new(1, 1, vec![0; 4]), 0));
+    }
+}
@@ -0,0 +1,83 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use audio::AudioBackend;
+use error::Result;
+
+use mixer::{MIXER_CHANNELS, MIXER_SAMPLE_RATE, Mixer};
+use sdl2;
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+use std::sync::{Arc, Mutex};
+
+/// Samples per channel in each buffer the device asks for; smaller means less latency
+const DEVICE_BUFFER_FRAMES: u16 = 1024;
+
+struct MixerCallback {
+    mixer: Arc<Mutex<Mixer>>,
+}
+
+impl AudioCallback for MixerCallback {
+    type Channel = i16;
+
+    fn callback(&mut self, out: &mut [i16]) {
+        match self.mixer.lock() {
+            Ok(mut mixer) => mixer.mix(out),
+            Err(_) => {
+                for sample in out.iter_mut() {
+                    *sample = 0;
+                }
+            }
+        }
+    }
+}
+
+/// Plays audio on the default output device. SDL pulls samples from the mixer on its own thread.
+pub struct SdlAudioBackend {
+    audio: sdl2::AudioSubsystem,
+    device: Option<AudioDevice<MixerCallback>>,
+}
+
+impl SdlAudioBackend {
+    pub fn new(sdl_context: &sdl2::Sdl) -> Result<SdlAudioBackend> {
+        Ok(SdlAudioBackend {
+            audio: try!(sdl_context.audio()),
+            device: None,
+        })
+    }
+}
+
+impl AudioBackend for SdlAudioBackend {
+    fn start(&mut self, mixer: Arc<Mutex<Mixer>>) -> Result<()> {
+        let desired_spec = AudioSpecDesired {
+            freq: Some(MIXER_SAMPLE_RATE as i32),
+            channels: Some(MIXER_CHANNELS as u8),
+            samples: Some(DEVICE_BUFFER_FRAMES),
+        };
+        let device = try!(self.audio.open_playback(None, &desired_spec, |_| MixerCallback { mixer: mixer }));
+        device.resume();
+        self.device = Some(device);
+        Ok(())
+    }
+
+    fn advance(&mut self, _frames: usize) -> Result<()> {
+        Ok(())
+    }
+}
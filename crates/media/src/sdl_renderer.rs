@@ -0,0 +1,217 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use display::{self, DisplaySettings, WindowMode};
+use error::Result;
+
+use nalgebra::Vector2;
+use renderer::Renderer;
+use rgba_image::RgbaImage;
+
+use sdl2;
+use texture::{self, SdlTexture, Texture};
+use types::{Color, Rect};
+
+pub struct SdlRenderer {
+    camera_pos: Vector2<i32>,
+    texture_tint: Color,
+    settings: DisplaySettings,
+    scale: (f32, f32),
+    _video: sdl2::VideoSubsystem,
+    renderer: sdl2::render::Renderer<'static>,
+}
+
+impl SdlRenderer {
+    pub fn new(sdl_context: &mut sdl2::Sdl, settings: &DisplaySettings, title: &str) -> Result<SdlRenderer> {
+        let video = try!(sdl_context.video());
+        let (width, height) = (settings.resolution.x, settings.resolution.y);
+        let mut window_builder = video.window(title, width, height);
+        window_builder.position_centered().opengl();
+        match settings.window_mode {
+            WindowMode::Windowed => window_builder.resizable(),
+            WindowMode::Fullscreen => window_builder.fullscreen(),
+            WindowMode::Borderless => window_builder.fullscreen_desktop(),
+        };
+        let mut window = try!(window_builder.build());
+        if settings.window_mode == WindowMode::Windowed {
+            window.set_minimum_size(width, height).expect("set window min size");
+        }
+
+        let mut renderer = try!(window.renderer().present_vsync().build());
+        renderer.set_blend_mode(sdl2::render::BlendMode::Blend);
+        println!("Renderer initialized with {:#?}", renderer.info());
+
+        let mut sdl_renderer = SdlRenderer {
+            camera_pos: Vector2::new(0, 0),
+            texture_tint: Color::rgb(255, 255, 255),
+            settings: *settings,
+            scale: (1.0, 1.0),
+            _video: video,
+            renderer: renderer,
+        };
+        sdl_renderer.update_scale();
+        Ok(sdl_renderer)
+    }
+
+    /// Recalculates how much to scale drawing up by; call this whenever the window's size changes
+    pub fn update_scale(&mut self) {
+        let scale = self.settings.scale_for(self.window_size());
+        self.set_scale(scale, scale);
+    }
+
+    pub fn scale(&self) -> (f32, f32) {
+        self.scale
+    }
+
+    fn window_size(&self) -> Vector2<u32> {
+        let size = self.renderer.window().unwrap().size();
+        Vector2::new(size.0, size.1)
+    }
+
+    /// Offsets the rects by the camera position, skipping empty ones (SDL would draw them a pixel wide)
+    fn to_sdl_rects(&self, rects: &[Rect]) -> Vec<sdl2::rect::Rect> {
+        let camera_pos = self.camera_pos;
+        rects.iter()
+            .filter(|rect| rect.w > 0 && rect.h > 0)
+            .map(|rect| Rect::of(rect.x - camera_pos.x, rect.y - camera_pos.y, rect.w, rect.h).into())
+            .collect()
+    }
+}
+
+impl Renderer for SdlRenderer {
+    fn create_texture(&mut self, width: u32, height: u32) -> Result<Texture> {
+        let mut sdl_texture = try!(self.renderer
+            .create_texture_static(sdl2::pixels::PixelFormatEnum::RGBA8888, width, height));
+        sdl_texture.set_blend_mode(sdl2::render::BlendMode::Blend);
+
+        let mut texture = texture::create_texture(sdl_texture, width, height);
+        try!(texture.update(Rect::of(0, 0, width as i32, height as i32),
+                            &vec![0u8; (width * height * 4) as usize]));
+        Ok(texture)
+    }
+
+    fn present(&mut self) {
+        self.set_render_color(Color::rgba(0, 0, 0, 0));
+        self.renderer.present();
+        self.renderer.clear();
+    }
+
+    /// Size of the window before scaling
+    fn viewport_size(&self) -> Vector2<u32> {
+        display::logical_size(self.window_size(), self.scale.0, self.scale.1)
+    }
+
+    fn set_scale(&mut self, scale_x: f32, scale_y: f32) {
+        self.scale = (scale_x, scale_y);
+        self.renderer.set_scale(scale_x, scale_y).expect("set render scale");
+    }
+
+    fn set_camera_position(&mut self, position: &Vector2<i32>) {
+        self.camera_pos = *position;
+    }
+
+    fn render_texture(&mut self,
+                      texture: &Texture,
+                      src_rect: Option<Rect>,
+                      mut dst_rect: Rect,
+                      flip_horizontal: bool,
+                      flip_vertical: bool) {
+        let sdl_texture = match texture.sdl_texture() {
+            Some(sdl_texture) => sdl_texture,
+            None => {
+                println!("Failed to render texture: it wasn't created by the SDL renderer");
+                return;
+            }
+        };
+
+        dst_rect.x -= self.camera_pos.x;
+        dst_rect.y -= self.camera_pos.y;
+
+        // Textures are shared, so the tint is only applied for the duration of the copy
+        let tint = self.texture_tint;
+        let tinted = tint != Color::rgb(255, 255, 255);
+        let mut sdl_texture = sdl_texture.borrow_mut();
+        if tinted {
+            sdl_texture.set_color_mod(tint.r, tint.g, tint.b);
+            sdl_texture.set_alpha_mod(tint.a);
+        }
+        self.renderer
+            .copy_ex(&*sdl_texture,
+                     src_rect.map(|r| r.into()),
+                     Some(dst_rect.into()),
+                     0.0,
+                     None,
+                     flip_horizontal,
+                     flip_vertical)
+            .unwrap_or_else(|err| {
+                println!("Failed to render texture: {}", err);
+            });
+        if tinted {
+            sdl_texture.set_color_mod(255, 255, 255);
+            sdl_texture.set_alpha_mod(255);
+        }
+    }
+
+    fn set_texture_tint(&mut self, tint: Color) {
+        self.texture_tint = tint;
+    }
+
+    fn set_render_color(&mut self, color: Color) {
+        self.renderer.set_draw_color(color.into());
+    }
+
+    fn render_rect(&mut self, mut rect: Rect) {
+        rect.x -= self.camera_pos.x;
+        rect.y -= self.camera_pos.y;
+        self.renderer.draw_rect(rect.into()).expect("Failed to draw rect");
+    }
+
+    fn render_line(&mut self, mut first: Vector2<i32>, mut second: Vector2<i32>) {
+        first.x -= self.camera_pos.x;
+        first.y -= self.camera_pos.y;
+        second.x -= self.camera_pos.x;
+        second.y -= self.camera_pos.y;
+        self.renderer
+            .draw_line(sdl2::rect::Point::new(first.x, first.y),
+                       sdl2::rect::Point::new(second.x, second.y))
+            .expect("Failed to draw line");
+    }
+
+    fn fill_rects(&mut self, rects: &[Rect]) {
+        let sdl_rects = self.to_sdl_rects(rects);
+        if !sdl_rects.is_empty() {
+            self.renderer.fill_rects(&sdl_rects).expect("Failed to fill rects");
+        }
+    }
+
+    fn render_rects(&mut self, rects: &[Rect]) {
+        let sdl_rects = self.to_sdl_rects(rects);
+        if !sdl_rects.is_empty() {
+            self.renderer.draw_rects(&sdl_rects).expect("Failed to draw rects");
+        }
+    }
+
+    fn capture(&mut self) -> Result<RgbaImage> {
+        let (width, height) = try!(self.renderer.output_size());
+        let pixels = try!(self.renderer.read_pixels(None, sdl2::pixels::PixelFormatEnum::RGBA8888));
+        Ok(RgbaImage::from_rgba8888(width, height, &pixels))
+    }
+}
@@ -0,0 +1,171 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use audio::AudioBackend;
+use error::Result;
+
+use mixer::{MIXER_CHANNELS, MIXER_SAMPLE_RATE, Mixer};
+use sound::{self, write_wav_header};
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+fn mix(mixer: &Option<Arc<Mutex<Mixer>>>, buffer: &mut Vec<i16>, frames: usize) {
+    buffer.clear();
+    buffer.resize(frames * MIXER_CHANNELS as usize, 0);
+    if let Some(ref mixer) = *mixer {
+        if let Ok(mut mixer) = mixer.lock() {
+            mixer.mix(buffer);
+        }
+    }
+}
+
+/// Throws the audio away, but still advances the mixer so that sounds finish on time
+pub struct NullAudioBackend {
+    mixer: Option<Arc<Mutex<Mixer>>>,
+    buffer: Vec<i16>,
+}
+
+impl NullAudioBackend {
+    pub fn new() -> NullAudioBackend {
+        NullAudioBackend {
+            mixer: None,
+            buffer: Vec::new(),
+        }
+    }
+}
+
+impl AudioBackend for NullAudioBackend {
+    fn start(&mut self, mixer: Arc<Mutex<Mixer>>) -> Result<()> {
+        self.mixer = Some(mixer);
+        Ok(())
+    }
+
+    fn advance(&mut self, frames: usize) -> Result<()> {
+        mix(&self.mixer, &mut self.buffer, frames);
+        Ok(())
+    }
+}
+
+/// Records the audio to a 16-bit stereo WAV file, in game time rather than real time
+pub struct WavAudioBackend<W: Write + Seek> {
+    writer: Option<W>,
+    mixer: Option<Arc<Mutex<Mixer>>>,
+    buffer: Vec<i16>,
+    data_len: u32,
+}
+
+impl WavAudioBackend<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(file_name: P) -> Result<WavAudioBackend<BufWriter<File>>> {
+        let file = try!(File::create(file_name.as_ref()));
+        WavAudioBackend::new(BufWriter::new(file))
+    }
+}
+
+impl<W: Write + Seek> WavAudioBackend<W> {
+    pub fn new(mut writer: W) -> Result<WavAudioBackend<W>> {
+        // Written again with the correct lengths when finished
+        try!(write_wav_header(&mut writer, MIXER_SAMPLE_RATE, MIXER_CHANNELS, 0));
+        Ok(WavAudioBackend {
+            writer: Some(writer),
+            mixer: None,
+            buffer: Vec::new(),
+            data_len: 0,
+        })
+    }
+
+    /// Fixes up the WAV header and returns the writer
+    pub fn finish(mut self) -> Result<W> {
+        try!(self.write_header());
+        Ok(self.writer.take().unwrap())
+    }
+
+    fn write_header(&mut self) -> Result<()> {
+        if let Some(ref mut writer) = self.writer {
+            let end = try!(writer.seek(SeekFrom::Current(0)));
+            try!(writer.seek(SeekFrom::Start(0)));
+            try!(write_wav_header(writer, MIXER_SAMPLE_RATE, MIXER_CHANNELS, self.data_len));
+            try!(writer.seek(SeekFrom::Start(end)));
+            try!(writer.flush());
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> AudioBackend for WavAudioBackend<W> {
+    fn start(&mut self, mixer: Arc<Mutex<Mixer>>) -> Result<()> {
+        self.mixer = Some(mixer);
+        Ok(())
+    }
+
+    fn advance(&mut self, frames: usize) -> Result<()> {
+        mix(&self.mixer, &mut self.buffer, frames);
+        if let Some(ref mut writer) = self.writer {
+            for sample in &self.buffer {
+                try!(sound::write_u16(writer, *sample as u16));
+            }
+        }
+        self.data_len += (self.buffer.len() * 2) as u32;
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> Drop for WavAudioBackend<W> {
+    fn drop(&mut self) {
+        if let Err(err) = self.write_header() {
+            println!("Failed to finish writing audio: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use audio::AudioBackend;
+    use mixer::{MIXER_SAMPLE_RATE, Mixer};
+    use sound::Sound;
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+    use super::WavAudioBackend;
+
+    #[test]
+    fn test_wav_backend_records_mixed_audio() {
+        let mixer = Arc::new(Mutex::new(Mixer::new(MIXER_SAMPLE_RATE, 4)));
+        mixer.lock().unwrap().play(Arc::new(Sound {
+                                       sample_rate: MIXER_SAMPLE_RATE,
+                                       channels: 1,
+                                       samples: vec![100, 200, 300],
+                                   }),
+                                   None,
+                                   1.0);
+
+        let mut backend = WavAudioBackend::new(Cursor::new(Vec::new())).unwrap();
+        backend.start(mixer).unwrap();
+        backend.advance(2).unwrap();
+        backend.advance(2).unwrap();
+        let wav = backend.finish().unwrap().into_inner();
+
+        let recorded = Sound::read_wav(&wav).unwrap();
+        assert_eq!(MIXER_SAMPLE_RATE, recorded.sample_rate);
+        assert_eq!(2, recorded.channels);
+        assert_eq!(vec![100, 100, 200, 200, 300, 300, 0, 0], recorded.samples);
+    }
+}
@@ -0,0 +1,468 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::Result;
+
+use nalgebra::Vector2;
+use renderer::Renderer;
+use rgba_image::RgbaImage;
+
+use std::cmp;
+use std::mem;
+use texture::{self, SoftwareTexture, Texture};
+use types::{Color, Rect};
+
+/// A single draw operation in screen coordinates (after the camera offset has been applied)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DrawCall {
+    Texture {
+        src_rect: Rect,
+        dst_rect: Rect,
+        flip_horizontal: bool,
+        flip_vertical: bool,
+    },
+    Rect { rect: Rect, color: Color },
+    FillRect { rect: Rect, color: Color },
+    Line {
+        first: Vector2<i32>,
+        second: Vector2<i32>,
+        color: Color,
+    },
+}
+
+/// Renderer that doesn't need a display or GPU. It records every draw call and
+/// rasterises them into an RGBA buffer (four bytes per pixel in R, G, B, A order).
+pub struct SoftwareRenderer {
+    width: u32,
+    height: u32,
+    camera_pos: Vector2<i32>,
+    scale: (f32, f32),
+    draw_color: Color,
+    texture_tint: Color,
+
+    pixels: Vec<u8>,
+    draw_calls: Vec<DrawCall>,
+
+    presented_pixels: Vec<u8>,
+    presented_draw_calls: Vec<DrawCall>,
+    frames_presented: u64,
+}
+
+impl SoftwareRenderer {
+    pub fn new(width: u32, height: u32) -> SoftwareRenderer {
+        let buffer_size = (width * height * 4) as usize;
+        SoftwareRenderer {
+            width: width,
+            height: height,
+            camera_pos: Vector2::new(0, 0),
+            scale: (1.0, 1.0),
+            draw_color: Color::rgba(0, 0, 0, 0),
+            texture_tint: Color::rgb(255, 255, 255),
+            pixels: vec![0u8; buffer_size],
+            draw_calls: Vec::new(),
+            presented_pixels: vec![0u8; buffer_size],
+            presented_draw_calls: Vec::new(),
+            frames_presented: 0,
+        }
+    }
+
+    /// Pixels of the most recently presented frame
+    pub fn pixels<'a>(&'a self) -> &'a [u8] {
+        &self.presented_pixels
+    }
+
+    /// Draw calls that made up the most recently presented frame
+    pub fn draw_calls<'a>(&'a self) -> &'a [DrawCall] {
+        &self.presented_draw_calls
+    }
+
+    pub fn frames_presented(&self) -> u64 {
+        self.frames_presented
+    }
+
+    fn scale_rect(&self, rect: Rect) -> Rect {
+        let (scale_x, scale_y) = self.scale;
+        Rect::of((rect.x as f32 * scale_x) as i32,
+                 (rect.y as f32 * scale_y) as i32,
+                 (rect.w as f32 * scale_x) as i32,
+                 (rect.h as f32 * scale_y) as i32)
+    }
+
+    fn scale_point(&self, point: Vector2<i32>) -> Vector2<i32> {
+        let (scale_x, scale_y) = self.scale;
+        Vector2::new((point.x as f32 * scale_x) as i32, (point.y as f32 * scale_y) as i32)
+    }
+
+    fn blit(&mut self,
+            src_pixels: &[u8],
+            src_width: u32,
+            src_rect: Rect,
+            dst_rect: Rect,
+            flip_horizontal: bool,
+            flip_vertical: bool) {
+        if src_rect.w <= 0 || src_rect.h <= 0 || dst_rect.w <= 0 || dst_rect.h <= 0 {
+            return;
+        }
+
+        let tint = self.texture_tint;
+        let first_y = cmp::max(0, -dst_rect.y);
+        let last_y = cmp::min(dst_rect.h, self.height as i32 - dst_rect.y);
+        let first_x = cmp::max(0, -dst_rect.x);
+        let last_x = cmp::min(dst_rect.w, self.width as i32 - dst_rect.x);
+        for dy in first_y..last_y {
+            let mut sy = dy * src_rect.h / dst_rect.h;
+            if flip_vertical {
+                sy = src_rect.h - 1 - sy;
+            }
+            for dx in first_x..last_x {
+                let mut sx = dx * src_rect.w / dst_rect.w;
+                if flip_horizontal {
+                    sx = src_rect.w - 1 - sx;
+                }
+                let src_index =
+                    (((src_rect.y + sy) as u32 * src_width + (src_rect.x + sx) as u32) * 4) as usize;
+                if src_index + 4 > src_pixels.len() {
+                    continue;
+                }
+                let texel = [src_pixels[src_index],
+                             src_pixels[src_index + 1],
+                             src_pixels[src_index + 2],
+                             src_pixels[src_index + 3]];
+                let color = unpack_rgba8888(texel);
+                self.blend_pixel(dst_rect.x + dx,
+                                 dst_rect.y + dy,
+                                 Color::rgba(modulate(color.r, tint.r),
+                                             modulate(color.g, tint.g),
+                                             modulate(color.b, tint.b),
+                                             modulate(color.a, tint.a)));
+            }
+        }
+    }
+
+    /// Alpha blends the color over the pixel that's already there
+    fn blend_pixel(&mut self, x: i32, y: i32, color: Color) {
+        if color.a == 0 {
+            return;
+        }
+        if let Some(index) = self.pixel_index(x, y) {
+            let alpha = color.a as u32;
+            let inv_alpha = 255 - alpha;
+            let dst = &mut self.pixels[index..index + 4];
+            dst[0] = ((color.r as u32 * alpha + dst[0] as u32 * inv_alpha) / 255) as u8;
+            dst[1] = ((color.g as u32 * alpha + dst[1] as u32 * inv_alpha) / 255) as u8;
+            dst[2] = ((color.b as u32 * alpha + dst[2] as u32 * inv_alpha) / 255) as u8;
+            dst[3] = (alpha + dst[3] as u32 * inv_alpha / 255) as u8;
+        }
+    }
+
+    fn pixel_index(&self, x: i32, y: i32) -> Option<usize> {
+        if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+            None
+        } else {
+            Some(((y as u32 * self.width + x as u32) * 4) as usize)
+        }
+    }
+
+    /// Bresenham's line algorithm
+    fn rasterise_line(&mut self, first: Vector2<i32>, second: Vector2<i32>, color: Color) {
+        let (mut x, mut y) = (first.x, first.y);
+        let (dx, dy) = ((second.x - first.x).abs(), -(second.y - first.y).abs());
+        let step_x = if first.x < second.x { 1 } else { -1 };
+        let step_y = if first.y < second.y { 1 } else { -1 };
+        let mut error = dx + dy;
+        loop {
+            self.blend_pixel(x, y, color);
+            if x == second.x && y == second.y {
+                break;
+            }
+            let error2 = 2 * error;
+            if error2 >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if error2 <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+}
+
+impl Renderer for SoftwareRenderer {
+    fn create_texture(&mut self, width: u32, height: u32) -> Result<Texture> {
+        Ok(texture::create_software_texture(width, height))
+    }
+
+    fn present(&mut self) {
+        let blank = vec![0u8; (self.width * self.height * 4) as usize];
+        self.presented_pixels = mem::replace(&mut self.pixels, blank);
+        self.presented_draw_calls = mem::replace(&mut self.draw_calls, Vec::new());
+        self.frames_presented += 1;
+        self.draw_color = Color::rgba(0, 0, 0, 0);
+        self.texture_tint = Color::rgb(255, 255, 255);
+    }
+
+    fn viewport_size(&self) -> Vector2<u32> {
+        Vector2::new(self.width, self.height)
+    }
+
+    fn set_scale(&mut self, scale_x: f32, scale_y: f32) {
+        self.scale = (scale_x, scale_y);
+    }
+
+    fn set_camera_position(&mut self, position: &Vector2<i32>) {
+        self.camera_pos = *position;
+    }
+
+    fn render_texture(&mut self,
+                      texture: &Texture,
+                      src_rect: Option<Rect>,
+                      mut dst_rect: Rect,
+                      flip_horizontal: bool,
+                      flip_vertical: bool) {
+        let src_pixels = match texture.software_pixels() {
+            Some(pixels) => pixels,
+            None => {
+                println!("Failed to render texture: it wasn't created by the software renderer");
+                return;
+            }
+        };
+
+        dst_rect.x -= self.camera_pos.x;
+        dst_rect.y -= self.camera_pos.y;
+        let src_rect = src_rect.unwrap_or(Rect::of(0, 0, texture.width as i32, texture.height as i32));
+        self.draw_calls.push(DrawCall::Texture {
+            src_rect: src_rect,
+            dst_rect: dst_rect,
+            flip_horizontal: flip_horizontal,
+            flip_vertical: flip_vertical,
+        });
+
+        let scaled_dst_rect = self.scale_rect(dst_rect);
+        self.blit(src_pixels,
+                  texture.width,
+                  src_rect,
+                  scaled_dst_rect,
+                  flip_horizontal,
+                  flip_vertical);
+    }
+
+    fn set_texture_tint(&mut self, tint: Color) {
+        self.texture_tint = tint;
+    }
+
+    fn set_render_color(&mut self, color: Color) {
+        self.draw_color = color;
+    }
+
+    fn render_rect(&mut self, mut rect: Rect) {
+        rect.x -= self.camera_pos.x;
+        rect.y -= self.camera_pos.y;
+        let color = self.draw_color;
+        self.draw_calls.push(DrawCall::Rect {
+            rect: rect,
+            color: color,
+        });
+
+        let rect = self.scale_rect(rect);
+        if rect.w <= 0 || rect.h <= 0 {
+            return;
+        }
+        let (left, top, right, bottom) = (rect.x, rect.y, rect.x + rect.w - 1, rect.y + rect.h - 1);
+        self.rasterise_line(Vector2::new(left, top), Vector2::new(right, top), color);
+        self.rasterise_line(Vector2::new(left, bottom), Vector2::new(right, bottom), color);
+        self.rasterise_line(Vector2::new(left, top), Vector2::new(left, bottom), color);
+        self.rasterise_line(Vector2::new(right, top), Vector2::new(right, bottom), color);
+    }
+
+    fn render_line(&mut self, mut first: Vector2<i32>, mut second: Vector2<i32>) {
+        first.x -= self.camera_pos.x;
+        first.y -= self.camera_pos.y;
+        second.x -= self.camera_pos.x;
+        second.y -= self.camera_pos.y;
+        let color = self.draw_color;
+        self.draw_calls.push(DrawCall::Line {
+            first: first,
+            second: second,
+            color: color,
+        });
+
+        let (first, second) = (self.scale_point(first), self.scale_point(second));
+        self.rasterise_line(first, second, color);
+    }
+
+    fn fill_rects(&mut self, rects: &[Rect]) {
+        let color = self.draw_color;
+        for rect in rects {
+            let mut rect = *rect;
+            rect.x -= self.camera_pos.x;
+            rect.y -= self.camera_pos.y;
+            self.draw_calls.push(DrawCall::FillRect {
+                rect: rect,
+                color: color,
+            });
+
+            let rect = self.scale_rect(rect);
+            for y in cmp::max(0, rect.y)..cmp::min(self.height as i32, rect.y + rect.h) {
+                for x in cmp::max(0, rect.x)..cmp::min(self.width as i32, rect.x + rect.w) {
+                    self.blend_pixel(x, y, color);
+                }
+            }
+        }
+    }
+
+    fn capture(&mut self) -> Result<RgbaImage> {
+        Ok(RgbaImage::new(self.width, self.height, self.pixels.clone()))
+    }
+}
+
+/// Scales a color channel by a tint channel (255 leaves it unchanged)
+fn modulate(channel: u8, tint: u8) -> u8 {
+    (channel as u32 * tint as u32 / 255) as u8
+}
+
+/// Textures are in SDL's RGBA8888 format: a native endian u32 laid out as 0xRRGGBBAA
+fn unpack_rgba8888(texel: [u8; 4]) -> Color {
+    let packed = unsafe { mem::transmute::<[u8; 4], u32>(texel) };
+    Color::rgba((packed >> 24) as u8,
+                (packed >> 16) as u8,
+                (packed >> 8) as u8,
+                packed as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector2;
+    use renderer::Renderer;
+    use std::mem;
+    use super::{DrawCall, SoftwareRenderer};
+    use types::{Color, Rect};
+
+    fn pack(color: Color) -> [u8; 4] {
+        let packed = (color.r as u32) << 24 | (color.g as u32) << 16 | (color.b as u32) << 8 | color.a as u32;
+        unsafe { mem::transmute::<u32, [u8; 4]>(packed) }
+    }
+
+    fn pixel(renderer: &SoftwareRenderer, x: u32, y: u32) -> Color {
+        let index = ((y * 8 + x) * 4) as usize;
+        let pixels = renderer.pixels();
+        Color::rgba(pixels[index], pixels[index + 1], pixels[index + 2], pixels[index + 3])
+    }
+
+    #[test]
+    fn test_render_texture() {
+        let (red, green) = (Color::rgb(255, 0, 0), Color::rgb(0, 255, 0));
+        let mut renderer = SoftwareRenderer::new(8, 8);
+        let mut texture = renderer.create_texture(2, 1).unwrap();
+        let mut texels = Vec::new();
+        texels.extend_from_slice(&pack(red));
+        texels.extend_from_slice(&pack(green));
+        texture.update(Rect::of(0, 0, 2, 1), &texels).unwrap();
+
+        renderer.set_camera_position(&Vector2::new(1, 1));
+        renderer.render_texture(&texture, None, Rect::of(2, 2, 2, 1), false, false);
+        renderer.render_texture(&texture, None, Rect::of(2, 3, 2, 1), true, false);
+        renderer.present();
+
+        assert_eq!(red, pixel(&renderer, 1, 1));
+        assert_eq!(green, pixel(&renderer, 2, 1));
+        assert_eq!(green, pixel(&renderer, 1, 2));
+        assert_eq!(red, pixel(&renderer, 2, 2));
+        assert_eq!(Color::rgba(0, 0, 0, 0), pixel(&renderer, 0, 0));
+        assert_eq!(DrawCall::Texture {
+                       src_rect: Rect::of(0, 0, 2, 1),
+                       dst_rect: Rect::of(1, 1, 2, 1),
+                       flip_horizontal: false,
+                       flip_vertical: false,
+                   },
+                   renderer.draw_calls()[0]);
+    }
+
+    #[test]
+    fn test_render_texture_clips_and_skips_transparent_pixels() {
+        let mut renderer = SoftwareRenderer::new(8, 8);
+        let mut texture = renderer.create_texture(4, 4).unwrap();
+        let texels: Vec<u8> = (0..16).flat_map(|_| pack(Color::rgb(0, 0, 255)).to_vec()).collect();
+        texture.update(Rect::of(0, 0, 4, 4), &texels).unwrap();
+        texture.update(Rect::of(3, 3, 1, 1), &[0, 0, 0, 0]).unwrap();
+
+        renderer.render_texture(&texture, None, Rect::of(6, 6, 4, 4), false, false);
+        renderer.render_texture(&texture, None, Rect::of(-2, -2, 4, 4), false, false);
+        renderer.present();
+
+        assert_eq!(Color::rgb(0, 0, 255), pixel(&renderer, 7, 7));
+        assert_eq!(Color::rgb(0, 0, 255), pixel(&renderer, 0, 0));
+        assert_eq!(Color::rgba(0, 0, 0, 0), pixel(&renderer, 1, 1));
+    }
+
+    #[test]
+    fn test_render_rect_and_line() {
+        let white = Color::rgb(255, 255, 255);
+        let mut renderer = SoftwareRenderer::new(8, 8);
+        renderer.set_render_color(white);
+        renderer.render_rect(Rect::of(1, 1, 3, 3));
+        renderer.render_line(Vector2::new(0, 7), Vector2::new(7, 7));
+
+        let capture = renderer.capture().unwrap();
+        assert_eq!(white, capture.pixel(3, 1));
+        assert_eq!(white, capture.pixel(7, 7));
+        renderer.present();
+
+        assert_eq!(white, pixel(&renderer, 1, 1));
+        assert_eq!(white, pixel(&renderer, 3, 3));
+        assert_eq!(Color::rgba(0, 0, 0, 0), pixel(&renderer, 2, 2));
+        assert_eq!(white, pixel(&renderer, 0, 7));
+        assert_eq!(white, pixel(&renderer, 7, 7));
+        assert_eq!(2, renderer.draw_calls().len());
+
+        // Presenting starts a new, empty frame
+        renderer.present();
+        assert_eq!(Color::rgba(0, 0, 0, 0), pixel(&renderer, 1, 1));
+        assert!(renderer.draw_calls().is_empty());
+        assert_eq!(2, renderer.frames_presented());
+    }
+
+    #[test]
+    fn test_fill_rects_and_tint() {
+        let mut renderer = SoftwareRenderer::new(8, 8);
+        renderer.set_render_color(Color::rgb(255, 0, 0));
+        renderer.fill_rects(&[Rect::of(0, 0, 4, 4), Rect::of(6, 6, 4, 4)]);
+        renderer.set_render_color(Color::rgba(0, 0, 255, 128));
+        renderer.fill_rects(&[Rect::of(2, 2, 4, 4)]);
+
+        let mut texture = renderer.create_texture(1, 1).unwrap();
+        texture.update(Rect::of(0, 0, 1, 1), &pack(Color::rgb(255, 255, 255))).unwrap();
+        renderer.set_texture_tint(Color::rgb(0, 255, 0));
+        renderer.render_texture(&texture, None, Rect::of(7, 0, 1, 1), false, false);
+        renderer.present();
+
+        assert_eq!(Color::rgb(255, 0, 0), pixel(&renderer, 1, 1));
+        assert_eq!(Color::rgb(127, 0, 128), pixel(&renderer, 3, 3));
+        assert_eq!(Color::rgba(0, 0, 128, 128), pixel(&renderer, 5, 5));
+        assert_eq!(Color::rgb(255, 0, 0), pixel(&renderer, 7, 7));
+        assert_eq!(Color::rgb(0, 255, 0), pixel(&renderer, 7, 0));
+        assert_eq!(DrawCall::FillRect {
+                       rect: Rect::of(6, 6, 4, 4),
+                       color: Color::rgb(255, 0, 0),
+                   },
+                   renderer.draw_calls()[1]);
+    }
+}
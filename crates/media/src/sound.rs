@@ -0,0 +1,169 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::{ErrorKind, Result};
+
+use std::io::{self, Write};
+
+/// Decoded PCM audio as signed 16-bit samples (interleaved when there's more than one channel)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sound {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub samples: Vec<i16>,
+}
+
+impl Sound {
+    /// Decodes a RIFF WAVE file containing 8 or 16-bit PCM (which is what sounds.drs has)
+    pub fn read_wav(data: &[u8]) -> Result<Sound> {
+        if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WAVE" {
+            return Err(ErrorKind::InvalidWav("missing RIFF/WAVE header".into()).into());
+        }
+
+        let mut format: Option<(u16, u16, u32, u16)> = None;
+        let mut offset = 12;
+        while offset + 8 <= data.len() {
+            let chunk_id = &data[offset..offset + 4];
+            let chunk_size = read_u32(data, offset + 4) as usize;
+            let chunk_start = offset + 8;
+            // Some files in the wild have a data chunk size that runs past the end of the file
+            let chunk_end = ::std::cmp::min(chunk_start + chunk_size, data.len());
+            let chunk = &data[chunk_start..chunk_end];
+
+            if chunk_id == b"fmt " {
+                if chunk.len() < 16 {
+                    return Err(ErrorKind::InvalidWav("fmt chunk is too short".into()).into());
+                }
+                format = Some((read_u16(chunk, 0),
+                               read_u16(chunk, 2),
+                               read_u32(chunk, 4),
+                               read_u16(chunk, 14)));
+            } else if chunk_id == b"data" {
+                let (format_tag, channels, sample_rate, bits_per_sample) = match format {
+                    Some(format) => format,
+                    None => return Err(ErrorKind::InvalidWav("data chunk before fmt chunk".into()).into()),
+                };
+                if format_tag != 1 || channels == 0 {
+                    return Err(ErrorKind::InvalidWav(format!("unsupported format {}", format_tag)).into());
+                }
+                let samples = match bits_per_sample {
+                    8 => chunk.iter().map(|&s| ((s as i16) - 128) << 8).collect(),
+                    16 => chunk.chunks(2).filter(|s| s.len() == 2).map(|s| read_u16(s, 0) as i16).collect(),
+                    bits => {
+                        return Err(ErrorKind::InvalidWav(format!("unsupported bit depth {}", bits)).into())
+                    }
+                };
+                return Ok(Sound {
+                    sample_rate: sample_rate,
+                    channels: channels,
+                    samples: samples,
+                });
+            }
+
+            // Chunks are padded to an even number of bytes
+            offset = chunk_start + chunk_size + (chunk_size & 1);
+        }
+        Err(ErrorKind::InvalidWav("missing data chunk".into()).into())
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.samples.len() / self.channels as usize
+    }
+}
+
+/// Writes the header of a 16-bit PCM WAVE file whose sample data is `data_len` bytes long
+pub fn write_wav_header<W: Write>(writer: &mut W,
+                                  sample_rate: u32,
+                                  channels: u16,
+                                  data_len: u32)
+                                  -> io::Result<()> {
+    let block_align = channels * 2;
+    try!(writer.write_all(b"RIFF"));
+    try!(write_u32(writer, 36 + data_len));
+    try!(writer.write_all(b"WAVEfmt "));
+    try!(write_u32(writer, 16));
+    try!(write_u16(writer, 1));
+    try!(write_u16(writer, channels));
+    try!(write_u32(writer, sample_rate));
+    try!(write_u32(writer, sample_rate * block_align as u32));
+    try!(write_u16(writer, block_align));
+    try!(write_u16(writer, 16));
+    try!(writer.write_all(b"data"));
+    write_u32(writer, data_len)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    data[offset] as u16 | (data[offset + 1] as u16) << 8
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    read_u16(data, offset) as u32 | (read_u16(data, offset + 2) as u32) << 16
+}
+
+pub fn write_u16<W: Write>(writer: &mut W, value: u16) -> io::Result<()> {
+    writer.write_all(&[value as u8, (value >> 8) as u8])
+}
+
+fn write_u32<W: Write>(writer: &mut W, value: u32) -> io::Result<()> {
+    try!(write_u16(writer, value as u16));
+    write_u16(writer, (value >> 16) as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Sound, write_u16, write_wav_header};
+
+    fn wav_bytes(channels: u16, samples: &[i16]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_wav_header(&mut bytes, 22050, channels, samples.len() as u32 * 2).unwrap();
+        for sample in samples {
+            write_u16(&mut bytes, *sample as u16).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_read_wav_16_bit() {
+        let sound = Sound::read_wav(&wav_bytes(2, &[1, -1, 32767, -32768])).unwrap();
+        assert_eq!(Sound {
+                       sample_rate: 22050,
+                       channels: 2,
+                       samples: vec![1, -1, 32767, -32768],
+                   },
+                   sound);
+        assert_eq!(2, sound.frame_count());
+    }
+
+    #[test]
+    fn test_read_wav_8_bit() {
+        let mut bytes = wav_bytes(1, &[]);
+        bytes[34] = 8; // bits per sample
+        bytes[40] = 3; // data length
+        bytes.extend_from_slice(&[0, 128, 255]);
+        assert_eq!(vec![-32768, 0, 32512], Sound::read_wav(&bytes).unwrap().samples);
+    }
+
+    #[test]
+    fn test_read_invalid_wav() {
+        assert!(Sound::read_wav(b"RIFF\0\0\0\0WAVE").is_err());
+        assert!(Sound::read_wav(b"not a wav file").is_err());
+    }
+}
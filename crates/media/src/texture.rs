@@ -19,12 +19,52 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use error::Result;
 use sdl2;
+use std::cell::RefCell;
+use types::Rect;
 
 pub struct Texture {
     pub width: u32,
     pub height: u32,
-    texture: sdl2::render::Texture,
+    data: TextureData,
+}
+
+enum TextureData {
+    /// In a RefCell so that its color and alpha modulation can be changed while rendering it
+    Sdl(RefCell<sdl2::render::Texture>),
+
+    /// RGBA8888 pixels for textures that are drawn by the software renderer
+    Software(Vec<u8>),
+}
+
+impl Texture {
+    /// Replaces the pixels in the given area of the texture with the given RGBA8888 pixels
+    pub fn update(&mut self, rect: Rect, rgba_pixels: &[u8]) -> Result<()> {
+        let pitch = 4 * rect.w as usize;
+        match self.data {
+            TextureData::Sdl(ref texture) => {
+                let sdl_rect: sdl2::rect::Rect = rect.into();
+                texture.borrow_mut()
+                    .update(Some(sdl_rect), rgba_pixels, pitch)
+                    .map_err(|err| format!("Failed to update texture: {}", err).into())
+            }
+            TextureData::Software(ref mut pixels) => {
+                if rect.x < 0 || rect.y < 0 || rect.x + rect.w > self.width as i32 ||
+                   rect.y + rect.h > self.height as i32 || rgba_pixels.len() < pitch * rect.h as usize {
+                    return Err(format!("Failed to update texture: {:?} is out of bounds", rect).into());
+                }
+                let texture_pitch = 4 * self.width as usize;
+                for row in 0..rect.h as usize {
+                    let dst_start = (rect.y as usize + row) * texture_pitch + 4 * rect.x as usize;
+                    let src_start = row * pitch;
+                    pixels[dst_start..dst_start + pitch]
+                        .copy_from_slice(&rgba_pixels[src_start..src_start + pitch]);
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 // TODO: Haven't quite figured out how to make a new method on Texture that is only exposed
@@ -33,17 +73,42 @@ pub fn create_texture(sdl_texture: sdl2::render::Texture, width: u32, height: u3
     Texture {
         width: width,
         height: height,
-        texture: sdl_texture,
+        data: TextureData::Sdl(RefCell::new(sdl_texture)),
+    }
+}
+
+pub fn create_software_texture(width: u32, height: u32) -> Texture {
+    Texture {
+        width: width,
+        height: height,
+        data: TextureData::Software(vec![0u8; (width * height * 4) as usize]),
     }
 }
 
 // Separate so that it's not exported with the crate
 pub trait SdlTexture {
-    fn sdl_texture<'a>(&'a self) -> &'a sdl2::render::Texture;
+    fn sdl_texture<'a>(&'a self) -> Option<&'a RefCell<sdl2::render::Texture>>;
 }
 
 impl SdlTexture for Texture {
-    fn sdl_texture<'a>(&'a self) -> &'a sdl2::render::Texture {
-        &self.texture
+    fn sdl_texture<'a>(&'a self) -> Option<&'a RefCell<sdl2::render::Texture>> {
+        match self.data {
+            TextureData::Sdl(ref texture) => Some(texture),
+            TextureData::Software(_) => None,
+        }
+    }
+}
+
+// Separate so that it's not exported with the crate
+pub trait SoftwareTexture {
+    fn software_pixels<'a>(&'a self) -> Option<&'a [u8]>;
+}
+
+impl SoftwareTexture for Texture {
+    fn software_pixels<'a>(&'a self) -> Option<&'a [u8]> {
+        match self.data {
+            TextureData::Software(ref pixels) => Some(pixels),
+            TextureData::Sdl(_) => None,
+        }
     }
 }
@@ -20,7 +20,7 @@
 // SOFTWARE.
 
 use error::{Error, Result};
-use renderer::{Renderer, SdlRenderer};
+use renderer::Renderer;
 use sdl2::pixels::PixelFormatEnum;
 
 use sdl2::surface::Surface;
@@ -36,7 +36,8 @@ pub struct TextureBuilder<'a> {
     error: Option<Error>,
 }
 
-fn to_rgba(palette: &[u32], src_pixels: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
+/// Converts palette indexed pixels into RGBA8888 pixels; palette index 0 is transparent
+pub fn to_rgba(palette: &[u32], src_pixels: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
     let mut dst_pixels = io::Cursor::new(vec![0u8; width * height * 4]);
 
     for y in 0..height {
@@ -103,6 +104,20 @@ impl<'a> TextureBuilder<'a> {
             return Err(self.error.unwrap());
         }
 
-        renderer.create_texture_from_surface(self.surface)
+        let (width, height) = (self.surface.width(), self.surface.height());
+        let pitch = self.surface.pitch() as usize;
+        let row_len = 4 * width as usize;
+
+        // The surface rows may be padded, so compact them before handing them to the renderer
+        let mut pixels = Vec::with_capacity(row_len * height as usize);
+        self.surface.with_lock(|surface_pixels| {
+            for row in 0..height as usize {
+                pixels.extend_from_slice(&surface_pixels[row * pitch..row * pitch + row_len]);
+            }
+        });
+
+        let mut texture = try!(renderer.create_texture(width, height));
+        try!(texture.update(Rect::of(0, 0, width as i32, height as i32), &pixels));
+        Ok(texture)
     }
 }
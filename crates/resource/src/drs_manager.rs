@@ -22,7 +22,7 @@
 
 
 use drs::DrsFile;
-use error::Result;
+use error::{ErrorKind, Result};
 use game_dir::GameDir;
 use std::cell::RefCell;
 
@@ -39,6 +39,22 @@ pub enum DrsKey {
 }
 
 impl DrsKey {
+    pub fn all() -> &'static [DrsKey] {
+        use self::DrsKey::*;
+        static ALL: [DrsKey; 5] = [Border, Graphics, Interfac, Sounds, Terrain];
+        &ALL
+    }
+
+    /// Whether the game can run without this archive. Missing optional archives
+    /// only disable the features that depend on them (i.e., no sounds.drs means no audio).
+    pub fn is_required(&self) -> bool {
+        use self::DrsKey::*;
+        match *self {
+            Graphics | Interfac | Terrain => true,
+            Border | Sounds => false,
+        }
+    }
+
     pub fn path(&self) -> &'static str {
         use self::DrsKey::*;
         match *self {
@@ -66,16 +82,26 @@ impl DrsManager {
         }))
     }
 
-    pub fn get<'a>(&'a self, drs_key: DrsKey) -> &'a DrsFile {
-        self.resources.get(&drs_key).unwrap()
+    pub fn get<'a>(&'a self, drs_key: DrsKey) -> Result<&'a DrsFile> {
+        self.resources.get(&drs_key).ok_or(ErrorKind::DrsNotLoaded(drs_key).into())
     }
 
+    pub fn is_loaded(&self, drs_key: DrsKey) -> bool {
+        self.resources.contains_key(&drs_key)
+    }
+
+    /// Loads every DRS archive that the game directory has. Only fails if
+    /// a required archive is missing or if an archive fails to load.
     pub fn preload(&mut self) -> Result<()> {
-        try!(self.preload_drs(DrsKey::Border));
-        try!(self.preload_drs(DrsKey::Graphics));
-        try!(self.preload_drs(DrsKey::Interfac));
-        try!(self.preload_drs(DrsKey::Sounds));
-        try!(self.preload_drs(DrsKey::Terrain));
+        for drs_key in DrsKey::all() {
+            if self.game_dir.capabilities().has_drs(*drs_key) {
+                try!(self.preload_drs(*drs_key));
+            } else if drs_key.is_required() {
+                return Err(ErrorKind::GameDataFileNotFound(drs_key.path().into()).into());
+            } else {
+                println!("Skipping {:?} since it wasn't found", drs_key.path());
+            }
+        }
         Ok(())
     }
 
@@ -49,9 +49,13 @@ error_chain! {
             description("interfac.drs is missing its binary table")
             display("interfac.drs is missing its binary table")
         }
-        InterfacMissingPalette {
-            description("interfac.drs is missing the 50500 palette file")
-            display("interfac.drs is missing the 50500 palette file")
+        PaletteNotFound(palette_id: u32) {
+            description("palette not found")
+            display("interfac.drs is missing the {} palette file", palette_id)
+        }
+        DrsNotLoaded(drs_key: DrsKey) {
+            description("DRS not loaded")
+            display("\"{}\" isn't loaded", drs_key.path())
         }
         NoSlpTableInDrs(drs_key: DrsKey) {
             description("no SLPs in DRS")
@@ -61,6 +65,18 @@ error_chain! {
             description("SLP not found")
             display("{}.slp not found in \"{}\"", slp_id, drs_key.path())
         }
+        NoWavTableInDrs(drs_key: DrsKey) {
+            description("no WAVs in DRS")
+            display("no WAVs found in \"{}\"", drs_key.path())
+        }
+        WavNotFound(wav_id: u32) {
+            description("WAV not found")
+            display("{}.wav not found in \"{}\"", wav_id, DrsKey::Sounds.path())
+        }
+        InvalidFont(message: String) {
+            description("invalid font")
+            display("invalid font: {}", message)
+        }
         GameDirInvalid(message: String) {
             description("Game directory is invalid")
             display("{}", message)
@@ -0,0 +1,398 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::{ErrorKind, Result};
+
+/// Resource type ID of font resources in a NE (16-bit Windows) executable
+const NE_RT_FONT: u16 = 0x8008;
+
+/// Glyphs for ASCII 32 through 126 used when the game's fonts can't be found. Each byte is a row,
+/// with the least significant bit being the leftmost pixel.
+const BUILTIN_GLYPHS: [[u8; 8]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00], // '!'
+    [0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x36, 0x36, 0x7F, 0x36, 0x7F, 0x36, 0x36, 0x00], // '#'
+    [0x0C, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x0C, 0x00], // '$'
+    [0x00, 0x63, 0x33, 0x18, 0x0C, 0x66, 0x63, 0x00], // '%'
+    [0x1C, 0x36, 0x1C, 0x6E, 0x3B, 0x33, 0x6E, 0x00], // '&'
+    [0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00], // '''
+    [0x18, 0x0C, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x00], // '('
+    [0x06, 0x0C, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x00], // ')'
+    [0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00], // '*'
+    [0x00, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x00, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ','
+    [0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // '.'
+    [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00], // '/'
+    [0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00], // '0'
+    [0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00], // '1'
+    [0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00], // '2'
+    [0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00], // '3'
+    [0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00], // '4'
+    [0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00], // '5'
+    [0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00], // '6'
+    [0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00], // '7'
+    [0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00], // '8'
+    [0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ';'
+    [0x18, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x18, 0x00], // '<'
+    [0x00, 0x00, 0x3F, 0x00, 0x00, 0x3F, 0x00, 0x00], // '='
+    [0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00], // '>'
+    [0x1E, 0x33, 0x30, 0x18, 0x0C, 0x00, 0x0C, 0x00], // '?'
+    [0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x00], // '@'
+    [0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00], // 'A'
+    [0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00], // 'B'
+    [0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00], // 'C'
+    [0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00], // 'D'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00], // 'E'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00], // 'F'
+    [0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00], // 'G'
+    [0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00], // 'H'
+    [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'I'
+    [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00], // 'J'
+    [0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00], // 'K'
+    [0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00], // 'L'
+    [0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00], // 'M'
+    [0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00], // 'N'
+    [0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00], // 'O'
+    [0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00], // 'P'
+    [0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00], // 'Q'
+    [0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00], // 'R'
+    [0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00], // 'S'
+    [0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'T'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00], // 'U'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'V'
+    [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00], // 'W'
+    [0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00], // 'X'
+    [0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00], // 'Y'
+    [0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00], // 'Z'
+    [0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x00], // '['
+    [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00], // '\'
+    [0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x00], // ']'
+    [0x08, 0x1C, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF], // '_'
+    [0x0C, 0x0C, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x1E, 0x30, 0x3E, 0x33, 0x6E, 0x00], // 'a'
+    [0x07, 0x06, 0x06, 0x3E, 0x66, 0x66, 0x3B, 0x00], // 'b'
+    [0x00, 0x00, 0x1E, 0x33, 0x03, 0x33, 0x1E, 0x00], // 'c'
+    [0x38, 0x30, 0x30, 0x3E, 0x33, 0x33, 0x6E, 0x00], // 'd'
+    [0x00, 0x00, 0x1E, 0x33, 0x3F, 0x03, 0x1E, 0x00], // 'e'
+    [0x1C, 0x36, 0x06, 0x0F, 0x06, 0x06, 0x0F, 0x00], // 'f'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'g'
+    [0x07, 0x06, 0x36, 0x6E, 0x66, 0x66, 0x67, 0x00], // 'h'
+    [0x0C, 0x00, 0x0E, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'i'
+    [0x30, 0x00, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E], // 'j'
+    [0x07, 0x06, 0x66, 0x36, 0x1E, 0x36, 0x67, 0x00], // 'k'
+    [0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'l'
+    [0x00, 0x00, 0x33, 0x7F, 0x7F, 0x6B, 0x63, 0x00], // 'm'
+    [0x00, 0x00, 0x1F, 0x33, 0x33, 0x33, 0x33, 0x00], // 'n'
+    [0x00, 0x00, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x00], // 'o'
+    [0x00, 0x00, 0x3B, 0x66, 0x66, 0x3E, 0x06, 0x0F], // 'p'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x78], // 'q'
+    [0x00, 0x00, 0x3B, 0x6E, 0x66, 0x06, 0x0F, 0x00], // 'r'
+    [0x00, 0x00, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x00], // 's'
+    [0x08, 0x0C, 0x3E, 0x0C, 0x0C, 0x2C, 0x18, 0x00], // 't'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x6E, 0x00], // 'u'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'v'
+    [0x00, 0x00, 0x63, 0x6B, 0x7F, 0x7F, 0x36, 0x00], // 'w'
+    [0x00, 0x00, 0x63, 0x36, 0x1C, 0x36, 0x63, 0x00], // 'x'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'y'
+    [0x00, 0x00, 0x3F, 0x19, 0x0C, 0x26, 0x3F, 0x00], // 'z'
+    [0x38, 0x0C, 0x0C, 0x07, 0x0C, 0x0C, 0x38, 0x00], // '{'
+    [0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00], // '|'
+    [0x07, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x07, 0x00], // '}'
+    [0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '~'
+];
+
+pub struct Glyph {
+    pub width: u32,
+
+    /// One byte per pixel, row by row: 0 where the glyph is transparent, 1 where it's drawn
+    pub pixels: Vec<u8>,
+}
+
+/// A bitmap font with a glyph for each character in the range [first_char, last_char]
+pub struct Font {
+    pub height: u32,
+    first_char: u8,
+    default_char: u8,
+    glyphs: Vec<Glyph>,
+}
+
+impl Font {
+    /// The font that's used when the game doesn't come with any
+    pub fn builtin() -> Font {
+        let glyphs = BUILTIN_GLYPHS.iter()
+            .map(|rows| {
+                let mut pixels = Vec::with_capacity(64);
+                for row in rows {
+                    for x in 0..8 {
+                        pixels.push((*row >> x) & 1);
+                    }
+                }
+                Glyph {
+                    width: 8,
+                    pixels: pixels,
+                }
+            })
+            .collect();
+        Font {
+            height: 8,
+            first_char: b' ',
+            default_char: b'?',
+            glyphs: glyphs,
+        }
+    }
+
+    /// Reads all of the raster fonts out of a Windows .fon file (a NE executable with font resources)
+    pub fn read_fon(data: &[u8]) -> Result<Vec<Font>> {
+        if data.len() < 0x40 || &data[0..2] != b"MZ" {
+            return Err(invalid_font("not a .fon file"));
+        }
+        let ne_offset = try!(read_u32(data, 0x3C)) as usize;
+        if data.len() < ne_offset + 2 || &data[ne_offset..ne_offset + 2] != b"NE" {
+            return Err(invalid_font(".fon file is missing its NE header"));
+        }
+
+        let resource_table = ne_offset + try!(read_u16(data, ne_offset + 0x24)) as usize;
+        let alignment_shift = try!(read_u16(data, resource_table)) as usize;
+        let mut fonts = Vec::new();
+        let mut offset = resource_table + 2;
+        loop {
+            let type_id = try!(read_u16(data, offset));
+            if type_id == 0 {
+                break;
+            }
+            let resource_count = try!(read_u16(data, offset + 2)) as usize;
+            offset += 8;
+            for _ in 0..resource_count {
+                if type_id == NE_RT_FONT {
+                    let start = (try!(read_u16(data, offset)) as usize) << alignment_shift;
+                    let length = (try!(read_u16(data, offset + 2)) as usize) << alignment_shift;
+                    if start + length > data.len() {
+                        return Err(invalid_font("font resource is out of bounds"));
+                    }
+                    fonts.push(try!(Font::read_fnt(&data[start..start + length])));
+                }
+                offset += 12;
+            }
+        }
+
+        if fonts.is_empty() {
+            return Err(invalid_font(".fon file has no fonts in it"));
+        }
+        Ok(fonts)
+    }
+
+    /// Reads a Windows raster font (.fnt, version 2 or 3)
+    pub fn read_fnt(data: &[u8]) -> Result<Font> {
+        let version = try!(read_u16(data, 0));
+        let (glyph_table, glyph_entry_size) = match version {
+            0x200 => (118, 4),
+            0x300 => (148, 6),
+            _ => return Err(invalid_font(&format!("unsupported .fnt version 0x{:x}", version))),
+        };
+        if try!(read_u16(data, 66)) & 1 != 0 {
+            return Err(invalid_font("vector fonts aren't supported"));
+        }
+
+        let height = try!(read_u16(data, 88)) as u32;
+        let first_char = try!(read_u8(data, 95));
+        let last_char = try!(read_u8(data, 96));
+        let default_char = first_char.wrapping_add(try!(read_u8(data, 97)));
+        if last_char < first_char {
+            return Err(invalid_font("font has no characters"));
+        }
+
+        let mut glyphs = Vec::new();
+        for index in 0..(last_char - first_char) as usize + 1 {
+            let entry = glyph_table + index * glyph_entry_size;
+            let width = try!(read_u16(data, entry)) as u32;
+            let bits_offset = if version == 0x200 {
+                try!(read_u16(data, entry + 2)) as usize
+            } else {
+                try!(read_u32(data, entry + 2)) as usize
+            };
+
+            // Glyph bits are stored as columns of bytes, each column being 8 pixels wide
+            let mut pixels = vec![0u8; (width * height) as usize];
+            for y in 0..height {
+                for x in 0..width {
+                    let byte_offset = bits_offset + (x / 8 * height + y) as usize;
+                    let byte = try!(read_u8(data, byte_offset));
+                    pixels[(y * width + x) as usize] = (byte >> (7 - x % 8)) & 1;
+                }
+            }
+            glyphs.push(Glyph {
+                width: width,
+                pixels: pixels,
+            });
+        }
+
+        Ok(Font {
+            height: height,
+            first_char: first_char,
+            default_char: default_char,
+            glyphs: glyphs,
+        })
+    }
+
+    /// Makes a copy of the font with every pixel scaled up by the given factor
+    pub fn scaled(&self, factor: u32) -> Font {
+        let glyphs = self.glyphs
+            .iter()
+            .map(|glyph| {
+                let width = glyph.width * factor;
+                let height = self.height * factor;
+                let mut pixels = Vec::with_capacity((width * height) as usize);
+                for y in 0..height {
+                    for x in 0..width {
+                        pixels.push(glyph.pixels[((y / factor) * glyph.width + x / factor) as usize]);
+                    }
+                }
+                Glyph {
+                    width: width,
+                    pixels: pixels,
+                }
+            })
+            .collect();
+        Font {
+            height: self.height * factor,
+            first_char: self.first_char,
+            default_char: self.default_char,
+            glyphs: glyphs,
+        }
+    }
+
+    pub fn glyphs<'a>(&'a self) -> &'a [Glyph] {
+        &self.glyphs
+    }
+
+    /// Index of the glyph to draw for the given character. Fonts are in the Windows-1252
+    /// code page, which matches Unicode for most of the characters in it.
+    pub fn glyph_index(&self, c: char) -> usize {
+        let last_char = self.first_char as u32 + self.glyphs.len() as u32 - 1;
+        let code = c as u32;
+        if code >= self.first_char as u32 && code <= last_char {
+            (code - self.first_char as u32) as usize
+        } else if self.default_char >= self.first_char &&
+                  ((self.default_char - self.first_char) as usize) < self.glyphs.len() {
+            (self.default_char - self.first_char) as usize
+        } else {
+            0
+        }
+    }
+
+    /// Width in pixels of a single line of text
+    pub fn text_width(&self, text: &str) -> u32 {
+        text.chars().map(|c| self.glyphs[self.glyph_index(c)].width).sum()
+    }
+}
+
+fn invalid_font(message: &str) -> ::error::Error {
+    ErrorKind::InvalidFont(message.into()).into()
+}
+
+fn read_u8(data: &[u8], offset: usize) -> Result<u8> {
+    data.get(offset).cloned().ok_or_else(|| invalid_font("unexpected end of font data"))
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    Ok(try!(read_u8(data, offset)) as u16 | (try!(read_u8(data, offset + 1)) as u16) << 8)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    Ok(try!(read_u16(data, offset)) as u32 | (try!(read_u16(data, offset + 2)) as u32) << 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Font;
+
+    /// Creates a version 2 .fnt with two 10x2 glyphs ('A' and 'B')
+    fn test_fnt() -> Vec<u8> {
+        let mut data = vec![0u8; 118 + 3 * 4];
+        data[0] = 0x00;
+        data[1] = 0x02;
+        data[88] = 2; // height
+        data[95] = b'A';
+        data[96] = b'B';
+        data[97] = 1; // default char is 'B'
+
+        let bits_start = data.len();
+        for (index, entry) in [(10u16, bits_start), (10u16, bits_start + 4), (0, 0)].iter().enumerate() {
+            let offset = 118 + index * 4;
+            data[offset] = entry.0 as u8;
+            data[offset + 2] = entry.1 as u8;
+        }
+
+        // 'A': two columns of bytes, two rows each
+        data.extend_from_slice(&[0b10000000, 0b00000001, 0b01000000, 0b11000000]);
+        // 'B': all set
+        data.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+        data
+    }
+
+    #[test]
+    fn test_read_fnt() {
+        let font = Font::read_fnt(&test_fnt()).unwrap();
+        assert_eq!(2, font.height);
+        assert_eq!(2, font.glyphs().len());
+
+        let a = &font.glyphs()[font.glyph_index('A')];
+        assert_eq!(10, a.width);
+        assert_eq!(vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+                        0, 0, 0, 0, 0, 0, 0, 1, 1, 1],
+                   a.pixels);
+
+        assert_eq!(1, font.glyph_index('B'));
+        assert_eq!(1, font.glyph_index('z'));
+        assert_eq!(30, font.text_width("ABz"));
+    }
+
+    #[test]
+    fn test_read_fnt_rejects_garbage() {
+        assert!(Font::read_fnt(&[0u8; 4]).is_err());
+        assert!(Font::read_fnt(&test_fnt()[0..120]).is_err());
+        assert!(Font::read_fon(&test_fnt()).is_err());
+    }
+
+    #[test]
+    fn test_builtin_and_scaled() {
+        let font = Font::builtin();
+        assert_eq!(8, font.height);
+        assert_eq!(16, font.text_width("Hi"));
+        assert_eq!(font.glyph_index('?'), font.glyph_index('\u{263A}'));
+
+        let period = &font.glyphs()[font.glyph_index('.')];
+        assert_eq!(1, period.pixels[5 * 8 + 2]);
+        assert_eq!(0, period.pixels[5 * 8 + 1]);
+
+        let large = font.scaled(2);
+        assert_eq!(16, large.height);
+        let large_period = &large.glyphs()[large.glyph_index('.')];
+        assert_eq!(16, large_period.width);
+        assert_eq!(1, large_period.pixels[11 * 16 + 5]);
+        assert_eq!(0, large_period.pixels[11 * 16 + 3]);
+    }
+}
@@ -0,0 +1,224 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::{ChainErr, Result};
+use font::Font;
+use game_dir::GameDir;
+use media::{Renderer, Texture};
+use nalgebra::Vector2;
+use render_command::RenderTextParams;
+
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::mem;
+use std::path::Path;
+use std::rc::Rc;
+use types::{Color, Rect};
+
+/// Height of the font that's closest to what the original game uses for most of its text
+const NORMAL_FONT_HEIGHT: u32 = 13;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum FontStyle {
+    Small,
+    Normal,
+    Large,
+}
+
+/// Where text is drawn relative to its position
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TextAlignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// All of a font's glyphs in a single row, drawn in one color
+struct FontAtlas {
+    texture: Texture,
+    glyph_offsets: Vec<i32>,
+}
+
+pub struct FontManager {
+    fonts: Vec<Font>,
+    small: usize,
+    normal: usize,
+    large: usize,
+    atlases: HashMap<(FontStyle, Color), FontAtlas>,
+}
+
+pub type FontManagerRef = Rc<RefCell<FontManager>>;
+
+impl FontManager {
+    /// Loads the given font file (.fon or .fnt) if there is one. Otherwise, looks for fonts in the
+    /// game data directory and falls back to a built-in font if there aren't any.
+    pub fn new(game_dir: &GameDir, font_file: Option<&Path>) -> Result<FontManagerRef> {
+        let mut fonts = Vec::new();
+        if let Some(font_file) = font_file {
+            fonts = try!(read_font_file(font_file));
+        } else {
+            let mut font_files = game_dir.find_files_with_extension("", "fon");
+            font_files.extend(game_dir.find_files_with_extension("", "fnt"));
+            font_files.extend(game_dir.find_files_with_extension("data", "fon"));
+            font_files.extend(game_dir.find_files_with_extension("data", "fnt"));
+            for font_file in font_files {
+                match read_font_file(&font_file) {
+                    Ok(file_fonts) => fonts.extend(file_fonts),
+                    Err(err) => println!("Skipping font {:?}: {}", font_file, err),
+                }
+            }
+        }
+        Ok(FontManager::with_fonts(fonts))
+    }
+
+    /// Uses the smallest font for small text, the largest for large text, and the one closest to
+    /// the original game's text size for everything else
+    pub fn with_fonts(mut fonts: Vec<Font>) -> FontManagerRef {
+        if fonts.is_empty() {
+            let builtin = Font::builtin();
+            let large = builtin.scaled(2);
+            fonts = vec![builtin, large];
+        }
+        fonts.sort_by_key(|font| font.height);
+
+        let normal = (0..fonts.len())
+            .min_by_key(|&index| (fonts[index].height as i32 - NORMAL_FONT_HEIGHT as i32).abs())
+            .unwrap();
+        let large = fonts.len() - 1;
+        Rc::new(RefCell::new(FontManager {
+            fonts: fonts,
+            small: 0,
+            normal: normal,
+            large: large,
+            atlases: HashMap::new(),
+        }))
+    }
+
+    pub fn font<'a>(&'a self, style: FontStyle) -> &'a Font {
+        let index = match style {
+            FontStyle::Small => self.small,
+            FontStyle::Normal => self.normal,
+            FontStyle::Large => self.large,
+        };
+        &self.fonts[index]
+    }
+
+    /// Size in pixels of the given (possibly multi-line) text
+    pub fn text_size(&self, style: FontStyle, text: &str) -> Vector2<u32> {
+        let font = self.font(style);
+        let width = text.lines().map(|line| font.text_width(line)).max().unwrap_or(0);
+        Vector2::new(width, font.height * text.lines().count() as u32)
+    }
+
+    pub fn render_text(&mut self, renderer: &mut Renderer, params: &RenderTextParams) -> Result<()> {
+        let key = (params.font_style, params.color);
+        if !self.atlases.contains_key(&key) {
+            let atlas = try!(create_atlas(renderer, self.font(params.font_style), params.color));
+            self.atlases.insert(key, atlas);
+        }
+
+        let font = self.font(params.font_style);
+        let atlas = &self.atlases[&key];
+        let line_height = font.height as i32;
+        for (line_index, line) in params.text.lines().enumerate() {
+            let line_width = font.text_width(line) as i32;
+            let mut x = match params.alignment {
+                TextAlignment::Left => params.position.x,
+                TextAlignment::Center => params.position.x - line_width / 2,
+                TextAlignment::Right => params.position.x - line_width,
+            };
+            let y = params.position.y + line_index as i32 * line_height;
+
+            for c in line.chars() {
+                let glyph_index = font.glyph_index(c);
+                let glyph_width = font.glyphs()[glyph_index].width as i32;
+                let mut src_rect = Rect::of(atlas.glyph_offsets[glyph_index], 0, glyph_width, line_height);
+                let mut dst_rect = Rect::of(x, y, glyph_width, line_height);
+                x += glyph_width;
+
+                if let Some(clip_rect) = params.clip_rect {
+                    match dst_rect.intersection(&clip_rect) {
+                        Some(visible) => {
+                            src_rect = Rect::of(src_rect.x + visible.x - dst_rect.x,
+                                                visible.y - dst_rect.y,
+                                                visible.w,
+                                                visible.h);
+                            dst_rect = visible;
+                        }
+                        None => continue,
+                    }
+                }
+                if dst_rect.w > 0 {
+                    renderer.render_texture(&atlas.texture, Some(src_rect), dst_rect, false, false);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn read_font_file(file_name: &Path) -> Result<Vec<Font>> {
+    let mut data = Vec::new();
+    let mut file = try!(File::open(file_name).chain_err(|| format!("Failed to open font {:?}", file_name)));
+    try!(file.read_to_end(&mut data).chain_err(|| format!("Failed to read font {:?}", file_name)));
+    if data.starts_with(b"MZ") {
+        Font::read_fon(&data)
+    } else {
+        Ok(vec![try!(Font::read_fnt(&data))])
+    }
+}
+
+fn create_atlas(renderer: &mut Renderer, font: &Font, color: Color) -> Result<FontAtlas> {
+    // Textures can't be empty, even if every glyph is
+    let height = cmp::max(font.height as usize, 1);
+    let width = cmp::max(font.glyphs().iter().map(|glyph| glyph.width as usize).sum::<usize>(), 1);
+
+    let packed = (color.r as u32) << 24 | (color.g as u32) << 16 | (color.b as u32) << 8 | color.a as u32;
+    let texel = unsafe { mem::transmute::<u32, [u8; 4]>(packed) };
+
+    let mut pixels = vec![0u8; width * height * 4];
+    let mut glyph_offsets = Vec::with_capacity(font.glyphs().len());
+    let mut offset = 0;
+    for glyph in font.glyphs() {
+        let glyph_width = glyph.width as usize;
+        for y in 0..font.height as usize {
+            for x in 0..glyph_width {
+                if glyph.pixels[y * glyph_width + x] != 0 {
+                    let index = (y * width + offset + x) * 4;
+                    pixels[index..index + 4].copy_from_slice(&texel);
+                }
+            }
+        }
+        glyph_offsets.push(offset as i32);
+        offset += glyph_width;
+    }
+
+    let (width, height) = (width as u32, height as u32);
+    let mut texture = try!(renderer.create_texture(width, height));
+    try!(texture.update(Rect::of(0, 0, width as i32, height as i32), &pixels));
+    Ok(FontAtlas {
+        texture: texture,
+        glyph_offsets: glyph_offsets,
+    })
+}
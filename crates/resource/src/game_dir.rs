@@ -19,15 +19,85 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use drs_manager::DrsKey;
 use error::{ErrorKind, Result, Error, ChainErr};
 
 use std::ascii::AsciiExt;
+use std::fmt;
 use std::fs;
 use std::path::{Component, Path, PathBuf};
 
+/// Game data files other than the DRS archives, and whether or not Chariot can run without them
+const OTHER_GAME_DATA_FILES: &'static [(&'static str, bool)] = &[("language.dll", false),
+                                                                 ("data/empires.dat", true),
+                                                                 ("data/tileedge.dat", false)];
+
+/// Every game data file Chariot knows how to use, and whether or not it can run without it
+fn game_data_files() -> Vec<(&'static str, bool)> {
+    let mut files: Vec<(&'static str, bool)> = DrsKey::all()
+        .iter()
+        .map(|drs_key| (drs_key.path(), drs_key.is_required()))
+        .chain(OTHER_GAME_DATA_FILES.iter().cloned())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Report of which game data files were found in the game data directory
+#[derive(Clone, Debug)]
+pub struct Capabilities {
+    files: Vec<(&'static str, Option<PathBuf>)>,
+}
+
+impl Capabilities {
+    /// Returns true if the given game data file (i.e., "data/sounds.drs") was found
+    pub fn has(&self, file_name: &str) -> bool {
+        self.files.iter().any(|&(name, ref path)| name == file_name && path.is_some())
+    }
+
+    pub fn has_drs(&self, drs_key: DrsKey) -> bool {
+        self.has(drs_key.path())
+    }
+
+    pub fn audio(&self) -> bool {
+        self.has_drs(DrsKey::Sounds)
+    }
+
+    pub fn terrain_borders(&self) -> bool {
+        self.has_drs(DrsKey::Border)
+    }
+
+    pub fn localization(&self) -> bool {
+        self.has("language.dll")
+    }
+
+    pub fn missing(&self) -> Vec<&'static str> {
+        self.files.iter().filter(|&&(_, ref path)| path.is_none()).map(|&(name, _)| name).collect()
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for &(name, ref path) in &self.files {
+            match *path {
+                Some(ref path) => try!(writeln!(f, "Found {:?} at {:?}", name, path)),
+                None => try!(writeln!(f, "Missing {:?}", name)),
+            }
+        }
+        if !self.audio() {
+            try!(writeln!(f, "Audio is disabled"));
+        }
+        if !self.terrain_borders() {
+            try!(writeln!(f, "Terrain borders are disabled"));
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 pub struct GameDir {
     dir: PathBuf,
+    capabilities: Capabilities,
 }
 
 impl GameDir {
@@ -43,24 +113,25 @@ impl GameDir {
             return Err(error(dir, "Given game data directory isn't a directory"));
         }
 
-        let game_dir = GameDir { dir: dir.to_path_buf() };
-        for file_name in &["language.dll",
-                           "data/border.drs",
-                           "data/empires.dat",
-                           "data/graphics.drs",
-                           "data/interfac.drs",
-                           "data/sounds.drs",
-                           "data/terrain.drs",
-                           "data/tileedge.dat"] {
-            if let Ok(actual_file_name) = game_dir.find_file(file_name) {
-                println!("Found {:?} at {:?}", file_name, actual_file_name);
-            } else {
+        let mut game_dir = GameDir {
+            dir: dir.to_path_buf(),
+            capabilities: Capabilities { files: Vec::new() },
+        };
+        for (file_name, required) in game_data_files() {
+            let actual_file_name = game_dir.find_file(file_name).ok();
+            if required && actual_file_name.is_none() {
                 return Err(error(dir, &format!("Failed to find {}", file_name)));
             }
+            game_dir.capabilities.files.push((file_name, actual_file_name));
         }
         Ok(game_dir)
     }
 
+    /// Which of the game data files are available
+    pub fn capabilities<'a>(&'a self) -> &'a Capabilities {
+        &self.capabilities
+    }
+
     /// Find a file in the game data directory even if the requested case doesn't match
     /// the case of the file name on the file system (for case-sensitive file systems)
     pub fn find_file<P: AsRef<Path>>(&self, file_name: P) -> Result<PathBuf> {
@@ -89,6 +160,35 @@ impl GameDir {
         }
         Ok(full_path)
     }
+
+    /// Lists the files with the given extension (i.e., "fon") in the given directory of the game
+    /// data directory, regardless of case
+    pub fn find_files_with_extension<P: AsRef<Path>>(&self, dir: P, extension: &str) -> Vec<PathBuf> {
+        let dir = if dir.as_ref().components().next().is_none() {
+            self.dir.clone()
+        } else {
+            match self.find_file(dir) {
+                Ok(dir) => dir,
+                Err(_) => return Vec::new(),
+            }
+        };
+
+        let mut files: Vec<PathBuf> = match fs::read_dir(&dir) {
+            Ok(entries) => {
+                entries.filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        let matches = path.extension()
+                            .map_or(false, |ext| ext.to_string_lossy().eq_ignore_ascii_case(extension));
+                        matches && path.is_file()
+                    })
+                    .collect()
+            }
+            Err(_) => Vec::new(),
+        };
+        files.sort();
+        files
+    }
 }
 
 fn error(dir: &Path, msg: &str) -> Error {
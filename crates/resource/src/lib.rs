@@ -38,12 +38,25 @@ extern crate chariot_types as types;
 mod error;
 mod game_dir;
 mod drs_manager;
+mod font;
+mod font_manager;
+mod shape_decoder;
 mod shape_manager;
 mod shape_metadata;
 mod render_command;
+mod sound_manager;
+mod palettes;
+mod texture_atlas;
 
 pub use drs_manager::{DrsKey, DrsManager, DrsManagerRef};
-pub use game_dir::GameDir;
+pub use error::{ChainErr, Error, ErrorKind, Result};
+pub use font::{Font, Glyph};
+pub use font_manager::{FontManager, FontManagerRef, FontStyle, TextAlignment};
+pub use game_dir::{Capabilities, GameDir};
+pub use palettes::{DEFAULT_PALETTE_FILE_ID, LAVA_PALETTE_CYCLE, Palette, PaletteCycle, WATER_PALETTE_CYCLE};
 pub use render_command::*;
-pub use shape_manager::{Shape, ShapeKey, ShapeManager, ShapeManagerRef};
-pub use shape_metadata::{ShapeMetadata, ShapeMetadataKey, ShapeMetadataStore, ShapeMetadataStoreRef};
+pub use shape_manager::{DEFAULT_TEXTURE_BUDGET, MissingShapePolicy, MissingShapeReport, Shape, ShapeKey,
+                        ShapeManager, ShapeManagerRef, ShapeManagerStats};
+pub use shape_metadata::{FrameMetadata, ShapeMetadata, ShapeMetadataKey, ShapeMetadataStore,
+                         ShapeMetadataStoreRef};
+pub use sound_manager::{SoundManager, SoundManagerRef};
@@ -0,0 +1,196 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use drs::DrsFileType;
+use drs_manager::{DrsKey, DrsManager};
+use error::{ErrorKind, Result};
+use identifier::PaletteId;
+use palette::{self, PaletteColor};
+
+use std::io;
+use std::sync::Arc;
+use types::Fixed;
+
+/// Palette that most of the game's graphics use
+pub const DEFAULT_PALETTE_FILE_ID: u32 = 50500;
+
+// The original game's palette cycling isn't documented anywhere we know of: the two ranges below
+// (indices 241-247 and 248-254 of palette 50500) are unverified guesses, so dump palette 50500
+// from interfac.drs and compare it with the water and lava terrain SLPs before relying on them.
+
+/// The default palette's water colors, which the original game rotates so that water shimmers
+pub const WATER_PALETTE_CYCLE: PaletteCycle = PaletteCycle {
+    first_index: 248,
+    count: 7,
+    step_interval: fixed_const!(0.2),
+};
+
+/// The default palette's lava colors, which rotate faster than water's to make lava flow
+pub const LAVA_PALETTE_CYCLE: PaletteCycle = PaletteCycle {
+    first_index: 241,
+    count: 7,
+    step_interval: fixed_const!(0.1),
+};
+
+/// Rotates a range of palette colors by one index every `step_interval` seconds.
+/// Any pixel using a color in the range appears to animate.
+#[derive(Copy, Clone, Debug)]
+pub struct PaletteCycle {
+    pub first_index: u8,
+    pub count: u8,
+    pub step_interval: Fixed,
+}
+
+impl PaletteCycle {
+    pub fn new(first_index: u8, count: u8, step_interval: Fixed) -> PaletteCycle {
+        PaletteCycle {
+            first_index: first_index,
+            count: count,
+            step_interval: step_interval,
+        }
+    }
+
+    fn contains(&self, index: u8) -> bool {
+        index >= self.first_index && (index as usize) < self.first_index as usize + self.count as usize
+    }
+}
+
+struct CycleState {
+    cycle: PaletteCycle,
+    elapsed: Fixed,
+    offset: usize,
+}
+
+pub struct Palette {
+    base_colors: Vec<u32>,
+    colors: Arc<Vec<u32>>,
+    cycles: Vec<CycleState>,
+}
+
+impl Palette {
+    pub fn new(colors: Vec<u32>) -> Palette {
+        Palette {
+            colors: Arc::new(colors.clone()),
+            base_colors: colors,
+            cycles: Vec::new(),
+        }
+    }
+
+    pub fn load(drs_manager: &DrsManager, palette_id: PaletteId) -> Result<Palette> {
+        let interfac = try!(drs_manager.get(DrsKey::Interfac));
+        let bin_table = try!(interfac.find_table(DrsFileType::Binary)
+            .ok_or(ErrorKind::InterfacBinaryTableMissing));
+        let palette_contents = &try!(bin_table.find_file_contents(*palette_id)
+            .ok_or(ErrorKind::PaletteNotFound(*palette_id)));
+        let colors = try!(palette::read_from(&mut io::Cursor::new(palette_contents)))
+            .iter()
+            .map(|c: &PaletteColor| -> u32 { (*c).into() })
+            .collect();
+        Ok(Palette::new(colors))
+    }
+
+    /// The palette's colors with the cycles at their current positions
+    pub fn colors(&self) -> Arc<Vec<u32>> {
+        self.colors.clone()
+    }
+
+    pub fn has_cycles(&self) -> bool {
+        !self.cycles.is_empty()
+    }
+
+    /// Returns true if any of the given palette indices are affected by a cycle
+    pub fn is_cycled(&self, indices: &[u8]) -> bool {
+        self.has_cycles() && indices.iter().any(|&i| self.cycles.iter().any(|c| c.cycle.contains(i)))
+    }
+
+    pub fn add_cycle(&mut self, cycle: PaletteCycle) {
+        self.cycles.push(CycleState {
+            cycle: cycle,
+            elapsed: 0.into(),
+            offset: 0,
+        });
+    }
+
+    pub fn clear_cycles(&mut self) {
+        self.cycles.clear();
+        self.colors = Arc::new(self.base_colors.clone());
+    }
+
+    /// Advances the cycles and returns true if any of the colors changed
+    pub fn update(&mut self, time_step: Fixed) -> bool {
+        let mut changed = false;
+        for state in &mut self.cycles {
+            if state.cycle.count == 0 || state.cycle.step_interval <= 0.into() {
+                continue;
+            }
+            state.elapsed += time_step;
+            while state.elapsed >= state.cycle.step_interval {
+                state.elapsed -= state.cycle.step_interval;
+                state.offset = (state.offset + 1) % state.cycle.count as usize;
+                changed = true;
+            }
+        }
+
+        if changed {
+            let mut colors = self.base_colors.clone();
+            for state in &self.cycles {
+                let (first, count) = (state.cycle.first_index as usize, state.cycle.count as usize);
+                for i in 0..count {
+                    if first + i < colors.len() {
+                        let src = first + (i + state.offset) % count;
+                        colors[first + i] = self.base_colors.get(src).cloned().unwrap_or(0);
+                    }
+                }
+            }
+            self.colors = Arc::new(colors);
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Palette, PaletteCycle};
+    use types::Fixed;
+
+    #[test]
+    fn test_palette_cycle() {
+        let mut palette = Palette::new(vec![0, 1, 2, 3, 4, 5]);
+        palette.add_cycle(PaletteCycle::new(2, 3, Fixed::from(1)));
+        assert!(palette.is_cycled(&[0, 4]));
+        assert!(!palette.is_cycled(&[0, 1, 5]));
+
+        assert!(!palette.update(Fixed::from(0.5)));
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], *palette.colors());
+
+        assert!(palette.update(Fixed::from(0.5)));
+        assert_eq!(vec![0, 1, 3, 4, 2, 5], *palette.colors());
+
+        // Large time steps can advance more than once
+        assert!(palette.update(Fixed::from(2)));
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], *palette.colors());
+
+        palette.update(Fixed::from(1));
+        palette.clear_cycles();
+        assert!(!palette.has_cycles());
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], *palette.colors());
+    }
+}
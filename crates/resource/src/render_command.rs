@@ -19,45 +19,91 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use media::Renderer;
+use error::Result;
+use font_manager::{FontManager, FontStyle, TextAlignment};
+use media::{self, Renderer};
 use nalgebra::Vector2;
 use std::cmp::{Ordering, PartialOrd};
 use super::{ShapeKey, ShapeManager};
 use types::{Color, Rect};
 
-#[derive(Copy, Clone, Debug)]
+const UNTINTED: Color = Color {
+    r: 255,
+    g: 255,
+    b: 255,
+    a: 255,
+};
+
+#[derive(Clone, Debug)]
 pub enum RenderCommand {
     RenderShape(RenderOrder, RenderShapeParams),
     RenderRect(RenderOrder, RenderRectParams),
     RenderLine(RenderOrder, RenderLineParams),
+    RenderPolygon(RenderOrder, RenderPolygonParams),
+    RenderEllipse(RenderOrder, RenderEllipseParams),
+    RenderText(RenderOrder, RenderTextParams),
 }
 
 impl RenderCommand {
     pub fn render_all(renderer: &mut Renderer,
                       shape_manager: &mut ShapeManager,
-                      commands: &mut Vec<RenderCommand>) {
+                      font_manager: &mut FontManager,
+                      commands: &mut Vec<RenderCommand>)
+                      -> Result<()> {
         use RenderCommand::*;
+        shape_manager.update_textures(renderer);
+
+        // Consecutive primitives of the same color are drawn together
+        let mut batch = PrimitiveBatch::new();
+
         commands.sort_by(|a, b| a.order().cmp(b.order()));
         for command in commands {
             match *command {
                 RenderShape(_, params) => {
-                    shape_manager.get(&params.shape_key, renderer)
-                        .unwrap()
-                        .render_frame(renderer,
-                                      params.frame_num as usize,
-                                      &params.position,
-                                      params.flip_horizontal,
-                                      params.flip_vertical);
+                    batch.flush(renderer);
+                    let tinted = params.tint != UNTINTED;
+                    if tinted {
+                        renderer.set_texture_tint(params.tint);
+                    }
+
+                    // Shapes that failed to load (or whose DRS is missing) are skipped
+                    let result = shape_manager.render_frame(renderer,
+                                                            &params.shape_key,
+                                                            params.frame_num as usize,
+                                                            &params.position,
+                                                            params.flip_horizontal,
+                                                            params.flip_vertical);
+                    if tinted {
+                        renderer.set_texture_tint(UNTINTED);
+                    }
+                    try!(result);
                 }
                 RenderRect(_, params) => {
-                    renderer.render_rect(params.rect);
+                    let batch = batch.for_color(renderer, params.color);
+                    if params.filled {
+                        batch.fill_rects.push(params.rect);
+                    } else {
+                        batch.outline_rects.push(params.rect);
+                    }
                 }
                 RenderLine(_, params) => {
-                    renderer.set_render_color(params.color);
-                    renderer.render_line(params.points[0], params.points[1]);
+                    batch.for_color(renderer, params.color).lines.push(params.points);
+                }
+                RenderPolygon(_, ref params) => {
+                    batch.for_color(renderer, params.color).add_polygon(&params.points, params.filled);
+                }
+                RenderEllipse(_, params) => {
+                    let points = media::ellipse_polygon(params.center, params.radii);
+                    batch.for_color(renderer, params.color).add_polygon(&points, params.filled);
+                }
+                RenderText(_, ref params) => {
+                    batch.flush(renderer);
+                    try!(font_manager.render_text(renderer, params));
                 }
             }
         }
+        batch.flush(renderer);
+        Ok(())
     }
 
     pub fn new_shape(layer: u16,
@@ -77,6 +123,43 @@ impl RenderCommand {
         RenderCommand::RenderShape(order, params)
     }
 
+    /// Multiplies a shape's colors (and alpha) by the tint; has no effect on other commands
+    pub fn tinted(mut self, tint: Color) -> RenderCommand {
+        if let RenderCommand::RenderShape(_, ref mut params) = self {
+            params.tint = tint;
+        }
+        self
+    }
+
+    pub fn new_rect(layer: u16, depth: i32, color: Color, rect: Rect, filled: bool) -> RenderCommand {
+        let order = RenderOrder::new(layer, depth, false);
+        let params = RenderRectParams::new(color, rect, filled);
+        RenderCommand::RenderRect(order, params)
+    }
+
+    pub fn new_polygon(layer: u16,
+                       depth: i32,
+                       color: Color,
+                       points: Vec<Vector2<i32>>,
+                       filled: bool)
+                       -> RenderCommand {
+        let order = RenderOrder::new(layer, depth, false);
+        let params = RenderPolygonParams::new(color, points, filled);
+        RenderCommand::RenderPolygon(order, params)
+    }
+
+    pub fn new_ellipse(layer: u16,
+                       depth: i32,
+                       color: Color,
+                       center: Vector2<i32>,
+                       radii: Vector2<i32>,
+                       filled: bool)
+                       -> RenderCommand {
+        let order = RenderOrder::new(layer, depth, false);
+        let params = RenderEllipseParams::new(color, center, radii, filled);
+        RenderCommand::RenderEllipse(order, params)
+    }
+
     pub fn new_line(layer: u16,
                     depth: i32,
                     color: Color,
@@ -88,9 +171,35 @@ impl RenderCommand {
         RenderCommand::RenderLine(order, params)
     }
 
-    pub fn new_debug_rect(layer: u16, depth: i32, rect: Rect) -> RenderCommand {
+    pub fn new_text(layer: u16,
+                    depth: i32,
+                    text: String,
+                    font_style: FontStyle,
+                    color: Color,
+                    position: Vector2<i32>,
+                    alignment: TextAlignment,
+                    clip_rect: Option<Rect>)
+                    -> RenderCommand {
+        let order = RenderOrder::new(layer, depth, false);
+        let params = RenderTextParams::new(text, font_style, color, position, alignment, clip_rect);
+        RenderCommand::RenderText(order, params)
+    }
+
+    pub fn new_debug_text(layer: u16,
+                          depth: i32,
+                          text: String,
+                          color: Color,
+                          position: Vector2<i32>)
+                          -> RenderCommand {
+        let order = RenderOrder::new(layer, depth, true);
+        let params =
+            RenderTextParams::new(text, FontStyle::Small, color, position, TextAlignment::Left, None);
+        RenderCommand::RenderText(order, params)
+    }
+
+    pub fn new_debug_rect(layer: u16, depth: i32, color: Color, rect: Rect) -> RenderCommand {
         let order = RenderOrder::new(layer, depth, true);
-        let params = RenderRectParams::new(rect);
+        let params = RenderRectParams::new(color, rect, false);
         RenderCommand::RenderRect(order, params)
     }
 
@@ -111,6 +220,9 @@ impl RenderCommand {
             RenderShape(ref order, _) => order,
             RenderRect(ref order, _) => order,
             RenderLine(ref order, _) => order,
+            RenderPolygon(ref order, _) => order,
+            RenderEllipse(ref order, _) => order,
+            RenderText(ref order, _) => order,
         }
     }
 }
@@ -154,6 +266,9 @@ pub struct RenderShapeParams {
     pub position: Vector2<i32>,
     pub flip_horizontal: bool,
     pub flip_vertical: bool,
+
+    /// Multiplied with the shape's colors; white leaves them unchanged
+    pub tint: Color,
 }
 
 impl RenderShapeParams {
@@ -169,18 +284,25 @@ impl RenderShapeParams {
             position: position,
             flip_horizontal: flip_horizontal,
             flip_vertical: flip_vertical,
+            tint: UNTINTED,
         }
     }
 }
 
 #[derive(Copy, Clone, Debug)]
 pub struct RenderRectParams {
+    pub color: Color,
     pub rect: Rect,
+    pub filled: bool,
 }
 
 impl RenderRectParams {
-    pub fn new(rect: Rect) -> RenderRectParams {
-        RenderRectParams { rect: rect }
+    pub fn new(color: Color, rect: Rect, filled: bool) -> RenderRectParams {
+        RenderRectParams {
+            color: color,
+            rect: rect,
+            filled: filled,
+        }
     }
 }
 
@@ -198,3 +320,129 @@ impl RenderLineParams {
         }
     }
 }
+
+#[derive(Clone, Debug)]
+pub struct RenderPolygonParams {
+    pub color: Color,
+    pub points: Vec<Vector2<i32>>,
+    pub filled: bool,
+}
+
+impl RenderPolygonParams {
+    pub fn new(color: Color, points: Vec<Vector2<i32>>, filled: bool) -> RenderPolygonParams {
+        RenderPolygonParams {
+            color: color,
+            points: points,
+            filled: filled,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct RenderEllipseParams {
+    pub color: Color,
+    pub center: Vector2<i32>,
+
+    /// Horizontal and vertical radius
+    pub radii: Vector2<i32>,
+    pub filled: bool,
+}
+
+impl RenderEllipseParams {
+    pub fn new(color: Color, center: Vector2<i32>, radii: Vector2<i32>, filled: bool) -> RenderEllipseParams {
+        RenderEllipseParams {
+            color: color,
+            center: center,
+            radii: radii,
+            filled: filled,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RenderTextParams {
+    pub text: String,
+    pub font_style: FontStyle,
+    pub color: Color,
+
+    /// Top of the text; left, center, or right depending on the alignment
+    pub position: Vector2<i32>,
+    pub alignment: TextAlignment,
+
+    /// Only the part of the text inside of this rect is drawn
+    pub clip_rect: Option<Rect>,
+}
+
+impl RenderTextParams {
+    pub fn new(text: String,
+               font_style: FontStyle,
+               color: Color,
+               position: Vector2<i32>,
+               alignment: TextAlignment,
+               clip_rect: Option<Rect>)
+               -> RenderTextParams {
+        RenderTextParams {
+            text: text,
+            font_style: font_style,
+            color: color,
+            position: position,
+            alignment: alignment,
+            clip_rect: clip_rect,
+        }
+    }
+}
+
+/// Primitives waiting to be drawn in a single color
+struct PrimitiveBatch {
+    color: Option<Color>,
+    fill_rects: Vec<Rect>,
+    outline_rects: Vec<Rect>,
+    lines: Vec<[Vector2<i32>; 2]>,
+}
+
+impl PrimitiveBatch {
+    fn new() -> PrimitiveBatch {
+        PrimitiveBatch {
+            color: None,
+            fill_rects: Vec::new(),
+            outline_rects: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Returns the batch to add primitives of the given color to, drawing what's already
+    /// batched if it's a different color
+    fn for_color(&mut self, renderer: &mut Renderer, color: Color) -> &mut PrimitiveBatch {
+        if self.color != Some(color) {
+            self.flush(renderer);
+            self.color = Some(color);
+        }
+        self
+    }
+
+    fn add_polygon(&mut self, points: &[Vector2<i32>], filled: bool) {
+        if filled {
+            self.fill_rects.extend(media::polygon_spans(points));
+        } else {
+            self.lines.extend(media::polygon_outline(points));
+        }
+    }
+
+    fn flush(&mut self, renderer: &mut Renderer) {
+        if let Some(color) = self.color.take() {
+            renderer.set_render_color(color);
+            if !self.fill_rects.is_empty() {
+                renderer.fill_rects(&self.fill_rects);
+            }
+            if !self.outline_rects.is_empty() {
+                renderer.render_rects(&self.outline_rects);
+            }
+            if !self.lines.is_empty() {
+                renderer.render_lines(&self.lines);
+            }
+            self.fill_rects.clear();
+            self.outline_rects.clear();
+            self.lines.clear();
+        }
+    }
+}
@@ -0,0 +1,158 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::Result;
+use media;
+use nalgebra::Vector2;
+use shape_manager::ShapeKey;
+use slp::SlpFile;
+
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Drawn in place of shapes that are missing from their DRS archives
+const PLACEHOLDER_SLP: &'static [u8] = include_bytes!("../../../data/nope-64x64.slp");
+
+/// A single SLP frame that has been converted into RGBA8888 pixels
+pub struct DecodedFrame {
+    pub width: u32,
+    pub height: u32,
+    pub center: Vector2<i32>,
+    pub pixels: Vec<u8>,
+
+    /// The palette indices the RGBA pixels were converted from
+    pub indexed_pixels: Vec<u8>,
+}
+
+pub struct DecodedShape {
+    pub shape_key: ShapeKey,
+    pub frames: ::std::result::Result<Vec<DecodedFrame>, String>,
+}
+
+struct DecodeJob {
+    shape_key: ShapeKey,
+
+    /// Raw SLP file contents; the placeholder SLP gets decoded if there aren't any
+    slp_contents: Option<Vec<u8>>,
+    palette: Arc<Vec<u32>>,
+}
+
+/// Pool of worker threads that parse SLP files and convert them into RGBA pixels so that
+/// the only work left on the render thread is uploading the pixels into textures
+pub struct ShapeDecoder {
+    job_sender: Option<Sender<DecodeJob>>,
+    result_receiver: Receiver<DecodedShape>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ShapeDecoder {
+    pub fn new(worker_count: usize) -> ShapeDecoder {
+        let (job_sender, job_receiver) = mpsc::channel::<DecodeJob>();
+        let (result_sender, result_receiver) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let workers = (0..worker_count)
+            .map(|index| {
+                let job_receiver = job_receiver.clone();
+                let result_sender = result_sender.clone();
+                thread::Builder::new()
+                    .name(format!("shape-decoder-{}", index))
+                    .spawn(move || {
+                        loop {
+                            // The lock is released as soon as a job has been received
+                            let job = match job_receiver.lock().unwrap().recv() {
+                                Ok(job) => job,
+                                Err(_) => break,
+                            };
+                            let decoded = DecodedShape {
+                                shape_key: job.shape_key,
+                                frames: decode(&job).map_err(|err| format!("{}", err)),
+                            };
+                            if result_sender.send(decoded).is_err() {
+                                break;
+                            }
+                        }
+                    })
+                    .expect("spawn shape decoder thread")
+            })
+            .collect();
+
+        ShapeDecoder {
+            job_sender: Some(job_sender),
+            result_receiver: result_receiver,
+            workers: workers,
+        }
+    }
+
+    /// Queues a shape for decoding
+    pub fn submit(&self, shape_key: ShapeKey, slp_contents: Option<Vec<u8>>, palette: Arc<Vec<u32>>) {
+        let job = DecodeJob {
+            shape_key: shape_key,
+            slp_contents: slp_contents,
+            palette: palette,
+        };
+        self.job_sender.as_ref().unwrap().send(job).expect("shape decoder threads are running");
+    }
+
+    /// Returns a decoded shape if one is ready without blocking
+    pub fn try_recv(&self) -> Option<DecodedShape> {
+        self.result_receiver.try_recv().ok()
+    }
+
+    /// Waits for the next decoded shape
+    pub fn recv(&self) -> Option<DecodedShape> {
+        self.result_receiver.recv().ok()
+    }
+}
+
+impl Drop for ShapeDecoder {
+    fn drop(&mut self) {
+        // Closing the job channel lets the workers finish up
+        self.job_sender = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn decode(job: &DecodeJob) -> Result<Vec<DecodedFrame>> {
+    let player_color = *job.shape_key.player_color;
+    let slp_contents = match job.slp_contents {
+        Some(ref slp_contents) => &slp_contents[..],
+        None => PLACEHOLDER_SLP,
+    };
+    let slp = try!(SlpFile::read_from(&mut io::Cursor::new(slp_contents), player_color));
+
+    let mut frames = Vec::new();
+    for shape in slp.shapes {
+        let (width, height) = (shape.header.width, shape.header.height);
+        frames.push(DecodedFrame {
+            width: width,
+            height: height,
+            center: Vector2::new(shape.header.center_x, shape.header.center_y),
+            pixels: try!(media::to_rgba(&job.palette, &shape.pixels, width as usize, height as usize)),
+            indexed_pixels: shape.pixels,
+        });
+    }
+    Ok(frames)
+}
@@ -22,97 +22,460 @@
 
 use drs::DrsFileType;
 use drs_manager::{DrsKey, DrsManagerRef};
-use error::{ErrorKind, Result};
-use identifier::{PlayerColorId, SlpFileId};
-use media::{Renderer, Texture, TextureBuilder};
+use error::{Error, ErrorKind, Result};
+use identifier::{PaletteId, PlayerColorId, SlpFileId};
+use media::{self, Renderer};
 
 use nalgebra::Vector2;
-use palette::{self, PaletteColor};
-use slp::SlpFile;
+use palettes::{DEFAULT_PALETTE_FILE_ID, Palette, PaletteCycle};
+use shape_decoder::{DecodedFrame, DecodedShape, ShapeDecoder};
 
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::io;
+use std::fmt;
 use std::rc::Rc;
-use types::Rect;
+use std::str::FromStr;
+use std::sync::Arc;
+use texture_atlas::{AtlasRegion, TextureAtlas};
+use types::{Fixed, Rect};
 
-const SHAPE_PADDING: i32 = 4;
-const PALETTE_FILE_ID: u32 = 50500;
 const CENTER_CUTOFF: i32 = 100000;
+const ATLAS_PAGE_SIZE: u32 = 2048;
+const DECODER_THREADS: usize = 3;
+
+/// Shapes that failed to load are remembered so they aren't retried every frame,
+/// but only this many of them
+const MAX_FAILED_SHAPES: usize = 1024;
+
+/// Default amount of texture memory the shape manager is allowed to use before it
+/// starts evicting the least recently used shapes
+pub const DEFAULT_TEXTURE_BUDGET: usize = 256 * 1024 * 1024;
 
 #[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
 pub struct ShapeKey {
     pub drs_key: DrsKey,
     pub slp_id: SlpFileId,
     pub player_color: PlayerColorId,
+    pub palette_id: PaletteId,
 }
 
 impl ShapeKey {
+    /// Creates a key for a shape that uses the default palette
     pub fn new(drs_key: DrsKey, slp_id: SlpFileId, player_color: PlayerColorId) -> ShapeKey {
         ShapeKey {
             drs_key: drs_key,
             slp_id: slp_id,
             player_color: player_color,
+            palette_id: (DEFAULT_PALETTE_FILE_ID as usize).into(),
         }
     }
+
+    pub fn with_palette(mut self, palette_id: PaletteId) -> ShapeKey {
+        self.palette_id = palette_id;
+        self
+    }
 }
 
 pub struct Shape {
-    texture: Texture,
-    frames: Vec<Rect>,
+    frames: Vec<AtlasRegion>,
     centers: Vec<Vector2<i32>>,
+
+    /// Palette indices for each frame; only kept around if the shape's palette
+    /// is being cycled so that the frames can be converted again with the new colors
+    indexed_frames: Vec<Vec<u8>>,
 }
 
 impl Shape {
-    fn load_from(slp: &SlpFile, palette: &[u32], renderer: &mut Renderer) -> Result<Shape> {
-        let mut total_rect = Rect::new();
-        let mut dst_rects = Vec::new();
-        let mut centers = Vec::new();
-        let mut next_x = 0i32;
-        for shape in &slp.shapes {
-            let dst_rect = Rect::of(next_x,
-                                    0,
-                                    shape.header.width as i32,
-                                    shape.header.height as i32);
-            dst_rects.push(dst_rect);
-            centers.push(Vector2::new(shape.header.center_x, shape.header.center_y));
-
-            total_rect.extend(&dst_rect);
-            next_x += shape.header.width as i32 + SHAPE_PADDING;
-        }
-
-        let mut texture_builder =
-            try!(TextureBuilder::new(total_rect.w as u32, total_rect.h as u32, &palette));
-        for (index, shape) in slp.shapes.iter().enumerate() {
-            texture_builder = texture_builder.blit_shape(&shape.pixels,
-                                                         Rect::of(0,
-                                                                  0,
-                                                                  shape.header.width as i32,
-                                                                  shape.header.height as i32),
-                                                         dst_rects[index]);
-        }
-
-        Ok(Shape {
-            texture: try!(texture_builder.build(renderer)),
-            frames: dst_rects,
-            centers: centers,
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+/// What to do when a shape's SLP can't be found in its DRS archive
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MissingShapePolicy {
+    /// Draw the "nope" placeholder graphic in its place
+    Placeholder,
+    /// Don't draw anything
+    Skip,
+    /// Fail rendering with an error
+    Error,
+}
+
+impl FromStr for MissingShapePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> ::std::result::Result<MissingShapePolicy, String> {
+        use self::MissingShapePolicy::*;
+        match s {
+            "placeholder" => Ok(Placeholder),
+            "skip" => Ok(Skip),
+            "error" => Ok(Error),
+            _ => Err(format!("unknown missing shape policy \"{}\"", s)),
+        }
+    }
+}
+
+/// Every shape that was requested but couldn't be found, along with the reason why
+#[derive(Default)]
+pub struct MissingShapeReport {
+    missing: Vec<(ShapeKey, String)>,
+}
+
+impl MissingShapeReport {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.missing.len()
+    }
+
+    fn record(&mut self, shape_key: &ShapeKey, reason: String) {
+        if !self.missing.iter().any(|&(ref key, _)| key == shape_key) {
+            self.missing.push((*shape_key, reason));
+        }
+    }
+}
+
+impl fmt::Display for MissingShapeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut missing: Vec<&(ShapeKey, String)> = self.missing.iter().collect();
+        missing.sort_by_key(|&&(ref key, _)| (key.drs_key.path(), *key.slp_id, *key.player_color));
+
+        try!(writeln!(f, "{} missing shapes:", missing.len()));
+        for &&(ref key, ref reason) in &missing {
+            try!(writeln!(f,
+                          "  {}.slp in \"{}\" (player color {}): {}",
+                          *key.slp_id,
+                          key.drs_key.path(),
+                          *key.player_color,
+                          reason));
+        }
+        Ok(())
+    }
+}
+
+enum ShapeCache {
+    /// Waiting for the decoder threads to finish with it
+    Pending,
+    Cached(Shape),
+    Failed,
+}
+
+struct CacheEntry {
+    cache: ShapeCache,
+    last_used: u64,
+}
+
+impl CacheEntry {
+    fn is_pending(&self) -> bool {
+        match self.cache {
+            ShapeCache::Pending => true,
+            _ => false,
+        }
+    }
+
+    fn is_failed(&self) -> bool {
+        match self.cache {
+            ShapeCache::Failed => true,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ShapeManagerStats {
+    pub shapes: usize,
+    pub pending: usize,
+    pub textures: usize,
+    pub texture_bytes: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl ShapeManagerStats {
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 { 0.0 } else { self.hits as f64 / lookups as f64 }
+    }
+}
+
+impl fmt::Display for ShapeManagerStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "{} shapes ({} pending) in {} textures ({:.1} MB), hit rate {:.1}% ({} hits, {} misses), \
+                {} evictions",
+               self.shapes,
+               self.pending,
+               self.textures,
+               self.texture_bytes as f64 / (1024.0 * 1024.0),
+               self.hit_rate() * 100.0,
+               self.hits,
+               self.misses,
+               self.evictions)
+    }
+}
+
+pub struct ShapeManager {
+    drs_manager: DrsManagerRef,
+    shapes: HashMap<ShapeKey, CacheEntry>,
+    decoder: ShapeDecoder,
+    palettes: HashMap<PaletteId, Palette>,
+    cycled_palettes: Vec<PaletteId>,
+    atlas: TextureAtlas,
+    texture_budget: usize,
+    missing_shape_policy: MissingShapePolicy,
+    missing_shapes: MissingShapeReport,
+    wait_for_decoding: bool,
+    tick: u64,
+    stats: ShapeManagerStats,
+}
+
+pub type ShapeManagerRef = Rc<RefCell<ShapeManager>>;
+
+impl ShapeManager {
+    pub fn new(drs_manager: DrsManagerRef) -> Result<ShapeManagerRef> {
+        let default_palette_id: PaletteId = (DEFAULT_PALETTE_FILE_ID as usize).into();
+        let default_palette = try!(Palette::load(&*drs_manager.borrow(), default_palette_id));
+        let mut palettes = HashMap::new();
+        palettes.insert(default_palette_id, default_palette);
+
+        Ok(Rc::new(RefCell::new(ShapeManager {
+            drs_manager: drs_manager,
+            shapes: HashMap::new(),
+            decoder: ShapeDecoder::new(DECODER_THREADS),
+            palettes: palettes,
+            cycled_palettes: Vec::new(),
+            atlas: TextureAtlas::new(ATLAS_PAGE_SIZE),
+            texture_budget: DEFAULT_TEXTURE_BUDGET,
+            missing_shape_policy: MissingShapePolicy::Placeholder,
+            missing_shapes: Default::default(),
+            wait_for_decoding: false,
+            tick: 0,
+            stats: Default::default(),
+        })))
+    }
+
+    /// Sets the number of bytes of texture memory that can be used before shapes
+    /// start getting evicted. Takes effect the next time a shape is loaded.
+    pub fn set_texture_budget(&mut self, bytes: usize) {
+        self.texture_budget = bytes;
+    }
+
+    /// Makes shapes render the first frame they're drawn rather than once the decoder threads
+    /// get to them, at the cost of stalling that frame. Headless runs and frame dumps use this
+    /// so that their output doesn't depend on how fast the decoder threads are.
+    pub fn set_wait_for_decoding(&mut self, wait_for_decoding: bool) {
+        self.wait_for_decoding = wait_for_decoding;
+    }
+
+    pub fn set_missing_shape_policy(&mut self, policy: MissingShapePolicy) {
+        self.missing_shape_policy = policy;
+    }
+
+    /// Shapes that have been requested so far that couldn't be found
+    pub fn missing_shapes<'a>(&'a self) -> &'a MissingShapeReport {
+        &self.missing_shapes
+    }
+
+    pub fn stats(&self) -> ShapeManagerStats {
+        let mut stats = self.stats;
+        for entry in self.shapes.values() {
+            match entry.cache {
+                ShapeCache::Pending => stats.pending += 1,
+                ShapeCache::Cached(_) => stats.shapes += 1,
+                ShapeCache::Failed => {}
+            }
+        }
+        stats.textures = self.atlas.page_count();
+        stats.texture_bytes = self.atlas.size_bytes();
+        stats
+    }
+
+    /// Starts decoding the given shapes in the background so that they're
+    /// ready by the time they're first drawn
+    pub fn prefetch(&mut self, shape_keys: &[ShapeKey]) -> Result<()> {
+        for shape_key in shape_keys {
+            if !self.shapes.contains_key(shape_key) {
+                try!(self.request(shape_key));
+            }
+        }
+        Ok(())
+    }
+
+    /// The palette's colors, for anything drawn from palette indices that isn't a shape
+    pub fn palette_colors(&mut self, palette_id: PaletteId) -> Result<Arc<Vec<u32>>> {
+        Ok(try!(self.load_palette(palette_id)).colors())
+    }
+
+    /// Starts rotating a range of colors in the given palette. Shapes using the palette
+    /// that are already loaded get reloaded so that they pick up the animation.
+    pub fn add_palette_cycle(&mut self, palette_id: PaletteId, cycle: PaletteCycle) -> Result<()> {
+        try!(self.load_palette(palette_id)).add_cycle(cycle);
+
+        let affected: Vec<ShapeKey> = self.shapes
+            .keys()
+            .filter(|key| key.palette_id == palette_id)
+            .cloned()
+            .collect();
+        for shape_key in affected {
+            if let Some(entry) = self.shapes.remove(&shape_key) {
+                if let ShapeCache::Cached(shape) = entry.cache {
+                    self.release_shape(&shape);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn clear_palette_cycles(&mut self, palette_id: PaletteId) {
+        if let Some(palette) = self.palettes.get_mut(&palette_id) {
+            palette.clear_cycles();
+            self.cycled_palettes.push(palette_id);
+        }
+    }
+
+    /// Advances the palette cycles; the affected shapes get updated in `update_textures`
+    pub fn update_palette_cycles(&mut self, time_step: Fixed) {
+        for (palette_id, palette) in &mut self.palettes {
+            if palette.update(time_step) {
+                self.cycled_palettes.push(*palette_id);
+            }
+        }
+    }
+
+    /// Uploads the shapes that the decoder threads have finished into the texture atlas,
+    /// and converts shapes again whose palettes have cycled. Should be called once a frame
+    /// before rendering.
+    pub fn update_textures(&mut self, renderer: &mut Renderer) {
+        self.upload_decoded(renderer);
+        self.upload_cycled();
+    }
+
+    /// Blocks until the decoder threads have finished every shape that's waiting on them,
+    /// and uploads those shapes into the texture atlas
+    pub fn wait_for_pending(&mut self, renderer: &mut Renderer) {
+        while self.shapes.values().any(|entry| entry.is_pending()) {
+            match self.decoder.recv() {
+                Some(decoded) => self.upload(decoded, renderer),
+                None => break,
+            }
+        }
+    }
+
+    fn upload_decoded(&mut self, renderer: &mut Renderer) {
+        while let Some(decoded) = self.decoder.try_recv() {
+            self.upload(decoded, renderer);
+        }
+    }
+
+    fn upload(&mut self, decoded: DecodedShape, renderer: &mut Renderer) {
+        // The shape may have been evicted or failed while it was being decoded
+        if !self.shapes.get(&decoded.shape_key).map_or(false, CacheEntry::is_pending) {
+            return;
+        }
+
+        let shape_key = decoded.shape_key;
+        let cache = match decoded.frames
+            .map_err(|err| err.into())
+            .and_then(|frames| self.load_shape(&shape_key, frames, renderer)) {
+            Ok(shape) => ShapeCache::Cached(shape),
+            Err(err) => {
+                println!("Failed to load shape {:?}: {}", decoded.shape_key, err);
+                ShapeCache::Failed
+            }
+        };
+        if let Some(entry) = self.shapes.get_mut(&decoded.shape_key) {
+            entry.cache = cache;
+        }
+        if self.shapes.get(&shape_key).map_or(false, CacheEntry::is_failed) {
+            self.forget_failed_shapes(&shape_key);
+        }
+    }
+
+    fn upload_cycled(&mut self) {
+        let cycled_palettes: Vec<PaletteId> = self.cycled_palettes.drain(..).collect();
+        for palette_id in cycled_palettes {
+            let colors = self.palettes[&palette_id].colors();
+            for (shape_key, entry) in &self.shapes {
+                if shape_key.palette_id != palette_id {
+                    continue;
+                }
+                if let ShapeCache::Cached(ref shape) = entry.cache {
+                    for (region, indexed_pixels) in shape.frames.iter().zip(shape.indexed_frames.iter()) {
+                        let result = match media::to_rgba(&colors,
+                                                          indexed_pixels,
+                                                          region.rect.w as usize,
+                                                          region.rect.h as usize) {
+                            Ok(pixels) => self.atlas.upload(region, &pixels),
+                            Err(err) => Err(err.into()),
+                        };
+                        if let Err(err) = result {
+                            println!("Failed to update cycled shape {:?}: {}", shape_key, err);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the shape if it has been loaded. Shapes that haven't been seen
+    /// before get queued for decoding and show up in a later frame.
+    pub fn get<'a>(&'a mut self, shape_key: &ShapeKey) -> Result<Option<&'a Shape>> {
+        use self::ShapeCache::*;
+
+        self.tick += 1;
+        let tick = self.tick;
+
+        if !self.shapes.contains_key(shape_key) {
+            try!(self.request(shape_key));
+        }
+
+        let entry = self.shapes.get_mut(&shape_key).unwrap();
+        entry.last_used = tick;
+        Ok(match entry.cache {
+            Cached(ref shape) => {
+                self.stats.hits += 1;
+                Some(shape)
+            }
+            Pending => {
+                self.stats.misses += 1;
+                None
+            }
+            Failed => None,
         })
     }
 
-    pub fn render_frame(&self,
+    pub fn render_frame(&mut self,
                         renderer: &mut Renderer,
+                        shape_key: &ShapeKey,
                         frame: usize,
                         position: &Vector2<i32>,
                         flip_horizontal: bool,
-                        flip_vertical: bool) {
-        let src_rect = self.frames[frame];
-        let center = &self.centers[frame];
+                        flip_vertical: bool)
+                        -> Result<()> {
+        if self.wait_for_decoding {
+            if !self.shapes.contains_key(shape_key) {
+                try!(self.request(shape_key));
+            }
+            if self.shapes.get(shape_key).map_or(false, CacheEntry::is_pending) {
+                self.wait_for_pending(renderer);
+            }
+        }
+
+        let (region, center) = match try!(self.get(shape_key)) {
+            Some(shape) if frame < shape.frames.len() => (shape.frames[frame], shape.centers[frame]),
+            _ => return Ok(()),
+        };
 
         // Fixes #53: If the frame's center is an extreme value, it shouldn't be drawn
         if center.x.abs() > CENTER_CUTOFF || center.y.abs() > CENTER_CUTOFF {
-            return;
+            return Ok(());
         }
 
+        let src_rect = region.rect;
         let mut dst_rect = Rect::of(0, 0, src_rect.w, src_rect.h);
         dst_rect.translate(position.x, position.y);
 
@@ -126,89 +489,177 @@ impl Shape {
         }
         dst_rect.translate(-offset_x, -offset_y);
 
-        renderer.render_texture(&self.texture,
+        renderer.render_texture(self.atlas.texture(&region),
                                 Some(src_rect.into()),
                                 dst_rect.into(),
                                 flip_horizontal,
                                 flip_vertical);
+        Ok(())
     }
-}
 
-enum ShapeCache {
-    Cached(Shape),
-    Failed,
-}
+    /// Hands the shape's SLP contents off to the decoder threads. Only fails if the shape
+    /// is missing and the missing shape policy says to treat that as an error.
+    fn request(&mut self, shape_key: &ShapeKey) -> Result<()> {
+        let palette = match self.load_palette(shape_key.palette_id).map(|palette| palette.colors()) {
+            Ok(palette) => palette,
+            Err(err) => {
+                println!("Failed to load shape {:?}: {}", shape_key, err);
+                self.insert_entry(shape_key, ShapeCache::Failed);
+                return Ok(());
+            }
+        };
 
-pub struct ShapeManager {
-    drs_manager: DrsManagerRef,
-    shapes: HashMap<ShapeKey, ShapeCache>,
-    palette: Vec<u32>,
-}
+        let cache = match self.read_slp_contents(shape_key) {
+            Ok(Some(slp_contents)) => {
+                self.decoder.submit(*shape_key, Some(slp_contents), palette);
+                ShapeCache::Pending
+            }
+            Ok(None) => {
+                let err: Error = ErrorKind::SlpNotFound(shape_key.drs_key, *shape_key.slp_id).into();
+                self.missing_shapes.record(shape_key, format!("{}", err));
+                match self.missing_shape_policy {
+                    MissingShapePolicy::Placeholder => {
+                        self.decoder.submit(*shape_key, None, palette);
+                        ShapeCache::Pending
+                    }
+                    MissingShapePolicy::Skip => ShapeCache::Failed,
+                    MissingShapePolicy::Error => return Err(err),
+                }
+            }
+            Err(err) => {
+                // Only optional archives can be missing by this point, so this isn't fatal
+                self.missing_shapes.record(shape_key, format!("{}", err));
+                ShapeCache::Failed
+            }
+        };
+        self.insert_entry(shape_key, cache);
+        Ok(())
+    }
 
-pub type ShapeManagerRef = Rc<RefCell<ShapeManager>>;
+    fn insert_entry(&mut self, shape_key: &ShapeKey, cache: ShapeCache) {
+        self.shapes.insert(*shape_key,
+                           CacheEntry {
+                               cache: cache,
+                               last_used: self.tick,
+                           });
+        if self.shapes[shape_key].is_failed() {
+            self.forget_failed_shapes(shape_key);
+        }
+    }
 
-impl ShapeManager {
-    pub fn new(drs_manager: DrsManagerRef) -> Result<ShapeManagerRef> {
-        let palette = {
-                let borrowed_drs = drs_manager.borrow();
-                let interfac = borrowed_drs.get(DrsKey::Interfac);
-                let bin_table = try!(interfac.find_table(DrsFileType::Binary)
-                    .ok_or(ErrorKind::InterfacBinaryTableMissing));
-                let palette_contents = &try!(bin_table.find_file_contents(PALETTE_FILE_ID)
-                    .ok_or(ErrorKind::InterfacMissingPalette));
-                try!(palette::read_from(&mut io::Cursor::new(palette_contents)))
-            }
+    /// Forgets the least recently used failed shapes (other than the one that just failed)
+    /// once there are too many of them. They get retried if they're drawn again.
+    fn forget_failed_shapes(&mut self, just_failed: &ShapeKey) {
+        let mut failed: Vec<(u64, ShapeKey)> = self.shapes
             .iter()
-            .map(|c: &PaletteColor| -> u32 { (*c).into() })
+            .filter(|&(key, entry)| key != just_failed && entry.is_failed())
+            .map(|(key, entry)| (entry.last_used, *key))
             .collect();
+        if failed.len() < MAX_FAILED_SHAPES {
+            return;
+        }
 
-        Ok(Rc::new(RefCell::new(ShapeManager {
-            drs_manager: drs_manager,
-            shapes: HashMap::new(),
-            palette: palette,
-        })))
+        failed.sort_by_key(|&(last_used, _)| last_used);
+        for &(_, ref key) in &failed[..failed.len() + 1 - MAX_FAILED_SHAPES] {
+            self.shapes.remove(key);
+        }
     }
 
-    pub fn get<'a>(&'a mut self, shape_key: &ShapeKey, renderer: &mut Renderer) -> Option<&'a Shape> {
-        use self::ShapeCache::*;
+    fn load_palette<'a>(&'a mut self, palette_id: PaletteId) -> Result<&'a mut Palette> {
+        if !self.palettes.contains_key(&palette_id) {
+            let palette = try!(Palette::load(&*self.drs_manager.borrow(), palette_id));
+            self.palettes.insert(palette_id, palette);
+        }
+        Ok(self.palettes.get_mut(&palette_id).unwrap())
+    }
 
-        let cached = self.shapes.get(&shape_key).is_some();
-        if !cached {
-            match self.load_shape(shape_key, renderer) {
-                Ok(shape) => {
-                    self.shapes.insert(*shape_key, Cached(shape));
-                }
+    /// Copies the SLP out of its DRS archive. Returns `None` if the archive doesn't have the SLP.
+    fn read_slp_contents(&self, shape_key: &ShapeKey) -> Result<Option<Vec<u8>>> {
+        let borrowed_drs = self.drs_manager.borrow();
+        let drs_file = try!(borrowed_drs.get(shape_key.drs_key));
+
+        let slp_table = try!(drs_file.find_table(DrsFileType::Slp)
+            .ok_or(ErrorKind::NoSlpTableInDrs(shape_key.drs_key)));
+        Ok(slp_table.find_file_contents(*shape_key.slp_id).map(|contents| contents.to_vec()))
+    }
+
+    fn load_shape(&mut self,
+                  shape_key: &ShapeKey,
+                  frames: Vec<DecodedFrame>,
+                  renderer: &mut Renderer)
+                  -> Result<Shape> {
+        let keep_indexed = match self.palettes.get(&shape_key.palette_id) {
+            Some(palette) => frames.iter().any(|frame| palette.is_cycled(&frame.indexed_pixels)),
+            None => false,
+        };
+
+        let mut shape = Shape {
+            frames: Vec::new(),
+            centers: Vec::new(),
+            indexed_frames: Vec::new(),
+        };
+        for frame in frames {
+            let region = match self.allocate_region(frame.width, frame.height, renderer) {
+                Ok(region) => region,
                 Err(err) => {
-                    self.shapes.insert(*shape_key, Failed);
-                    println!("Failed to load shape {:?}: {}", shape_key, err);
-                    return None;
+                    // Don't leak the atlas space of frames that were already uploaded
+                    self.release_shape(&shape);
+                    return Err(err);
                 }
             };
+            shape.frames.push(region);
+            shape.centers.push(frame.center);
+            if let Err(err) = self.atlas.upload(&region, &frame.pixels) {
+                self.release_shape(&shape);
+                return Err(err);
+            }
+            if keep_indexed {
+                shape.indexed_frames.push(frame.indexed_pixels);
+            }
         }
+        Ok(shape)
+    }
 
-        match *self.shapes.get(&shape_key).unwrap() {
-            Cached(ref shape) => return Some(shape),
-            Failed => return None,
+    /// Finds room in the atlas for a frame, evicting the least recently used shapes
+    /// if a new atlas page would put us over the texture budget
+    fn allocate_region(&mut self, width: u32, height: u32, renderer: &mut Renderer) -> Result<AtlasRegion> {
+        loop {
+            if let Some(region) = self.atlas.allocate(width, height) {
+                return Ok(region);
+            }
+
+            let required_bytes = self.atlas.size_bytes() + self.atlas.new_page_bytes(width, height);
+            if required_bytes <= self.texture_budget || !self.evict_least_recently_used() {
+                return self.atlas.allocate_in_new_page(width, height, renderer);
+            }
         }
     }
 
-    fn load_shape(&self, shape_key: &ShapeKey, renderer: &mut Renderer) -> Result<Shape> {
-        let borrowed_drs = self.drs_manager.borrow();
-        let drs_file = borrowed_drs.get(shape_key.drs_key);
+    fn evict_least_recently_used(&mut self) -> bool {
+        let lru_key = self.shapes
+            .iter()
+            .filter(|&(_, entry)| match entry.cache {
+                ShapeCache::Cached(_) => true,
+                _ => false,
+            })
+            .min_by_key(|&(_, entry)| entry.last_used)
+            .map(|(key, _)| *key);
 
-        let slp_table = try!(drs_file.find_table(DrsFileType::Slp)
-            .ok_or(ErrorKind::NoSlpTableInDrs(shape_key.drs_key)));
-        
-        let slp = match slp_table.find_file_contents(*shape_key.slp_id) {
-            Some(slp_contents) => {
-                try!(SlpFile::read_from(&mut io::Cursor::new(slp_contents), *shape_key.player_color))
-            },
-            None => {
-                // Load the "missing" SLP file if we can't find the requested SLP in the DRS archive
-                try!(SlpFile::read_from_file("data/nope-64x64.slp", *shape_key.player_color))
+        match lru_key {
+            Some(key) => {
+                if let Some(CacheEntry { cache: ShapeCache::Cached(shape), .. }) = self.shapes.remove(&key) {
+                    self.release_shape(&shape);
+                }
+                self.stats.evictions += 1;
+                true
             }
-        };
+            None => false,
+        }
+    }
 
-        Shape::load_from(&slp, &self.palette, renderer)
+    fn release_shape(&mut self, shape: &Shape) {
+        for region in &shape.frames {
+            self.atlas.release(region);
+        }
     }
 }
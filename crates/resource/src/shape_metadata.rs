@@ -23,12 +23,18 @@
 use drs::DrsFileType;
 use drs_manager::{DrsKey, DrsManager};
 use identifier::SlpFileId;
-use slp::SlpHeader;
+use nalgebra::Vector2;
 
 use std::collections::HashMap;
 use std::io;
 use std::sync::Arc;
 
+const SLP_HEADER_SIZE: usize = 32;
+const SLP_FRAME_INFO_SIZE: usize = 32;
+
+/// Outline value that indicates that a row has no visible pixels
+const TRANSPARENT_ROW: u16 = 0x8000;
+
 #[derive(Debug, Hash, Eq, PartialEq)]
 pub struct ShapeMetadataKey {
     pub drs_key: DrsKey,
@@ -44,8 +50,110 @@ impl ShapeMetadataKey {
     }
 }
 
+/// Visible span of a single row in a frame; the outline data stores how many
+/// transparent pixels are on the left and right side of each row
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct RowOutline {
+    left: u16,
+    right: u16,
+}
+
+#[derive(Debug)]
+pub struct FrameMetadata {
+    pub width: u32,
+    pub height: u32,
+
+    /// Offset of the frame's hotspot from its top-left corner
+    pub center: Vector2<i32>,
+
+    outline: Vec<Option<RowOutline>>,
+}
+
+impl FrameMetadata {
+    /// Returns true if the given point (relative to the top-left of the frame) lands on
+    /// a visible pixel according to the frame's outline
+    pub fn contains(&self, point: &Vector2<i32>) -> bool {
+        if point.x < 0 || point.y < 0 || point.x >= self.width as i32 || point.y >= self.height as i32 {
+            return false;
+        }
+        match self.outline.get(point.y as usize) {
+            Some(&Some(row)) => point.x >= row.left as i32 && point.x < self.width as i32 - row.right as i32,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the given screen point lands on a visible pixel of this frame
+    /// when it's drawn at the given position, the same way `ShapeManager::render_frame` draws it
+    pub fn hit_test(&self,
+                    position: &Vector2<i32>,
+                    flip_horizontal: bool,
+                    flip_vertical: bool,
+                    point: &Vector2<i32>)
+                    -> bool {
+        let (width, height) = (self.width as i32, self.height as i32);
+        let offset_x = if flip_horizontal { width - self.center.x } else { self.center.x };
+        let offset_y = if flip_vertical { height - self.center.y } else { self.center.y };
+
+        let mut local = Vector2::new(point.x - (position.x - offset_x), point.y - (position.y - offset_y));
+        if flip_horizontal {
+            local.x = width - 1 - local.x;
+        }
+        if flip_vertical {
+            local.y = height - 1 - local.y;
+        }
+        self.contains(&local)
+    }
+}
+
 pub struct ShapeMetadata {
     pub shape_count: u32,
+    pub frames: Vec<FrameMetadata>,
+}
+
+impl ShapeMetadata {
+    /// Reads the frame sizes, centers, and outlines out of an SLP file without decoding its pixels
+    pub fn read_from(slp_contents: &[u8]) -> io::Result<ShapeMetadata> {
+        let shape_count = try!(read_u32(slp_contents, 4));
+        let mut frames = Vec::with_capacity(shape_count as usize);
+        for index in 0..shape_count as usize {
+            let info = SLP_HEADER_SIZE + index * SLP_FRAME_INFO_SIZE;
+            let outline_offset = try!(read_u32(slp_contents, info + 4)) as usize;
+            let width = try!(read_u32(slp_contents, info + 16));
+            let height = try!(read_u32(slp_contents, info + 20));
+            let center_x = try!(read_u32(slp_contents, info + 24)) as i32;
+            let center_y = try!(read_u32(slp_contents, info + 28)) as i32;
+
+            let mut outline = Vec::with_capacity(height as usize);
+            for row in 0..height as usize {
+                let left = try!(read_u16(slp_contents, outline_offset + row * 4));
+                let right = try!(read_u16(slp_contents, outline_offset + row * 4 + 2));
+                outline.push(if left == TRANSPARENT_ROW || right == TRANSPARENT_ROW {
+                    None
+                } else {
+                    Some(RowOutline {
+                        left: left,
+                        right: right,
+                    })
+                });
+            }
+
+            frames.push(FrameMetadata {
+                width: width,
+                height: height,
+                center: Vector2::new(center_x, center_y),
+                outline: outline,
+            });
+        }
+
+        Ok(ShapeMetadata {
+            shape_count: shape_count,
+            frames: frames,
+        })
+    }
+
+    pub fn frame<'a>(&'a self, frame: usize) -> Option<&'a FrameMetadata> {
+        self.frames.get(frame)
+    }
 }
 
 pub struct ShapeMetadataStore {
@@ -67,7 +175,10 @@ impl ShapeMetadataStore {
     fn load_drs(drs_manager: &DrsManager,
                 drs_key: DrsKey,
                 metadata: &mut HashMap<ShapeMetadataKey, ShapeMetadata>) {
-        let drs = drs_manager.get(drs_key);
+        let drs = match drs_manager.get(drs_key) {
+            Ok(drs) => drs,
+            Err(_) => return,
+        };
         if let Some(table) = drs.find_table(DrsFileType::Slp) {
             let keys: Vec<ShapeMetadataKey> = table.entries
                 .iter()
@@ -75,10 +186,13 @@ impl ShapeMetadataStore {
                 .collect();
 
             for key in keys {
-                // TODO: Should probably return a result instead of unwrapping
                 let contents = table.find_file_contents(*key.slp_id).unwrap();
-                let slp_header = SlpHeader::read_from(&mut io::Cursor::new(contents)).unwrap();
-                metadata.insert(key, ShapeMetadata { shape_count: slp_header.shape_count });
+                match ShapeMetadata::read_from(contents) {
+                    Ok(shape_metadata) => {
+                        metadata.insert(key, shape_metadata);
+                    }
+                    Err(err) => println!("Skipping metadata for SLP {:?}: {}", key, err),
+                }
             }
         }
     }
@@ -86,4 +200,116 @@ impl ShapeMetadataStore {
     pub fn get<'a>(&'a self, key: &ShapeMetadataKey) -> Option<&'a ShapeMetadata> {
         self.metadata.get(key)
     }
+
+    /// Convenience that returns a single frame's metadata
+    pub fn frame<'a>(&'a self, key: &ShapeMetadataKey, frame: usize) -> Option<&'a FrameMetadata> {
+        self.get(key).and_then(|shape_metadata| shape_metadata.frame(frame))
+    }
+}
+
+fn read_u16(contents: &[u8], offset: usize) -> io::Result<u16> {
+    let bytes = try!(read_bytes(contents, offset, 2));
+    Ok(bytes[0] as u16 | (bytes[1] as u16) << 8)
+}
+
+fn read_u32(contents: &[u8], offset: usize) -> io::Result<u32> {
+    let bytes = try!(read_bytes(contents, offset, 4));
+    Ok(bytes[0] as u32 | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 | (bytes[3] as u32) << 24)
+}
+
+fn read_bytes<'a>(contents: &'a [u8], offset: usize, len: usize) -> io::Result<&'a [u8]> {
+    if offset + len > contents.len() {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "SLP is truncated"));
+    }
+    Ok(&contents[offset..offset + len])
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector2;
+    use super::ShapeMetadata;
+
+    fn push_u16(bytes: &mut Vec<u8>, value: u16) {
+        bytes.push(value as u8);
+        bytes.push((value >> 8) as u8);
+    }
+
+    fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+        push_u16(bytes, value as u16);
+        push_u16(bytes, (value >> 16) as u16);
+    }
+
+    /// Builds an SLP with a single 4x3 frame whose hotspot is at (1, 2). The first row
+    /// is transparent, the second row covers columns 1-2, and the third covers columns 0-3.
+    fn test_slp() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"2.0N");
+        push_u32(&mut bytes, 1);
+        bytes.extend_from_slice(&[0u8; 24]);
+
+        let outline_offset = 64;
+        push_u32(&mut bytes, 0); // command table offset (unused)
+        push_u32(&mut bytes, outline_offset);
+        push_u32(&mut bytes, 0);
+        push_u32(&mut bytes, 0);
+        push_u32(&mut bytes, 4);
+        push_u32(&mut bytes, 3);
+        push_u32(&mut bytes, 1);
+        push_u32(&mut bytes, 2);
+
+        for &(left, right) in &[(0x8000, 0x8000), (1, 1), (0, 0)] {
+            push_u16(&mut bytes, left);
+            push_u16(&mut bytes, right);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_read_from() {
+        let metadata = ShapeMetadata::read_from(&test_slp()).unwrap();
+        assert_eq!(1, metadata.shape_count);
+        let frame = metadata.frame(0).unwrap();
+        assert_eq!((4, 3), (frame.width, frame.height));
+        assert_eq!(Vector2::new(1, 2), frame.center);
+        assert!(metadata.frame(1).is_none());
+
+        let mut truncated = test_slp();
+        truncated.pop();
+        assert!(ShapeMetadata::read_from(&truncated).is_err());
+    }
+
+    #[test]
+    fn test_contains() {
+        let metadata = ShapeMetadata::read_from(&test_slp()).unwrap();
+        let frame = metadata.frame(0).unwrap();
+        assert!(!frame.contains(&Vector2::new(1, 0)));
+        assert!(!frame.contains(&Vector2::new(0, 1)));
+        assert!(frame.contains(&Vector2::new(1, 1)));
+        assert!(frame.contains(&Vector2::new(2, 1)));
+        assert!(!frame.contains(&Vector2::new(3, 1)));
+        assert!(frame.contains(&Vector2::new(0, 2)));
+        assert!(frame.contains(&Vector2::new(3, 2)));
+        assert!(!frame.contains(&Vector2::new(4, 2)));
+        assert!(!frame.contains(&Vector2::new(-1, 2)));
+        assert!(!frame.contains(&Vector2::new(0, 3)));
+    }
+
+    #[test]
+    fn test_hit_test() {
+        let metadata = ShapeMetadata::read_from(&test_slp()).unwrap();
+        let frame = metadata.frame(0).unwrap();
+        let position = Vector2::new(100, 100);
+
+        // Unflipped, the frame's top-left is at (99, 98)
+        assert!(frame.hit_test(&position, false, false, &Vector2::new(100, 99)));
+        assert!(!frame.hit_test(&position, false, false, &Vector2::new(99, 99)));
+        assert!(frame.hit_test(&position, false, false, &Vector2::new(99, 100)));
+        assert!(!frame.hit_test(&position, false, false, &Vector2::new(100, 98)));
+
+        // Flipped horizontally, the top-left is at (97, 98) and the columns are mirrored
+        assert!(frame.hit_test(&position, true, false, &Vector2::new(98, 99)));
+        assert!(!frame.hit_test(&position, true, false, &Vector2::new(97, 99)));
+        assert!(frame.hit_test(&position, true, false, &Vector2::new(100, 100)));
+        assert!(!frame.hit_test(&position, true, false, &Vector2::new(101, 100)));
+    }
 }
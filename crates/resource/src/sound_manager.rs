@@ -0,0 +1,75 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use drs::DrsFileType;
+use drs_manager::{DrsKey, DrsManagerRef};
+use error::{ErrorKind, Result};
+use identifier::WavFileId;
+use media::Sound;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Decodes sounds out of sounds.drs on first use and keeps them around for the mixer
+pub struct SoundManager {
+    drs_manager: DrsManagerRef,
+    sounds: HashMap<WavFileId, Arc<Sound>>,
+}
+
+pub type SoundManagerRef = Rc<RefCell<SoundManager>>;
+
+impl SoundManager {
+    pub fn new(drs_manager: DrsManagerRef) -> SoundManagerRef {
+        Rc::new(RefCell::new(SoundManager {
+            drs_manager: drs_manager,
+            sounds: HashMap::new(),
+        }))
+    }
+
+    /// Whether there's a sounds.drs to play sounds from (it's optional)
+    pub fn is_available(&self) -> bool {
+        self.drs_manager.borrow().is_loaded(DrsKey::Sounds)
+    }
+
+    pub fn get(&mut self, wav_id: WavFileId) -> Result<Arc<Sound>> {
+        if let Some(sound) = self.sounds.get(&wav_id) {
+            return Ok(sound.clone());
+        }
+
+        let sound = {
+            let borrowed_drs = self.drs_manager.borrow();
+            let drs_file = try!(borrowed_drs.get(DrsKey::Sounds));
+            let wav_table = try!(drs_file.find_table(DrsFileType::Wav)
+                .ok_or(ErrorKind::NoWavTableInDrs(DrsKey::Sounds)));
+            let contents = try!(wav_table.find_file_contents(*wav_id)
+                .ok_or(ErrorKind::WavNotFound(*wav_id)));
+            Arc::new(try!(Sound::read_wav(contents)))
+        };
+        self.sounds.insert(wav_id, sound.clone());
+        Ok(sound)
+    }
+
+    pub fn loaded_count(&self) -> usize {
+        self.sounds.len()
+    }
+}
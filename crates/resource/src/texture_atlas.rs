@@ -0,0 +1,302 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use error::Result;
+use media::{Renderer, Texture};
+use std::cmp;
+use types::Rect;
+
+/// Space left around every region so that neighbors don't bleed into each other
+const REGION_PADDING: i32 = 2;
+
+/// Rectangle bin packer that supports releasing previously packed rectangles.
+/// Uses the guillotine algorithm with best short side fit.
+pub struct AtlasPacker {
+    width: i32,
+    height: i32,
+    free_rects: Vec<Rect>,
+}
+
+impl AtlasPacker {
+    pub fn new(width: i32, height: i32) -> AtlasPacker {
+        AtlasPacker {
+            width: width,
+            height: height,
+            free_rects: vec![Rect::of(0, 0, width, height)],
+        }
+    }
+
+    /// Finds space for a rectangle of the given size and returns where it was placed
+    pub fn insert(&mut self, width: i32, height: i32) -> Option<Rect> {
+        let mut best: Option<(usize, i32)> = None;
+        for (index, free) in self.free_rects.iter().enumerate() {
+            if free.w >= width && free.h >= height {
+                let short_side = cmp::min(free.w - width, free.h - height);
+                if best.map_or(true, |(_, best_short_side)| short_side < best_short_side) {
+                    best = Some((index, short_side));
+                }
+            }
+        }
+
+        best.map(|(index, _)| {
+            let free = self.free_rects.swap_remove(index);
+            let (leftover_w, leftover_h) = (free.w - width, free.h - height);
+
+            // Split along the shorter leftover axis so that the larger leftover stays in one piece
+            let (right, bottom) = if leftover_w < leftover_h {
+                (Rect::of(free.x + width, free.y, leftover_w, height),
+                 Rect::of(free.x, free.y + height, free.w, leftover_h))
+            } else {
+                (Rect::of(free.x + width, free.y, leftover_w, free.h),
+                 Rect::of(free.x, free.y + height, width, leftover_h))
+            };
+            for leftover in &[right, bottom] {
+                if leftover.w > 0 && leftover.h > 0 {
+                    self.free_rects.push(*leftover);
+                }
+            }
+
+            Rect::of(free.x, free.y, width, height)
+        })
+    }
+
+    /// Makes the space of a previously inserted rectangle available again
+    pub fn release(&mut self, rect: Rect) {
+        self.free_rects.push(rect);
+        self.merge_free_rects();
+    }
+
+    /// Returns true if nothing is packed
+    pub fn is_empty(&self) -> bool {
+        self.free_rects.len() == 1 && self.free_rects[0] == Rect::of(0, 0, self.width, self.height)
+    }
+
+    /// Joins free rectangles that share a full edge so that released space can be reused
+    /// for larger rectangles later on
+    fn merge_free_rects(&mut self) {
+        let mut merged = true;
+        while merged {
+            merged = false;
+            'search: for i in 0..self.free_rects.len() {
+                for j in 0..self.free_rects.len() {
+                    if i == j {
+                        continue;
+                    }
+                    let (a, b) = (self.free_rects[i], self.free_rects[j]);
+                    let joined = if a.y == b.y && a.h == b.h && a.x + a.w == b.x {
+                        Some(Rect::of(a.x, a.y, a.w + b.w, a.h))
+                    } else if a.x == b.x && a.w == b.w && a.y + a.h == b.y {
+                        Some(Rect::of(a.x, a.y, a.w, a.h + b.h))
+                    } else {
+                        None
+                    };
+                    if let Some(joined) = joined {
+                        self.free_rects[i] = joined;
+                        self.free_rects.swap_remove(j);
+                        merged = true;
+                        break 'search;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Location of an image inside of the texture atlas
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AtlasRegion {
+    pub page: usize,
+    pub rect: Rect,
+}
+
+struct AtlasPage {
+    texture: Texture,
+    packer: AtlasPacker,
+    region_count: usize,
+}
+
+impl AtlasPage {
+    fn size_bytes(&self) -> usize {
+        (self.texture.width * self.texture.height * 4) as usize
+    }
+}
+
+/// Collection of large textures (pages) that many images get packed into
+pub struct TextureAtlas {
+    page_size: u32,
+    pages: Vec<Option<AtlasPage>>,
+}
+
+impl TextureAtlas {
+    pub fn new(page_size: u32) -> TextureAtlas {
+        TextureAtlas {
+            page_size: page_size,
+            pages: Vec::new(),
+        }
+    }
+
+    /// Tries to allocate a region in one of the existing pages
+    pub fn allocate(&mut self, width: u32, height: u32) -> Option<AtlasRegion> {
+        let (padded_w, padded_h) = (width as i32 + REGION_PADDING, height as i32 + REGION_PADDING);
+        for (index, page) in self.pages.iter_mut().enumerate() {
+            if let Some(ref mut page) = *page {
+                if let Some(rect) = page.packer.insert(padded_w, padded_h) {
+                    page.region_count += 1;
+                    return Some(AtlasRegion {
+                        page: index,
+                        rect: Rect::of(rect.x, rect.y, width as i32, height as i32),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// Creates a new page and allocates the region in it. Images that are larger
+    /// than the page size get a page all to themselves.
+    pub fn allocate_in_new_page(&mut self,
+                                width: u32,
+                                height: u32,
+                                renderer: &mut Renderer)
+                                -> Result<AtlasRegion> {
+        let (page_w, page_h) = self.new_page_size(width, height);
+        let page = AtlasPage {
+            texture: try!(renderer.create_texture(page_w, page_h)),
+            packer: AtlasPacker::new(page_w as i32, page_h as i32),
+            region_count: 0,
+        };
+
+        let index = match self.pages.iter().position(|page| page.is_none()) {
+            Some(index) => {
+                self.pages[index] = Some(page);
+                index
+            }
+            None => {
+                self.pages.push(Some(page));
+                self.pages.len() - 1
+            }
+        };
+
+        let page = self.pages[index].as_mut().unwrap();
+        let rect = page.packer
+            .insert(width as i32 + REGION_PADDING, height as i32 + REGION_PADDING)
+            .expect("new atlas page is large enough for the region");
+        page.region_count += 1;
+        Ok(AtlasRegion {
+            page: index,
+            rect: Rect::of(rect.x, rect.y, width as i32, height as i32),
+        })
+    }
+
+    /// Number of bytes a new page would take up if it was created to hold the given size
+    pub fn new_page_bytes(&self, width: u32, height: u32) -> usize {
+        let (page_w, page_h) = self.new_page_size(width, height);
+        (page_w * page_h * 4) as usize
+    }
+
+    /// Frees up the region; the page is destroyed if nothing is left in it
+    pub fn release(&mut self, region: &AtlasRegion) {
+        let empty = match self.pages[region.page] {
+            Some(ref mut page) => {
+                let mut padded = region.rect;
+                padded.w += REGION_PADDING;
+                padded.h += REGION_PADDING;
+                page.packer.release(padded);
+                page.region_count -= 1;
+                page.region_count == 0
+            }
+            None => false,
+        };
+        if empty {
+            self.pages[region.page] = None;
+        }
+    }
+
+    /// Copies RGBA8888 pixels into the region
+    pub fn upload(&mut self, region: &AtlasRegion, rgba_pixels: &[u8]) -> Result<()> {
+        let page = self.pages[region.page].as_mut().expect("region is in a live atlas page");
+        try!(page.texture.update(region.rect, rgba_pixels));
+        Ok(())
+    }
+
+    pub fn texture<'a>(&'a self, region: &AtlasRegion) -> &'a Texture {
+        &self.pages[region.page].as_ref().expect("region is in a live atlas page").texture
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.iter().filter(|page| page.is_some()).count()
+    }
+
+    pub fn size_bytes(&self) -> usize {
+        self.pages.iter().filter_map(|page| page.as_ref()).map(|page| page.size_bytes()).sum()
+    }
+
+    fn new_page_size(&self, width: u32, height: u32) -> (u32, u32) {
+        let padding = REGION_PADDING as u32;
+        (cmp::max(self.page_size, width + padding), cmp::max(self.page_size, height + padding))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AtlasPacker;
+    use types::Rect;
+
+    #[test]
+    fn test_packer_insert() {
+        let mut packer = AtlasPacker::new(64, 64);
+        assert!(packer.is_empty());
+        assert_eq!(Some(Rect::of(0, 0, 32, 16)), packer.insert(32, 16));
+        assert!(!packer.is_empty());
+
+        // Shouldn't overlap with the first rectangle
+        let second = packer.insert(32, 32).unwrap();
+        assert!(second.x >= 32 || second.y >= 16);
+
+        assert_eq!(None, packer.insert(65, 1));
+        assert_eq!(None, packer.insert(64, 64));
+    }
+
+    #[test]
+    fn test_packer_fills_up() {
+        let mut packer = AtlasPacker::new(64, 64);
+        for _ in 0..16 {
+            assert!(packer.insert(16, 16).is_some());
+        }
+        assert_eq!(None, packer.insert(1, 1));
+    }
+
+    #[test]
+    fn test_packer_release() {
+        let mut packer = AtlasPacker::new(64, 64);
+        let rects: Vec<Rect> = (0..4).map(|_| packer.insert(32, 32).unwrap()).collect();
+        assert_eq!(None, packer.insert(32, 32));
+
+        packer.release(rects[2]);
+        assert_eq!(Some(rects[2]), packer.insert(32, 32));
+
+        for rect in rects {
+            packer.release(rect);
+        }
+        assert!(packer.is_empty());
+        assert_eq!(Some(Rect::of(0, 0, 64, 64)), packer.insert(64, 64));
+    }
+}
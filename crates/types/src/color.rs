@@ -21,7 +21,7 @@
 
 use sdl2;
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -87,7 +87,7 @@ impl Fixed {
     }
 
     pub fn sqrt(&self) -> Fixed {
-        if *self == Fixed::one() {
+        if *self == Fixed::one() || *self == Fixed::zero() {
             return *self;
         }
         if *self < Fixed::zero() {
@@ -548,6 +548,11 @@ mod tests {
         assert_eq!(Fixed::from(0.5), Fixed::from(0.25).sqrt());
     }
 
+    #[test]
+    fn test_sqrt_zero() {
+        assert_eq!(Fixed::from(0), Fixed::from(0).sqrt());
+    }
+
     #[test]
     #[should_panic]
     fn test_sqrt_neg() {
@@ -23,7 +23,7 @@ use sdl2;
 
 use std::cmp;
 
-#[derive(Default, Copy, Clone, Debug)]
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq)]
 pub struct Rect {
     pub x: i32,
     pub y: i32,
@@ -56,6 +56,15 @@ impl Rect {
         self.w = cmp::max(self.x + self.w, other.x + other.w) - self.x;
         self.h = cmp::max(self.y + self.h, other.y + other.h) - self.y;
     }
+
+    /// Returns the overlapping area of the two rects, if they overlap at all
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x = cmp::max(self.x, other.x);
+        let y = cmp::max(self.y, other.y);
+        let right = cmp::min(self.x + self.w, other.x + other.w);
+        let bottom = cmp::min(self.y + self.h, other.y + other.h);
+        if right > x && bottom > y { Some(Rect::of(x, y, right - x, bottom - y)) } else { None }
+    }
 }
 
 impl Into<sdl2::rect::Rect> for Rect {
@@ -63,3 +72,17 @@ impl Into<sdl2::rect::Rect> for Rect {
         sdl2::rect::Rect::new(self.x, self.y, self.w as u32, self.h as u32)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Rect;
+
+    #[test]
+    fn test_intersection() {
+        let rect = Rect::of(0, 0, 10, 10);
+        assert_eq!(Some(Rect::of(5, 2, 5, 3)), rect.intersection(&Rect::of(5, 2, 20, 3)));
+        assert_eq!(Some(rect), rect.intersection(&Rect::of(-5, -5, 20, 20)));
+        assert_eq!(None, rect.intersection(&Rect::of(10, 0, 5, 5)));
+        assert_eq!(None, rect.intersection(&Rect::of(0, -5, 5, 5)));
+    }
+}
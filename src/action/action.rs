@@ -19,7 +19,7 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use super::MoveToPositionParams;
+use super::{AttackParams, BuildParams, DropOffParams, GatherParams, MoveToPositionParams};
 
 /// Enum of possible actions a unit can undertake
 #[derive(Clone, Debug)]
@@ -29,4 +29,17 @@ pub enum Action {
 
     /// Instructs a unit to move to a given position on the map
     MoveToPosition(MoveToPositionParams),
+
+    /// Instructs a unit to chase and attack another unit until it dies
+    Attack(AttackParams),
+
+    /// Instructs a unit to gather from a resource site, returning to the
+    /// nearest drop site whenever it can't carry any more
+    Gather(GatherParams),
+
+    /// Instructs a unit to take the resources it's carrying to a drop site
+    DropOff(DropOffParams),
+
+    /// Instructs a unit to work on a building's foundation until it's finished
+    Build(BuildParams),
 }
@@ -20,7 +20,15 @@
 // SOFTWARE.
 
 mod action;
+mod attack;
+mod build;
+mod drop_off;
+mod gather;
 mod move_to_position;
 
 pub use self::action::Action;
+pub use self::attack::AttackParams;
+pub use self::build::BuildParams;
+pub use self::drop_off::DropOffParams;
+pub use self::gather::GatherParams;
 pub use self::move_to_position::MoveToPositionParams;
@@ -0,0 +1,61 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat::ResourceType;
+use ecs::resource::path_finder::Path;
+use specs;
+use types::{Fixed, Vector3};
+
+#[derive(Clone, Debug)]
+pub struct GatherActionComponent {
+    /// The resource site being gathered from
+    pub target: Option<specs::Entity>,
+
+    /// The type of resource being gathered and where it was last gathered from,
+    /// for finding another site nearby once the current one runs out
+    pub resource_type: Option<ResourceType>,
+    pub last_site_position: Option<Vector3>,
+
+    /// The drop site the unit is returning its resources to, if it's returning
+    pub drop_site: Option<specs::Entity>,
+    pub returning: bool,
+
+    pub path: Path,
+    pub repath_timer: Fixed,
+}
+
+impl specs::Component for GatherActionComponent {
+    type Storage = specs::HashMapStorage<GatherActionComponent>;
+}
+
+impl GatherActionComponent {
+    pub fn new(target: specs::Entity) -> GatherActionComponent {
+        GatherActionComponent {
+            target: Some(target),
+            resource_type: None,
+            last_site_position: None,
+            drop_site: None,
+            returning: false,
+            path: Path::new(),
+            repath_timer: 0.into(),
+        }
+    }
+}
@@ -19,6 +19,14 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+mod attack;
+mod build;
+mod drop_off;
+mod gather;
 mod move_to_position;
 
+pub use self::attack::AttackActionComponent;
+pub use self::build::BuildActionComponent;
+pub use self::drop_off::DropOffActionComponent;
+pub use self::gather::GatherActionComponent;
 pub use self::move_to_position::MoveToPositionActionComponent;
@@ -0,0 +1,44 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat::ResourceType;
+use specs;
+use types::Fixed;
+
+/// Resources a unit has gathered and is carrying back to a drop site
+#[derive(Clone, Debug)]
+pub struct CarriedResourceComponent {
+    pub resource_type: ResourceType,
+    pub amount: Fixed,
+}
+
+impl specs::Component for CarriedResourceComponent {
+    type Storage = specs::HashMapStorage<CarriedResourceComponent>;
+}
+
+impl CarriedResourceComponent {
+    pub fn new(resource_type: ResourceType) -> CarriedResourceComponent {
+        CarriedResourceComponent {
+            resource_type: resource_type,
+            amount: 0.into(),
+        }
+    }
+}
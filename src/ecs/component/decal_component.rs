@@ -19,7 +19,7 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use identifier::{PlayerColorId, SlpFileId};
+use identifier::{PaletteId, PlayerColorId, SlpFileId};
 use resource::DrsKey;
 use specs;
 use types::Fixed;
@@ -29,6 +29,9 @@ pub struct DecalComponent {
     pub player_color_id: PlayerColorId,
     pub drs_key: DrsKey,
     pub slp_file_id: SlpFileId,
+
+    /// Interface shapes aren't all drawn with the game's palette
+    pub palette_id: PaletteId,
     pub frame: u16,
     pub frame_time: Fixed,
 }
@@ -38,11 +41,16 @@ impl specs::Component for DecalComponent {
 }
 
 impl DecalComponent {
-    pub fn new(player_color_id: PlayerColorId, drs_key: DrsKey, slp_file_id: SlpFileId) -> DecalComponent {
+    pub fn new(player_color_id: PlayerColorId,
+               drs_key: DrsKey,
+               slp_file_id: SlpFileId,
+               palette_id: PaletteId)
+               -> DecalComponent {
         DecalComponent {
             player_color_id: player_color_id,
             drs_key: drs_key,
             slp_file_id: slp_file_id,
+            palette_id: palette_id,
             frame: 0u16,
             frame_time: 0.into(),
         }
@@ -0,0 +1,46 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use ecs::UnitComponent;
+use specs;
+use types::Fixed;
+
+/// Marks an entity as playing its dying animation. Once the animation is over,
+/// the entity is replaced by the unit's dead unit (e.g., a corpse or rubble).
+#[derive(Clone, Debug)]
+pub struct DyingComponent {
+    /// The unit that died
+    pub unit: UnitComponent,
+    pub time_left: Fixed,
+}
+
+impl specs::Component for DyingComponent {
+    type Storage = specs::HashMapStorage<DyingComponent>;
+}
+
+impl DyingComponent {
+    pub fn new(unit: UnitComponent, time_left: Fixed) -> DyingComponent {
+        DyingComponent {
+            unit: unit,
+            time_left: time_left,
+        }
+    }
+}
@@ -31,6 +31,9 @@ pub struct GraphicComponent {
     pub frame_time: Fixed,
     pub flip_horizontal: bool,
     pub flip_vertical: bool,
+
+    /// Frame of the animation cycle that sounds were last played for
+    pub sound_frame: Option<u16>,
 }
 
 impl specs::Component for GraphicComponent {
@@ -46,6 +49,7 @@ impl GraphicComponent {
             frame_time: 0.into(),
             flip_horizontal: false,
             flip_vertical: false,
+            sound_frame: None,
         }
     }
 
@@ -53,5 +57,6 @@ impl GraphicComponent {
         self.graphic_id = graphic_id;
         self.frame = 0u16;
         self.frame_time = 0.into();
+        self.sound_frame = None;
     }
 }
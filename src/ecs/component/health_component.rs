@@ -0,0 +1,50 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use specs;
+
+#[derive(Clone, Debug)]
+pub struct HealthComponent {
+    pub hit_points: i32,
+    pub max_hit_points: i32,
+}
+
+impl specs::Component for HealthComponent {
+    type Storage = specs::VecStorage<HealthComponent>;
+}
+
+impl HealthComponent {
+    pub fn new(max_hit_points: i32) -> HealthComponent {
+        HealthComponent {
+            hit_points: max_hit_points,
+            max_hit_points: max_hit_points,
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.hit_points > 0
+    }
+
+    /// Removes the given amount of hit points, stopping at zero
+    pub fn damage(&mut self, amount: i32) {
+        self.hit_points = if amount >= self.hit_points { 0 } else { self.hit_points - amount };
+    }
+}
@@ -22,9 +22,19 @@
 mod action;
 mod action_queue_component;
 mod camera_component;
+mod carried_resource_component;
+mod construction_component;
+mod corpse_component;
+mod damage_graphic_component;
 mod decal_component;
+mod dying_component;
 mod graphic_component;
+mod health_component;
 mod on_screen_component;
+mod production_queue_component;
+mod projectile_component;
+mod research_queue_component;
+mod resource_site_component;
 mod selected_unit_component;
 mod transform_component;
 mod unit_component;
@@ -33,9 +43,19 @@ mod velocity_component;
 pub use self::action::*;
 pub use self::action_queue_component::ActionQueueComponent;
 pub use self::camera_component::CameraComponent;
+pub use self::carried_resource_component::CarriedResourceComponent;
+pub use self::construction_component::ConstructionComponent;
+pub use self::corpse_component::CorpseComponent;
+pub use self::damage_graphic_component::DamageGraphicComponent;
 pub use self::decal_component::DecalComponent;
+pub use self::dying_component::DyingComponent;
 pub use self::graphic_component::GraphicComponent;
+pub use self::health_component::HealthComponent;
 pub use self::on_screen_component::OnScreenComponent;
+pub use self::production_queue_component::{ProductionQueueComponent, RallyPoint};
+pub use self::projectile_component::ProjectileComponent;
+pub use self::research_queue_component::ResearchQueueComponent;
+pub use self::resource_site_component::ResourceSiteComponent;
 pub use self::selected_unit_component::SelectedUnitComponent;
 pub use self::transform_component::TransformComponent;
 pub use self::unit_component::UnitComponent;
@@ -0,0 +1,123 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use identifier::UnitId;
+use specs;
+use std::collections::VecDeque;
+use types::{Fixed, Vector3};
+
+/// Most units that can be queued at a building at once
+pub const MAX_QUEUED_UNITS: usize = 15;
+
+/// Where units go once they're trained
+#[derive(Copy, Clone, Debug)]
+pub enum RallyPoint {
+    Position(Vector3),
+
+    /// A unit to gather from (a gather point) or walk to
+    Unit(specs::Entity),
+}
+
+/// The units a building is training, in order
+#[derive(Clone, Debug)]
+pub struct ProductionQueueComponent {
+    queue: VecDeque<UnitId>,
+
+    /// Seconds spent training the unit at the front of the queue
+    pub progress: Fixed,
+
+    pub rally_point: Option<RallyPoint>,
+}
+
+impl specs::Component for ProductionQueueComponent {
+    type Storage = specs::HashMapStorage<ProductionQueueComponent>;
+}
+
+impl ProductionQueueComponent {
+    pub fn new() -> ProductionQueueComponent {
+        ProductionQueueComponent {
+            queue: VecDeque::new(),
+            progress: 0.into(),
+            rally_point: None,
+        }
+    }
+
+    /// Adds the unit to the back of the queue; returns false if the queue is full
+    pub fn enqueue(&mut self, unit_id: UnitId) -> bool {
+        if self.queue.len() >= MAX_QUEUED_UNITS {
+            return false;
+        }
+        self.queue.push_back(unit_id);
+        true
+    }
+
+    /// Removes the unit at the back of the queue, abandoning its progress if it's the one in training
+    pub fn dequeue(&mut self) -> Option<UnitId> {
+        let unit_id = self.queue.pop_back();
+        if self.queue.is_empty() {
+            self.progress = 0.into();
+        }
+        unit_id
+    }
+
+    /// The unit currently being trained
+    pub fn current(&self) -> Option<UnitId> {
+        self.queue.front().cloned()
+    }
+
+    /// Removes the unit that just finished training from the front of the queue
+    pub fn finish_current(&mut self) -> Option<UnitId> {
+        self.progress = 0.into();
+        self.queue.pop_front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_QUEUED_UNITS, ProductionQueueComponent};
+    use types::Fixed;
+
+    #[test]
+    fn test_production_queue() {
+        let mut queue = ProductionQueueComponent::new();
+        assert!(queue.enqueue(83.into()));
+        assert!(queue.enqueue(73.into()));
+        assert_eq!(Some(83.into()), queue.current());
+
+        queue.progress = 5.into();
+        assert_eq!(Some(73.into()), queue.dequeue());
+        assert_eq!(Fixed::from(5), queue.progress);
+        assert_eq!(Some(83.into()), queue.dequeue());
+        assert_eq!(Fixed::from(0), queue.progress);
+        assert_eq!(None, queue.dequeue());
+
+        for _ in 0..MAX_QUEUED_UNITS {
+            assert!(queue.enqueue(83.into()));
+        }
+        assert!(!queue.enqueue(83.into()));
+        assert_eq!(Some(83.into()), queue.finish_current());
+        assert_eq!(MAX_QUEUED_UNITS - 1, queue.len());
+    }
+}
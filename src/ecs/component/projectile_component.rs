@@ -0,0 +1,99 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use ecs::UnitComponent;
+use specs;
+use types::{Fixed, Vector3};
+
+#[derive(Clone, Debug)]
+pub struct ProjectileComponent {
+    /// The unit that fired the projectile; its attack determines the damage done
+    pub source: UnitComponent,
+    pub source_entity: specs::Entity,
+
+    /// The unit the projectile is going to hit, or None if it's going to miss
+    pub target: Option<specs::Entity>,
+
+    pub start: Vector3,
+    pub end: Vector3,
+
+    /// Height of the top of the arc above the straight line from start to end
+    pub arc_height: Fixed,
+    pub flight_time: Fixed,
+    pub elapsed: Fixed,
+}
+
+impl specs::Component for ProjectileComponent {
+    type Storage = specs::HashMapStorage<ProjectileComponent>;
+}
+
+impl ProjectileComponent {
+    pub fn new(source: UnitComponent,
+               source_entity: specs::Entity,
+               target: Option<specs::Entity>,
+               start: Vector3,
+               end: Vector3,
+               arc_height: Fixed,
+               flight_time: Fixed)
+               -> ProjectileComponent {
+        ProjectileComponent {
+            source: source,
+            source_entity: source_entity,
+            target: target,
+            start: start,
+            end: end,
+            arc_height: arc_height,
+            flight_time: flight_time,
+            elapsed: 0.into(),
+        }
+    }
+
+    /// Position along the projectile's arc at the given time since it was fired
+    pub fn position_at(&self, time: Fixed) -> Vector3 {
+        let t = self.progress(time);
+        let mut position = self.start + (self.end - self.start) * t;
+        position.z = position.z + self.arc_height * 4.into() * t * (Fixed::from(1) - t);
+        position
+    }
+
+    /// Velocity along the projectile's arc at the given time since it was fired
+    pub fn velocity_at(&self, time: Fixed) -> Vector3 {
+        if self.flight_time <= 0.into() {
+            return Vector3::new(0.into(), 0.into(), 0.into());
+        }
+
+        let t = self.progress(time);
+        let inverse_time = Fixed::from(1) / self.flight_time;
+        let mut velocity = (self.end - self.start) * inverse_time;
+        velocity.z = velocity.z + self.arc_height * 4.into() * (Fixed::from(1) - t * 2.into()) * inverse_time;
+        velocity
+    }
+
+    fn progress(&self, time: Fixed) -> Fixed {
+        if self.flight_time <= 0.into() || time >= self.flight_time {
+            1.into()
+        } else if time <= 0.into() {
+            0.into()
+        } else {
+            time / self.flight_time
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use identifier::ResearchId;
+use specs;
+use std::collections::VecDeque;
+use types::Fixed;
+
+/// Most research that can be queued at a building at once
+pub const MAX_QUEUED_RESEARCH: usize = 5;
+
+/// The research a building is performing, in order
+#[derive(Clone, Debug)]
+pub struct ResearchQueueComponent {
+    queue: VecDeque<ResearchId>,
+
+    /// Seconds spent on the research at the front of the queue
+    pub progress: Fixed,
+}
+
+impl specs::Component for ResearchQueueComponent {
+    type Storage = specs::HashMapStorage<ResearchQueueComponent>;
+}
+
+impl ResearchQueueComponent {
+    pub fn new() -> ResearchQueueComponent {
+        ResearchQueueComponent {
+            queue: VecDeque::new(),
+            progress: 0.into(),
+        }
+    }
+
+    /// Adds the research to the back of the queue; returns false if the queue is full
+    /// or the research is already queued
+    pub fn enqueue(&mut self, research_id: ResearchId) -> bool {
+        if self.queue.len() >= MAX_QUEUED_RESEARCH || self.contains(research_id) {
+            return false;
+        }
+        self.queue.push_back(research_id);
+        true
+    }
+
+    /// Removes the research at the back of the queue, abandoning its progress if it's the one in progress
+    pub fn dequeue(&mut self) -> Option<ResearchId> {
+        let research_id = self.queue.pop_back();
+        if self.queue.is_empty() {
+            self.progress = 0.into();
+        }
+        research_id
+    }
+
+    /// The research currently in progress
+    pub fn current(&self) -> Option<ResearchId> {
+        self.queue.front().cloned()
+    }
+
+    /// Removes the research that was just completed from the front of the queue
+    pub fn finish_current(&mut self) -> Option<ResearchId> {
+        self.progress = 0.into();
+        self.queue.pop_front()
+    }
+
+    pub fn contains(&self, research_id: ResearchId) -> bool {
+        self.queue.contains(&research_id)
+    }
+
+    /// Everything in the queue, starting with the research in progress
+    pub fn queued(&self) -> Vec<ResearchId> {
+        self.queue.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MAX_QUEUED_RESEARCH, ResearchQueueComponent};
+
+    #[test]
+    fn test_research_queue() {
+        let mut queue = ResearchQueueComponent::new();
+        assert!(queue.enqueue(101.into()));
+        assert!(!queue.enqueue(101.into()));
+        assert_eq!(Some(101.into()), queue.current());
+
+        for research_id in 0..(MAX_QUEUED_RESEARCH - 1) {
+            assert!(queue.enqueue(research_id.into()));
+        }
+        assert!(!queue.enqueue(50.into()));
+        assert_eq!(Some(101.into()), queue.finish_current());
+        assert!(!queue.contains(101.into()));
+    }
+}
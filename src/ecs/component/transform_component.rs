@@ -51,6 +51,12 @@ impl TransformComponent {
         self.current_position = position;
     }
 
+    /// Moves straight to the position without interpolating from the old one
+    pub fn jump_to(&mut self, position: Vector3) {
+        self.last_position = position;
+        self.current_position = position;
+    }
+
     pub fn lerped_position(&self, lerp: Fixed) -> Vector3 {
         self.current_position + (self.current_position - self.last_position) * lerp
     }
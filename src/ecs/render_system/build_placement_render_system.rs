@@ -0,0 +1,72 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat;
+use ecs::resource::{BuildPlacement, Players, RenderCommands, ViewProjector};
+use specs;
+use super::RenderSystem;
+use super::graphic_render_system::render_graphic;
+use types::{Color, Fixed};
+
+/// Renders the building being placed in build mode, tinted red where it can't be placed
+pub struct BuildPlacementRenderSystem {
+    empires: dat::EmpiresDbRef,
+}
+
+impl BuildPlacementRenderSystem {
+    pub fn new(empires: dat::EmpiresDbRef) -> BuildPlacementRenderSystem {
+        BuildPlacementRenderSystem { empires: empires }
+    }
+}
+
+impl RenderSystem for BuildPlacementRenderSystem {
+    fn render(&mut self, arg: specs::RunArg, _lerp: Fixed) {
+        fetch_components!(arg, _entities, [
+            resource(build_placement: BuildPlacement),
+            resource(players: Players),
+            resource(projector: ViewProjector),
+            mut resource(render_commands: RenderCommands),
+        ]);
+
+        let (unit_id, position) = match (build_placement.unit_id, build_placement.position) {
+            (Some(unit_id), Some(position)) => (unit_id, position),
+            _ => return,
+        };
+        let local_player = players.local_player();
+        let unit_info = self.empires.unit(local_player.civ_id, unit_id);
+        if let Some(graphic_id) = unit_info.standing_graphic {
+            let tint = if build_placement.valid {
+                Color::rgba(255, 255, 255, 160)
+            } else {
+                Color::rgba(255, 64, 64, 160)
+            };
+            render_graphic(&self.empires,
+                           &mut render_commands,
+                           &projector.project(&position),
+                           local_player.player_color_id,
+                           graphic_id,
+                           0,
+                           false,
+                           false,
+                           Some(tint));
+        }
+    }
+}
@@ -45,9 +45,8 @@ impl RenderSystem for DecalRenderSystem {
 
         for (transform, decal) in (&transforms, &decals).iter() {
             let position = projector.project(&transform.lerped_position(lerp));
-            let shape_key = ShapeKey::new(decal.drs_key,
-                                          decal.slp_file_id,
-                                          decal.player_color_id.into());
+            let shape_key = ShapeKey::new(decal.drs_key, decal.slp_file_id, decal.player_color_id.into())
+                .with_palette(decal.palette_id);
             render_commands.push(RenderCommand::new_shape(20,
                                                           position.y,
                                                           shape_key,
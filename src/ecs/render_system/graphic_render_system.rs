@@ -20,14 +20,22 @@
 // SOFTWARE.
 
 use dat;
-use ecs::{GraphicComponent, OnScreenComponent, TransformComponent};
-use ecs::resource::{RenderCommands, ViewProjector};
+use ecs::{GraphicComponent, OnScreenComponent, TransformComponent, UnitComponent};
+use ecs::resource::{Players, RenderCommands, TileVisibility, ViewProjector, Visibility};
 use identifier::{GraphicId, PlayerColorId};
 use nalgebra::Vector2;
 use resource::{DrsKey, RenderCommand, ShapeKey};
 use specs::{self, Join};
 use super::RenderSystem;
-use types::Fixed;
+use types::{Color, Fixed};
+
+/// Darkens what's drawn in explored areas that are out of sight
+pub const FOG_TINT: Color = Color {
+    r: 128,
+    g: 128,
+    b: 128,
+    a: 255,
+};
 
 pub struct GraphicRenderSystem {
     empires: dat::EmpiresDbRef,
@@ -37,63 +45,103 @@ impl GraphicRenderSystem {
     pub fn new(empires: dat::EmpiresDbRef) -> GraphicRenderSystem {
         GraphicRenderSystem { empires: empires }
     }
+}
 
-    fn render_graphic(&self,
+/// Renders the graphic and its deltas, optionally tinted (e.g., for a building placement ghost)
+pub fn render_graphic(empires: &dat::EmpiresDb,
                       render_commands: &mut RenderCommands,
-                      projector: &ViewProjector,
                       position: &Vector2<i32>,
                       player_color_id: PlayerColorId,
                       graphic_id: GraphicId,
                       frame: u16,
                       flip_horizontal: bool,
-                      flip_vertical: bool) {
-        let graphic = self.empires.graphic(graphic_id);
-        if let Some(slp_id) = graphic.slp_id {
-            let shape_key = ShapeKey::new(DrsKey::Graphics, slp_id, player_color_id.into());
-            render_commands.push(RenderCommand::new_shape(graphic.layer as u16,
-                                                          position.y,
-                                                          shape_key,
-                                                          frame,
-                                                          *position,
-                                                          flip_horizontal,
-                                                          flip_vertical));
-        }
-        for delta in &graphic.deltas {
-            let delta_position = *position + Vector2::new(delta.offset_x as i32, delta.offset_y as i32);
-            self.render_graphic(render_commands,
-                                projector,
-                                &delta_position,
-                                player_color_id,
-                                delta.graphic_id,
-                                frame,
-                                flip_horizontal,
-                                flip_vertical);
-        }
+                      flip_vertical: bool,
+                      tint: Option<Color>) {
+    let graphic = empires.graphic(graphic_id);
+    if let Some(slp_id) = graphic.slp_id {
+        let shape_key = ShapeKey::new(DrsKey::Graphics, slp_id, player_color_id.into());
+        let command = RenderCommand::new_shape(graphic.layer as u16,
+                                               position.y,
+                                               shape_key,
+                                               frame,
+                                               *position,
+                                               flip_horizontal,
+                                               flip_vertical);
+        render_commands.push(match tint {
+            Some(tint) => command.tinted(tint),
+            None => command,
+        });
+    }
+    for delta in &graphic.deltas {
+        let delta_position = *position + Vector2::new(delta.offset_x as i32, delta.offset_y as i32);
+        render_graphic(empires,
+                       render_commands,
+                       &delta_position,
+                       player_color_id,
+                       delta.graphic_id,
+                       frame,
+                       flip_horizontal,
+                       flip_vertical,
+                       tint);
     }
 }
 
 impl RenderSystem for GraphicRenderSystem {
     fn render(&mut self, arg: specs::RunArg, lerp: Fixed) {
-        fetch_components!(arg, _entities, [
+        fetch_components!(arg, entities, [
             components(transforms: TransformComponent),
             components(graphics: GraphicComponent),
             components(on_screen: OnScreenComponent),
+            components(units: UnitComponent),
             resource(projector: ViewProjector),
+            resource(players: Players),
+            resource(visibility: Visibility),
             mut resource(render_commands: RenderCommands),
         ]);
 
-        for (transform, graphic, _on_screen) in (&transforms, &graphics, &on_screen).iter() {
+        let local_player_id = players.local_player().player_id;
+        let drawn = (&entities, &transforms, &graphics, &on_screen);
+        for (entity, transform, graphic, _on_screen) in drawn.iter() {
+            // Out of sight, only units that stay visible in the fog (e.g., trees) are drawn
+            let tint = match visibility.visibility_at(local_player_id, transform.position()) {
+                TileVisibility::Visible => None,
+                TileVisibility::Explored => {
+                    let visible_in_fog = units.get(entity)
+                        .map(|unit| unit.db(&self.empires).visible_in_fog)
+                        .unwrap_or(false);
+                    if !visible_in_fog {
+                        continue;
+                    }
+                    Some(FOG_TINT)
+                }
+                TileVisibility::Unexplored => continue,
+            };
             if let Some(graphic_id) = graphic.graphic_id {
                 let position = projector.project(&transform.lerped_position(lerp));
-                self.render_graphic(&mut render_commands,
-                                    &projector,
-                                    &position,
-                                    graphic.player_color_id,
-                                    graphic_id,
-                                    graphic.frame,
-                                    graphic.flip_horizontal,
-                                    graphic.flip_vertical);
+                render_graphic(&self.empires,
+                               &mut render_commands,
+                               &position,
+                               graphic.player_color_id,
+                               graphic_id,
+                               graphic.frame,
+                               graphic.flip_horizontal,
+                               graphic.flip_vertical,
+                               tint);
             }
         }
+
+        // Buildings that are out of sight are drawn as the player last saw them
+        for building in visibility.remembered_buildings(local_player_id) {
+            let position = projector.project(&building.position);
+            render_graphic(&self.empires,
+                           &mut render_commands,
+                           &position,
+                           building.player_color_id,
+                           building.graphic_id,
+                           building.frame,
+                           building.flip_horizontal,
+                           false,
+                           Some(FOG_TINT));
+        }
     }
 }
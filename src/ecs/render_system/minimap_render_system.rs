@@ -0,0 +1,175 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat;
+use ecs::{TransformComponent, UnitComponent};
+use ecs::resource::{Minimap, Players, RenderCommands, Terrain, TileVisibility, ViewProjector, Viewport,
+                    Visibility};
+use nalgebra::Vector2;
+use resource::RenderCommand;
+use specs::{self, Join};
+use std::sync::Arc;
+use super::RenderSystem;
+use types::{Color, Fixed, Rect};
+
+/// The minimap is drawn over everything else
+const MINIMAP_LAYER: u16 = 500;
+
+const BACKGROUND_COLOR: Color = Color {
+    r: 0,
+    g: 0,
+    b: 0,
+    a: 255,
+};
+
+const VIEWPORT_OUTLINE_COLOR: Color = Color {
+    r: 255,
+    g: 255,
+    b: 255,
+    a: 255,
+};
+
+/// Draws the terrain and units on the minimap along with the part of the map that's on screen
+pub struct MinimapRenderSystem {
+    empires: dat::EmpiresDbRef,
+    palette: Arc<Vec<u32>>,
+}
+
+impl MinimapRenderSystem {
+    pub fn new(empires: dat::EmpiresDbRef, palette: Arc<Vec<u32>>) -> MinimapRenderSystem {
+        MinimapRenderSystem {
+            empires: empires,
+            palette: palette,
+        }
+    }
+
+    fn palette_color(&self, index: u8) -> Color {
+        // Palette colors are packed RGBA8888
+        let color = self.palette.get(index as usize).cloned().unwrap_or(0);
+        Color::rgb((color >> 24) as u8, (color >> 16) as u8, (color >> 8) as u8)
+    }
+}
+
+impl RenderSystem for MinimapRenderSystem {
+    fn render(&mut self, arg: specs::RunArg, lerp: Fixed) {
+        fetch_components!(arg, _entities, [
+            components(transforms: TransformComponent),
+            components(units: UnitComponent),
+            resource(players: Players),
+            resource(terrain: Terrain),
+            resource(view_projector: ViewProjector),
+            resource(viewport: Viewport),
+            resource(visibility: Visibility),
+            mut resource(minimap: Minimap),
+            mut resource(render_commands: RenderCommands),
+        ]);
+
+        let local_player_id = players.local_player().player_id;
+        let camera = viewport.lerped_top_left(lerp);
+        let area = minimap.screen_rect(&viewport.size);
+        let origin = camera + Vector2::new(area.x, area.y);
+        render_commands.push(RenderCommand::new_rect(MINIMAP_LAYER,
+                                                     0,
+                                                     BACKGROUND_COLOR,
+                                                     Rect::of(origin.x, origin.y, area.w, area.h),
+                                                     true));
+
+        // Unexplored tiles are left black, and explored tiles that are out of sight are darkened
+        for row in 0..terrain.height() {
+            for col in 0..terrain.width() {
+                let tile_visibility = visibility.visibility(local_player_id, row, col);
+                let color = if tile_visibility == TileVisibility::Unexplored {
+                    None
+                } else {
+                    let terrain_id = terrain.tile_at_row_col(row, col).terrain_id;
+                    let color = self.palette_color(self.empires.terrain(terrain_id).colors[0]);
+                    if tile_visibility == TileVisibility::Visible {
+                        Some(color)
+                    } else {
+                        Some(Color::rgb(color.r / 2, color.g / 2, color.b / 2))
+                    }
+                };
+                minimap.set_tile_color(row, col, color);
+            }
+        }
+        for runs in minimap.color_runs() {
+            for run in runs {
+                let mut rect = run.rect;
+                rect.translate(origin.x, origin.y);
+                render_commands.push(RenderCommand::new_rect(MINIMAP_LAYER, 1, run.color, rect, true));
+            }
+        }
+
+        for (transform, unit) in (&transforms, &units).iter() {
+            let unit_info = unit.db(&self.empires);
+            let visible = match visibility.visibility_at(local_player_id, transform.position()) {
+                TileVisibility::Visible => true,
+                TileVisibility::Explored => unit_info.visible_in_fog,
+                TileVisibility::Unexplored => false,
+            };
+            if !visible && unit.player_id != local_player_id {
+                continue;
+            }
+
+            // Buildings are diamonds, and resources (like trees) have their own colors
+            let (diamond, player_colored) = match unit_info.minimap_mode {
+                1 => (false, true),
+                2 | 3 => (true, true),
+                4 | 5 => (false, false),
+                _ => continue,
+            };
+            let color = if player_colored {
+                self.palette_color(self.empires.player_color(unit.player_id.into()).palette_index)
+            } else {
+                self.palette_color(unit_info.minimap_color)
+            };
+
+            let point = origin + minimap.to_minimap(transform.position());
+            if diamond {
+                let points = vec![point + Vector2::new(0, -2),
+                                  point + Vector2::new(3, 0),
+                                  point + Vector2::new(0, 2),
+                                  point + Vector2::new(-3, 0)];
+                render_commands.push(RenderCommand::new_polygon(MINIMAP_LAYER, 2, color, points, true));
+            } else {
+                let rect = Rect::of(point.x - 1, point.y - 1, 2, 2);
+                render_commands.push(RenderCommand::new_rect(MINIMAP_LAYER, 3, color, rect, true));
+            }
+        }
+
+        // The part of the map that's on screen
+        let top_left = view_projector.unproject_at_elevation(&camera, 0.into());
+        let bottom_right = view_projector.unproject_at_elevation(&(camera + viewport.size), 0.into());
+        let (top_left, bottom_right) = (minimap.to_minimap(&top_left), minimap.to_minimap(&bottom_right));
+        let outline = Rect::of(top_left.x,
+                               top_left.y,
+                               bottom_right.x - top_left.x,
+                               bottom_right.y - top_left.y);
+        if let Some(mut outline) = outline.intersection(&Rect::of(0, 0, area.w, area.h)) {
+            outline.translate(origin.x, origin.y);
+            render_commands.push(RenderCommand::new_rect(MINIMAP_LAYER,
+                                                         4,
+                                                         VIEWPORT_OUTLINE_COLOR,
+                                                         outline,
+                                                         false));
+        }
+    }
+}
@@ -19,15 +19,19 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+mod build_placement_render_system;
 mod decal_render_system;
 mod graphic_render_system;
+mod minimap_render_system;
 mod render_system;
 mod terrain_render_system;
 mod tile_debug_render_system;
 mod unit_selection_render_system;
 
+pub use self::build_placement_render_system::BuildPlacementRenderSystem;
 pub use self::decal_render_system::DecalRenderSystem;
 pub use self::graphic_render_system::GraphicRenderSystem;
+pub use self::minimap_render_system::MinimapRenderSystem;
 pub use self::render_system::{RenderSystem, RenderSystemWrapper};
 pub use self::terrain_render_system::TerrainRenderSystem;
 pub use self::tile_debug_render_system::TileDebugRenderSystem;
@@ -21,7 +21,7 @@
 
 
 use dat;
-use ecs::resource::{RenderCommands, Terrain, ViewProjector, Viewport};
+use ecs::resource::{Players, RenderCommands, Terrain, TileVisibility, ViewProjector, Viewport, Visibility};
 use ecs::resource::terrain::{BlendInfo, BorderMatch, ElevationGraphic, ElevationMatch};
 use identifier::{SlpFileId, TerrainBorderId, TerrainId};
 
@@ -32,7 +32,8 @@ use std::cmp;
 
 use std::collections::HashMap;
 use super::RenderSystem;
-use types::{Fixed, Rect, Vector3};
+use super::graphic_render_system::FOG_TINT;
+use types::{Color, Fixed, Rect, Vector3};
 
 const TERRAIN_LAYER: u16 = 0;
 
@@ -75,6 +76,8 @@ impl RenderSystem for TerrainRenderSystem {
         fetch_components!(arg, _entities, [
             resource(projector: ViewProjector),
             resource(viewport: Viewport),
+            resource(players: Players),
+            resource(visibility: Visibility),
             mut resource(terrain: Terrain),
             mut resource(render_commands: RenderCommands),
         ]);
@@ -90,12 +93,19 @@ impl RenderSystem for TerrainRenderSystem {
         bounds.w = bounds.x + viewport.size.x + 2 * tile_width;
         bounds.h = bounds.y + viewport.size.y + 2 * tile_height;
 
+        // Unexplored tiles are left black, and explored tiles that are out of sight are darkened
+        let local_player_id = players.local_player().player_id;
         for row in area.y..(area.y + area.h) {
             for col in (area.x..(area.x + area.w)).rev() {
                 if row >= 0 && row < terrain.width() && col >= 0 && col < terrain.height() {
                     let pos = projector.project(&Vector3::new(col.into(), row.into(), 0.into()));
                     if pos.x > bounds.x && pos.y > bounds.y && pos.x < bounds.w && pos.y < bounds.h {
-                        self.blend_and_render_tile(&mut *render_commands, row, col, &mut terrain);
+                        let tint = match visibility.visibility(local_player_id, row, col) {
+                            TileVisibility::Visible => None,
+                            TileVisibility::Explored => Some(FOG_TINT),
+                            TileVisibility::Unexplored => continue,
+                        };
+                        self.blend_and_render_tile(&mut *render_commands, row, col, &mut terrain, tint);
                     }
                 }
             }
@@ -116,7 +126,8 @@ impl TerrainRenderSystem {
                              render_commands: &mut RenderCommands,
                              row: i32,
                              col: i32,
-                             terrain: &mut Terrain) {
+                             terrain: &mut Terrain,
+                             tint: Option<Color>) {
         let blended_tile = terrain.blend_at(row, col);
         let elevation_match = self.resolve_elevation(&blended_tile);
 
@@ -137,7 +148,8 @@ impl TerrainRenderSystem {
                              &tile,
                              render_offset_y,
                              row,
-                             col);
+                             col,
+                             tint);
         }
 
         if blended_tile.border_id.is_some() {
@@ -149,7 +161,8 @@ impl TerrainRenderSystem {
                                     elevation_graphic.index,
                                     render_offset_y,
                                     row,
-                                    col)
+                                    col,
+                                    tint)
             }
         }
     }
@@ -160,17 +173,22 @@ impl TerrainRenderSystem {
                       tile: &Tile<T>,
                       render_offset_y: f32,
                       row: i32,
-                      col: i32) {
+                      col: i32,
+                      tint: Option<Color>) {
         let (x, y) = self.project_row_col(row, col, render_offset_y);
         let frame_num = ((row + 1) * (col - row)) as usize % tile.frame_range.len();
 
-        render_commands.push(RenderCommand::new_shape(TERRAIN_LAYER,
-                                                      y,
-                                                      ShapeKey::new(drs_key, tile.slp_id, 0.into()),
-                                                      tile.frame_range[frame_num] as u16,
-                                                      Vector2::new(x, y),
-                                                      false,
-                                                      false));
+        let command = RenderCommand::new_shape(TERRAIN_LAYER,
+                                               y,
+                                               ShapeKey::new(drs_key, tile.slp_id, 0.into()),
+                                               tile.frame_range[frame_num] as u16,
+                                               Vector2::new(x, y),
+                                               false,
+                                               false);
+        render_commands.push(match tint {
+            Some(tint) => command.tinted(tint),
+            None => command,
+        });
     }
 
     fn render_borders(&mut self,
@@ -180,7 +198,8 @@ impl TerrainRenderSystem {
                       elevation_index: u8,
                       render_offset_y: f32,
                       row: i32,
-                      col: i32) {
+                      col: i32,
+                      tint: Option<Color>) {
         for border_index in border_indices {
             let border_key = TileKey::new(border_id, *border_index, elevation_index);
 
@@ -195,7 +214,8 @@ impl TerrainRenderSystem {
                              border,
                              render_offset_y,
                              row,
-                             col);
+                             col,
+                             tint);
         }
     }
 
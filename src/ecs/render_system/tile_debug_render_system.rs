@@ -19,12 +19,14 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use ecs::resource::{KeyboardKeyStates, MouseState, RenderCommands, Terrain, ViewProjector, Viewport};
-use media::{Key, KeyState};
-use resource::{DrsKey, RenderCommand, ShapeKey};
+use ecs::resource::{InputAction, InputActionStates, MouseState, RenderCommands, Terrain, ViewProjector,
+                    Viewport};
+use media::KeyState;
+use nalgebra::Vector2;
+use resource::{DrsKey, FontStyle, RenderCommand, ShapeKey, TextAlignment};
 use specs;
 use super::RenderSystem;
-use types::Fixed;
+use types::{Color, Fixed};
 
 /// Used for debugging tile positions and tile picking
 pub struct TileDebugRenderSystem;
@@ -41,7 +43,7 @@ impl RenderSystem for TileDebugRenderSystem {
             resource(mouse_state: MouseState),
             resource(view_projector: ViewProjector),
             resource(viewport: Viewport),
-            resource(keyboard_key_states: KeyboardKeyStates),
+            resource(actions: InputActionStates),
             mut resource(terrain: Terrain),
             mut resource(render_commands: RenderCommands),
         ]);
@@ -49,7 +51,7 @@ impl RenderSystem for TileDebugRenderSystem {
         let viewport_top_left = viewport.top_left_i32();
         let tile_pos = view_projector.unproject(&(mouse_state.position + viewport_top_left), &*terrain);
 
-        if keyboard_key_states.key_state(Key::Space) == KeyState::TransitionUp {
+        if actions.key_state(InputAction::DebugTileInfo) == KeyState::TransitionUp {
             let row: i32 = tile_pos.y.round().into();
             let col: i32 = tile_pos.x.round().into();
             let actual_tile = *terrain.tile_at(tile_pos);
@@ -61,9 +63,21 @@ impl RenderSystem for TileDebugRenderSystem {
                      blend_info);
         }
 
-        // Draw a cactus at the tile's position
+        // Draw a cactus at the tile's position, labeled with the tile's coordinates
         let debug_pos = view_projector.project(&tile_pos);
         let shape_key = ShapeKey::new(DrsKey::Graphics, 275.into(), 0.into());
         render_commands.push(RenderCommand::new_shape(1000, 0, shape_key, 0, debug_pos, false, false));
+
+        let label = format!("({}, {})",
+                            i32::from(tile_pos.y.round()),
+                            i32::from(tile_pos.x.round()));
+        render_commands.push(RenderCommand::new_text(1000,
+                                                     1,
+                                                     label,
+                                                     FontStyle::Small,
+                                                     Color::rgb(255, 255, 255),
+                                                     debug_pos + Vector2::new(12, -4),
+                                                     TextAlignment::Left,
+                                                     None));
     }
 }
@@ -21,7 +21,7 @@
 
 use dat;
 use ecs::{OnScreenComponent, SelectedUnitComponent, TransformComponent, UnitComponent};
-use ecs::resource::{RenderCommands, ViewProjector};
+use ecs::resource::{RenderCommands, SelectionBox, ViewProjector};
 use resource::RenderCommand;
 use specs::{self, Join};
 use super::RenderSystem;
@@ -46,9 +46,15 @@ impl RenderSystem for UnitSelectionRenderSystem {
             components(on_screen: OnScreenComponent),
             components(selected_units: SelectedUnitComponent),
             resource(projector: ViewProjector),
+            resource(selection_box: SelectionBox),
             mut resource(render_commands: RenderCommands),
         ]);
 
+        // The box being dragged out is drawn over the units
+        if let Some(rect) = selection_box.rect() {
+            render_commands.push(RenderCommand::new_rect(100, 0, Color::rgb(255, 255, 255), rect, false));
+        }
+
         let items = (&transforms, &units, &selected_units, &on_screen);
         for (transform, unit, _selected_unit, _on_screen) in items.iter() {
             let unit_info = self.empires.unit(unit.civilization_id, unit.unit_id);
@@ -60,13 +66,8 @@ impl RenderSystem for UnitSelectionRenderSystem {
                                         Vector3::new(unit_box.max.x, unit_box.min.y, unit_box.min.z),
                                         Vector3::new(unit_box.max.x, unit_box.max.y, unit_box.min.z),
                                         Vector3::new(unit_box.min.x, unit_box.max.y, unit_box.min.z)];
-            for i in 0..4 {
-                render_commands.push(RenderCommand::new_line(1,
-                                                             position.y,
-                                                             color,
-                                                             projector.project(&points[i]),
-                                                             projector.project(&points[(i + 1) % 4])));
-            }
+            let points = points.iter().map(|point| projector.project(point)).collect();
+            render_commands.push(RenderCommand::new_polygon(1, position.y, color, points, false));
         }
     }
 }
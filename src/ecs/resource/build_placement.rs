@@ -0,0 +1,65 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use identifier::UnitId;
+use types::Vector3;
+
+/// The building the local player is placing while in build mode, and where it'd go
+pub struct BuildPlacement {
+    /// The building being placed; None when not in build mode
+    pub unit_id: Option<UnitId>,
+
+    /// Where the building would be placed, snapped to the tiles under the mouse
+    pub position: Option<Vector3>,
+
+    /// Whether the building can be placed at its current position
+    pub valid: bool,
+
+    /// Set when the player clicks to place the building, and handled by the BuildPlacementSystem.
+    /// The flag is whether to stay in build mode afterward (to place several in a row).
+    pub place_requested: Option<bool>,
+}
+
+impl BuildPlacement {
+    pub fn new() -> BuildPlacement {
+        BuildPlacement {
+            unit_id: None,
+            position: None,
+            valid: false,
+            place_requested: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.unit_id.is_some()
+    }
+
+    pub fn start(&mut self, unit_id: UnitId) {
+        self.unit_id = Some(unit_id);
+        self.position = None;
+        self.valid = false;
+        self.place_requested = None;
+    }
+
+    pub fn cancel(&mut self) {
+        *self = BuildPlacement::new();
+    }
+}
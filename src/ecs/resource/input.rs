@@ -31,6 +31,9 @@ pub type MouseKeyStates = KeyStates<MouseButton>;
 pub struct MouseState {
     pub position: Vector2<i32>,
     pub key_states: MouseKeyStates,
+
+    /// Wheel movement since the last update
+    pub wheel: Vector2<i32>,
 }
 
 impl MouseState {
@@ -38,6 +41,7 @@ impl MouseState {
         MouseState {
             position: Vector2::new(0, 0),
             key_states: MouseKeyStates::new(HashMap::new()),
+            wheel: Vector2::new(0, 0),
         }
     }
 }
@@ -0,0 +1,309 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use media::{Key, KeyState, KeyStates, MouseButton};
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+use toml;
+
+/// The state of every input action this update, resolved from the key and mouse button states
+pub type InputActionStates = KeyStates<InputAction>;
+
+macro_rules! input_actions {
+    ( $( $action:ident => $name:tt, )* ) => {
+        /// Something the player can do that's bound to one or more keys or mouse buttons
+        #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+        pub enum InputAction {
+            $( $action, )*
+        }
+
+        impl InputAction {
+            pub fn all() -> &'static [InputAction] {
+                const ALL: &'static [InputAction] = &[ $( InputAction::$action, )* ];
+                ALL
+            }
+
+            /// The action's name in the bindings file
+            pub fn name(&self) -> &'static str {
+                match *self {
+                    $( InputAction::$action => $name, )*
+                }
+            }
+        }
+
+        impl FromStr for InputAction {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<InputAction, String> {
+                match s {
+                    $( $name => Ok(InputAction::$action), )*
+                    _ => Err(format!("unknown input action \"{}\"", s)),
+                }
+            }
+        }
+    }
+}
+
+input_actions! {
+    ScrollNorth => "scroll_north",
+    ScrollSouth => "scroll_south",
+    ScrollWest => "scroll_west",
+    ScrollEast => "scroll_east",
+    Select => "select",
+    AddToSelection => "add_to_selection",
+    Command => "command",
+    QueueCommand => "queue_command",
+    DebugTileInfo => "debug_tile_info",
+    BuildHouse => "build_house",
+    BuildGranary => "build_granary",
+    BuildStoragePit => "build_storage_pit",
+    BuildBarracks => "build_barracks",
+    BuildDock => "build_dock",
+    CancelBuild => "cancel_build",
+    TrainUnit1 => "train_unit_1",
+    TrainUnit2 => "train_unit_2",
+    TrainUnit3 => "train_unit_3",
+    TrainUnit4 => "train_unit_4",
+    TrainUnit5 => "train_unit_5",
+    CancelTraining => "cancel_training",
+    Research1 => "research_1",
+    Research2 => "research_2",
+    Research3 => "research_3",
+    Research4 => "research_4",
+    Research5 => "research_5",
+    CancelResearch => "cancel_research",
+    AssignControlGroup => "assign_control_group",
+    ControlGroup1 => "control_group_1",
+    ControlGroup2 => "control_group_2",
+    ControlGroup3 => "control_group_3",
+    ControlGroup4 => "control_group_4",
+    ControlGroup5 => "control_group_5",
+    ControlGroup6 => "control_group_6",
+    ControlGroup7 => "control_group_7",
+    ControlGroup8 => "control_group_8",
+    ControlGroup9 => "control_group_9",
+}
+
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub enum InputBinding {
+    Key(Key),
+    Mouse(MouseButton),
+}
+
+impl FromStr for InputBinding {
+    type Err = String;
+
+    /// Mouse buttons are "MouseLeft", "MouseMiddle" and "MouseRight"; anything else is a key name
+    fn from_str(s: &str) -> Result<InputBinding, String> {
+        if s.starts_with("Mouse") {
+            s["Mouse".len()..].parse().map(InputBinding::Mouse)
+        } else {
+            s.parse().map(InputBinding::Key)
+        }
+    }
+}
+
+/// Maps input actions to the keys and mouse buttons that trigger them
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputBindings {
+    bindings: HashMap<InputAction, Vec<InputBinding>>,
+}
+
+impl Default for InputBindings {
+    /// The bindings from the original game
+    fn default() -> InputBindings {
+        use self::InputAction::*;
+        use self::InputBinding::{Key as K, Mouse as M};
+
+        let mut bindings = HashMap::new();
+        bindings.insert(ScrollNorth, vec![K(Key::Up)]);
+        bindings.insert(ScrollSouth, vec![K(Key::Down)]);
+        bindings.insert(ScrollWest, vec![K(Key::Left)]);
+        bindings.insert(ScrollEast, vec![K(Key::Right)]);
+        bindings.insert(Select, vec![M(MouseButton::Left)]);
+        bindings.insert(AddToSelection, vec![K(Key::ShiftLeft), K(Key::ShiftRight)]);
+        bindings.insert(Command, vec![M(MouseButton::Right)]);
+        bindings.insert(QueueCommand, vec![K(Key::CtrlLeft), K(Key::CtrlRight)]);
+        bindings.insert(DebugTileInfo, vec![K(Key::Space)]);
+        bindings.insert(BuildHouse, vec![K(Key::H)]);
+        bindings.insert(BuildGranary, vec![K(Key::G)]);
+        bindings.insert(BuildStoragePit, vec![K(Key::S)]);
+        bindings.insert(BuildBarracks, vec![K(Key::B)]);
+        bindings.insert(BuildDock, vec![K(Key::D)]);
+        bindings.insert(CancelBuild, vec![K(Key::Escape)]);
+        bindings.insert(TrainUnit1, vec![K(Key::Q)]);
+        bindings.insert(TrainUnit2, vec![K(Key::W)]);
+        bindings.insert(TrainUnit3, vec![K(Key::E)]);
+        bindings.insert(TrainUnit4, vec![K(Key::R)]);
+        bindings.insert(TrainUnit5, vec![K(Key::T)]);
+        bindings.insert(CancelTraining, vec![K(Key::Backspace)]);
+        bindings.insert(Research1, vec![K(Key::Y)]);
+        bindings.insert(Research2, vec![K(Key::U)]);
+        bindings.insert(Research3, vec![K(Key::I)]);
+        bindings.insert(Research4, vec![K(Key::O)]);
+        bindings.insert(Research5, vec![K(Key::P)]);
+        bindings.insert(CancelResearch, vec![K(Key::Delete)]);
+        bindings.insert(AssignControlGroup, vec![K(Key::CtrlLeft), K(Key::CtrlRight)]);
+        bindings.insert(ControlGroup1, vec![K(Key::Num1)]);
+        bindings.insert(ControlGroup2, vec![K(Key::Num2)]);
+        bindings.insert(ControlGroup3, vec![K(Key::Num3)]);
+        bindings.insert(ControlGroup4, vec![K(Key::Num4)]);
+        bindings.insert(ControlGroup5, vec![K(Key::Num5)]);
+        bindings.insert(ControlGroup6, vec![K(Key::Num6)]);
+        bindings.insert(ControlGroup7, vec![K(Key::Num7)]);
+        bindings.insert(ControlGroup8, vec![K(Key::Num8)]);
+        bindings.insert(ControlGroup9, vec![K(Key::Num9)]);
+        InputBindings { bindings: bindings }
+    }
+}
+
+impl InputBindings {
+    pub fn read_from_file<P: AsRef<Path>>(file_name: P) -> Result<InputBindings, String> {
+        let mut text = String::new();
+        try!(File::open(file_name.as_ref())
+            .and_then(|mut file| file.read_to_string(&mut text))
+            .map_err(|err| err.to_string()));
+        InputBindings::parse(&text)
+    }
+
+    /// Parses bindings from TOML where each action name is assigned a list of keys and buttons:
+    ///
+    /// ```toml
+    /// scroll_north = ["Up", "W"]
+    /// select = ["MouseLeft"]
+    /// ```
+    ///
+    /// Actions that aren't in the file keep their default bindings, and an empty list unbinds an action.
+    pub fn parse(text: &str) -> Result<InputBindings, String> {
+        let mut parser = toml::Parser::new(text);
+        let table = match parser.parse() {
+            Some(table) => table,
+            None => {
+                let error = &parser.errors[0];
+                let (line, column) = parser.to_linecol(error.lo);
+                return Err(format!("{} at line {}, column {}", error.desc, line + 1, column + 1));
+            }
+        };
+
+        let mut input_bindings = InputBindings::default();
+        for (name, value) in &table {
+            let action: InputAction = try!(name.parse());
+            let values = try!(value.as_slice()
+                .ok_or(format!("expected a list of keys for \"{}\"", name)));
+
+            let mut bindings = Vec::new();
+            for value in values {
+                let binding = try!(value.as_str()
+                    .ok_or(format!("expected a key name in the list for \"{}\"", name)));
+                bindings.push(try!(binding.parse()));
+            }
+            input_bindings.bindings.insert(action, bindings);
+        }
+        Ok(input_bindings)
+    }
+
+    pub fn bindings(&self, action: InputAction) -> &[InputBinding] {
+        self.bindings.get(&action).map(|b| &b[..]).unwrap_or(&[])
+    }
+
+    /// Works out each action's state from the states of the keys and buttons bound to it.
+    /// When several are bound, the action is down as long as any of them is down.
+    pub fn resolve(&self,
+                   key_states: &KeyStates<Key>,
+                   mouse_button_states: &KeyStates<MouseButton>)
+                   -> InputActionStates {
+        let mut action_states = HashMap::new();
+        for (action, bindings) in &self.bindings {
+            let state = bindings.iter()
+                .map(|binding| match *binding {
+                    InputBinding::Key(key) => key_states.key_state(key),
+                    InputBinding::Mouse(button) => mouse_button_states.key_state(button),
+                })
+                .max_by_key(|state| state_priority(*state))
+                .unwrap_or(KeyState::Up);
+            action_states.insert(*action, state);
+        }
+        InputActionStates::new(action_states)
+    }
+}
+
+fn state_priority(state: KeyState) -> u8 {
+    match state {
+        KeyState::Up => 0,
+        KeyState::TransitionUp => 1,
+        KeyState::TransitionDown => 2,
+        KeyState::Down => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use media::{Key, KeyState, KeyStates, MouseButton};
+    use std::collections::HashMap;
+    use super::{InputAction, InputBinding, InputBindings};
+
+    #[test]
+    fn test_default_bindings_file_matches_defaults() {
+        let bindings = InputBindings::parse(include_str!("../../../data/bindings.toml")).unwrap();
+        assert_eq!(InputBindings::default(), bindings);
+        for action in InputAction::all() {
+            assert!(!bindings.bindings(*action).is_empty(), "{:?} isn't bound", action);
+        }
+    }
+
+    #[test]
+    fn test_parse_bindings() {
+        let bindings = InputBindings::parse("scroll_north = [\"W\", \"Up\"]\nselect = [\"MouseMiddle\"]\n\
+                                             command = []\n")
+            .unwrap();
+        assert_eq!(&[InputBinding::Key(Key::W), InputBinding::Key(Key::Up)],
+                   bindings.bindings(InputAction::ScrollNorth));
+        assert_eq!(&[InputBinding::Mouse(MouseButton::Middle)],
+                   bindings.bindings(InputAction::Select));
+        assert!(bindings.bindings(InputAction::Command).is_empty());
+        assert_eq!(&[InputBinding::Key(Key::Down)], bindings.bindings(InputAction::ScrollSouth));
+
+        assert!(InputBindings::parse("scroll_up = [\"W\"]").is_err());
+        assert!(InputBindings::parse("select = [\"MouseSide\"]").is_err());
+        assert!(InputBindings::parse("select = \"MouseLeft\"").is_err());
+        assert!(InputBindings::parse("select = [").is_err());
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut keys = HashMap::new();
+        keys.insert(Key::ShiftLeft, KeyState::TransitionUp);
+        keys.insert(Key::ShiftRight, KeyState::Down);
+        keys.insert(Key::Up, KeyState::TransitionDown);
+        let mut buttons = HashMap::new();
+        buttons.insert(MouseButton::Left, KeyState::TransitionUp);
+
+        let states = InputBindings::default().resolve(&KeyStates::new(keys), &KeyStates::new(buttons));
+        assert_eq!(KeyState::Down, states.key_state(InputAction::AddToSelection));
+        assert_eq!(KeyState::TransitionDown, states.key_state(InputAction::ScrollNorth));
+        assert_eq!(KeyState::TransitionUp, states.key_state(InputAction::Select));
+        assert_eq!(KeyState::Up, states.key_state(InputAction::Command));
+    }
+}
@@ -0,0 +1,255 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use nalgebra::Vector2;
+use std::cmp;
+use types::{Color, Fixed, Rect, Vector3};
+
+/// Size of the minimap on screen (twice as wide as it is tall, like the view of the map)
+pub const MINIMAP_WIDTH: i32 = 200;
+pub const MINIMAP_HEIGHT: i32 = 100;
+
+/// Gap between the minimap and the bottom right corner of the screen
+const MINIMAP_MARGIN: i32 = 8;
+
+/// The terrain is drawn in square cells of this many pixels, colored by the tile under their centers
+const CELL_SIZE: i32 = 2;
+
+/// Keeps positions picked on the minimap from landing right on the edge of the map
+const EDGE_DISTANCE: Fixed = fixed_const!(0.5);
+
+/// A strip of same colored cells in a row of the minimap, relative to its top left corner
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ColorRun {
+    pub rect: Rect,
+    pub color: Color,
+}
+
+/// Lays the map out as a diamond (matching the isometric view) in the bottom right corner
+/// of the screen, and keeps track of the terrain colors drawn on it. Rows of cells are only
+/// rebuilt when the color of a tile in them changes.
+pub struct Minimap {
+    map_width: i32,
+    map_height: i32,
+
+    /// Tiles along either of the diamond's diagonals (the map's width plus its height)
+    span: i32,
+
+    columns: i32,
+    rows: i32,
+
+    /// Index of the tile under each cell's center; `None` for cells outside of the map
+    cell_tiles: Vec<Option<usize>>,
+    tile_colors: Vec<Option<Color>>,
+    runs: Vec<Vec<ColorRun>>,
+    dirty_rows: Vec<bool>,
+}
+
+impl Minimap {
+    pub fn new(map_width: i32, map_height: i32) -> Minimap {
+        let (columns, rows) = (MINIMAP_WIDTH / CELL_SIZE, MINIMAP_HEIGHT / CELL_SIZE);
+        let mut minimap = Minimap {
+            map_width: map_width,
+            map_height: map_height,
+            span: cmp::max(1, map_width + map_height),
+            columns: columns,
+            rows: rows,
+            cell_tiles: Vec::with_capacity((columns * rows) as usize),
+            tile_colors: vec![None; (map_width * map_height) as usize],
+            runs: vec![Vec::new(); rows as usize],
+            dirty_rows: vec![true; rows as usize],
+        };
+
+        for row in 0..rows {
+            for col in 0..columns {
+                let center = Vector2::new(col * CELL_SIZE + CELL_SIZE / 2, row * CELL_SIZE + CELL_SIZE / 2);
+                let (x, y) = minimap.unclamped_world(&center);
+                let tile = minimap.tile_index(y.into(), x.into());
+                let tile = if x >= 0.into() && y >= 0.into() { tile } else { None };
+                minimap.cell_tiles.push(tile);
+            }
+        }
+        minimap
+    }
+
+    /// Where the minimap is on a screen of the given size
+    pub fn screen_rect(&self, viewport_size: &Vector2<i32>) -> Rect {
+        Rect::of(viewport_size.x - MINIMAP_WIDTH - MINIMAP_MARGIN,
+                 viewport_size.y - MINIMAP_HEIGHT - MINIMAP_MARGIN,
+                 MINIMAP_WIDTH,
+                 MINIMAP_HEIGHT)
+    }
+
+    /// Whether the screen position (e.g., of the mouse) is over the minimap
+    pub fn contains(&self, viewport_size: &Vector2<i32>, screen_position: &Vector2<i32>) -> bool {
+        let rect = self.screen_rect(viewport_size);
+        screen_position.x >= rect.x && screen_position.x < rect.x + rect.w &&
+        screen_position.y >= rect.y && screen_position.y < rect.y + rect.h
+    }
+
+    /// World coordinates (at zero elevation) of the map under a screen position over the minimap
+    pub fn world_coord_at(&self, viewport_size: &Vector2<i32>, screen_position: &Vector2<i32>) -> Vector3 {
+        let rect = self.screen_rect(viewport_size);
+        self.to_world(&(*screen_position - Vector2::new(rect.x, rect.y)))
+    }
+
+    /// Converts world coordinates into a point relative to the minimap's top left corner
+    pub fn to_minimap(&self, world_coord: &Vector3) -> Vector2<i32> {
+        let span = Fixed::from(self.span);
+        let u = (world_coord.x + world_coord.y) * MINIMAP_WIDTH.into() / span;
+        let v = (world_coord.y - world_coord.x + self.map_width.into()) * MINIMAP_HEIGHT.into() / span;
+        Vector2::new(u.into(), v.into())
+    }
+
+    /// Converts a point relative to the minimap's top left corner into world coordinates
+    /// (at zero elevation), clamped to the map
+    pub fn to_world(&self, point: &Vector2<i32>) -> Vector3 {
+        let (x, y) = self.unclamped_world(point);
+        let clamp = |value: Fixed, size: i32| {
+            cmp::max(EDGE_DISTANCE, cmp::min(value, Fixed::from(size) - EDGE_DISTANCE))
+        };
+        Vector3::new(clamp(x, self.map_width), clamp(y, self.map_height), 0.into())
+    }
+
+    /// Sets the color the tile is drawn in, or `None` to leave it out
+    pub fn set_tile_color(&mut self, row: i32, col: i32, color: Option<Color>) {
+        let index = match self.tile_index(row, col) {
+            Some(index) => index,
+            None => return,
+        };
+        if self.tile_colors[index] == color {
+            return;
+        }
+        self.tile_colors[index] = color;
+
+        // The tile's diamond reaches from its right corner up to its left corner down
+        let top = (row - col - 1 + self.map_width) * MINIMAP_HEIGHT / self.span / CELL_SIZE;
+        let bottom = (row - col + 1 + self.map_width) * MINIMAP_HEIGHT / self.span / CELL_SIZE;
+        for cell_row in cmp::max(0, top)..cmp::min(self.rows, bottom + 1) {
+            self.dirty_rows[cell_row as usize] = true;
+        }
+    }
+
+    /// The terrain to draw, rebuilding the rows whose tiles changed color
+    pub fn color_runs(&mut self) -> &[Vec<ColorRun>] {
+        for row in 0..self.rows {
+            if self.dirty_rows[row as usize] {
+                self.runs[row as usize] = self.build_runs(row);
+                self.dirty_rows[row as usize] = false;
+            }
+        }
+        &self.runs
+    }
+
+    fn build_runs(&self, row: i32) -> Vec<ColorRun> {
+        let mut runs: Vec<ColorRun> = Vec::new();
+        for col in 0..self.columns {
+            let color = match self.cell_tiles[(row * self.columns + col) as usize] {
+                Some(tile) => self.tile_colors[tile],
+                None => None,
+            };
+            if let Some(color) = color {
+                let x = col * CELL_SIZE;
+                if let Some(run) = runs.last_mut() {
+                    if run.color == color && run.rect.x + run.rect.w == x {
+                        run.rect.w += CELL_SIZE;
+                        continue;
+                    }
+                }
+                runs.push(ColorRun {
+                    rect: Rect::of(x, row * CELL_SIZE, CELL_SIZE, CELL_SIZE),
+                    color: color,
+                });
+            }
+        }
+        runs
+    }
+
+    fn unclamped_world(&self, point: &Vector2<i32>) -> (Fixed, Fixed) {
+        let span = Fixed::from(self.span);
+        let sum = Fixed::from(point.x) * span / MINIMAP_WIDTH.into();
+        let difference = Fixed::from(point.y) * span / MINIMAP_HEIGHT.into() - self.map_width.into();
+        ((sum - difference) / 2.into(), (sum + difference) / 2.into())
+    }
+
+    fn tile_index(&self, row: i32, col: i32) -> Option<usize> {
+        if row >= 0 && row < self.map_height && col >= 0 && col < self.map_width {
+            Some((row * self.map_width + col) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector2;
+    use super::{MINIMAP_HEIGHT, MINIMAP_WIDTH, Minimap};
+    use types::{Color, Rect, Vector3};
+
+    #[test]
+    fn test_minimap_coords() {
+        let minimap = Minimap::new(100, 100);
+        let v3 = |x: i32, y: i32| Vector3::new(x.into(), y.into(), 0.into());
+
+        // The map's corners are the diamond's corners
+        assert_eq!(Vector2::new(0, MINIMAP_HEIGHT / 2), minimap.to_minimap(&v3(0, 0)));
+        assert_eq!(Vector2::new(MINIMAP_WIDTH / 2, 0), minimap.to_minimap(&v3(100, 0)));
+        assert_eq!(Vector2::new(MINIMAP_WIDTH / 2, MINIMAP_HEIGHT), minimap.to_minimap(&v3(0, 100)));
+        assert_eq!(Vector2::new(MINIMAP_WIDTH, MINIMAP_HEIGHT / 2), minimap.to_minimap(&v3(100, 100)));
+
+        assert_eq!(v3(50, 50), minimap.to_world(&Vector2::new(MINIMAP_WIDTH / 2, MINIMAP_HEIGHT / 2)));
+        assert_eq!(v3(40, 20), minimap.to_world(&minimap.to_minimap(&v3(40, 20))));
+
+        // Points outside of the diamond are clamped to the map
+        let corner = minimap.to_world(&Vector2::new(0, 0));
+        assert_eq!(Vector3::new(50.into(), 0.5.into(), 0.into()), corner);
+
+        let viewport_size = Vector2::new(800, 600);
+        assert!(minimap.contains(&viewport_size, &Vector2::new(700, 550)));
+        assert!(!minimap.contains(&viewport_size, &Vector2::new(400, 300)));
+        assert_eq!(v3(50, 50), minimap.world_coord_at(&viewport_size, &Vector2::new(692, 542)));
+    }
+
+    #[test]
+    fn test_minimap_color_runs() {
+        let mut minimap = Minimap::new(10, 10);
+        assert!(minimap.color_runs().iter().all(|runs| runs.is_empty()));
+
+        let red = Color::rgb(255, 0, 0);
+        for row in 0..10 {
+            for col in 0..10 {
+                minimap.set_tile_color(row, col, Some(red));
+            }
+        }
+
+        // The middle row spans the diamond in one run; the centers of its outermost cells
+        // are just off the map
+        let middle = (MINIMAP_HEIGHT / 2 / 2) as usize;
+        assert_eq!(vec![Rect::of(2, MINIMAP_HEIGHT / 2, MINIMAP_WIDTH - 4, 2)],
+                   minimap.color_runs()[middle].iter().map(|run| run.rect).collect::<Vec<Rect>>());
+
+        // Changing a tile splits the runs over it
+        minimap.set_tile_color(5, 5, Some(Color::rgb(0, 0, 255)));
+        assert_eq!(3, minimap.color_runs()[middle].len());
+        assert_eq!(1, minimap.color_runs()[0].len());
+    }
+}
@@ -20,21 +20,39 @@
 // SOFTWARE.
 
 mod action_batcher;
+mod build_placement;
 mod input;
+mod input_bindings;
+mod minimap;
 pub mod path_finder;
 mod occupied_tiles;
+mod player_research;
+mod player_resources;
 mod players;
+mod random;
 mod render;
+mod selection_box;
+mod sound_queue;
 pub mod terrain;
 mod view_projector;
 mod viewport;
+mod visibility;
 
 pub use self::action_batcher::ActionBatcher;
+pub use self::build_placement::BuildPlacement;
 pub use self::input::{KeyboardKeyStates, MouseState};
+pub use self::input_bindings::{InputAction, InputActionStates, InputBinding, InputBindings};
+pub use self::minimap::Minimap;
 pub use self::occupied_tiles::OccupiedTiles;
 pub use self::path_finder::PathFinder;
+pub use self::player_research::PlayerResearch;
+pub use self::player_resources::{PlayerResources, Stockpile};
 pub use self::players::{Player, Players};
+pub use self::random::Random;
 pub use self::render::RenderCommands;
+pub use self::selection_box::SelectionBox;
+pub use self::sound_queue::{SoundEvent, SoundQueue};
 pub use self::terrain::{Terrain, Tile};
 pub use self::view_projector::ViewProjector;
 pub use self::viewport::Viewport;
+pub use self::visibility::{RememberedBuilding, TileVisibility, Visibility};
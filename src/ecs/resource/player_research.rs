@@ -0,0 +1,295 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat::{self, ResearchEffect, ResearchEffectValue, UnitAttributeId};
+use identifier::{AgeId, PlayerId, ResearchId, UnitId};
+use std::collections::{HashMap, HashSet};
+use types::Fixed;
+
+/// The research each player has completed (including research applied by finishing a building),
+/// and the effects of that research: which units are enabled or upgraded, and how unit stats changed
+pub struct PlayerResearch {
+    completed: HashMap<PlayerId, HashSet<ResearchId>>,
+    disabled: HashMap<PlayerId, HashSet<ResearchId>>,
+
+    /// The effects of the groups that have been applied to each player, in the order they were
+    /// applied. They're collected as research completes rather than every time a unit's stats are
+    /// looked up.
+    effects: HashMap<PlayerId, Vec<ResearchEffect>>,
+
+    /// Research that was completed but hasn't had its effects applied yet
+    pending: Vec<(PlayerId, ResearchId)>,
+}
+
+impl PlayerResearch {
+    pub fn new() -> PlayerResearch {
+        PlayerResearch {
+            completed: HashMap::new(),
+            disabled: HashMap::new(),
+            effects: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// Marks the research as complete; its effects are applied by the ResearchSystem
+    pub fn complete(&mut self, player_id: PlayerId, research_id: ResearchId) {
+        if self.completed.entry(player_id).or_insert(HashSet::new()).insert(research_id) {
+            self.pending.push((player_id, research_id));
+        }
+    }
+
+    pub fn is_complete(&self, player_id: PlayerId, research_id: ResearchId) -> bool {
+        self.completed.get(&player_id).map(|completed| completed.contains(&research_id)).unwrap_or(false)
+    }
+
+    /// Removes and returns the research that was completed since the last time this was called
+    pub fn take_pending(&mut self) -> Vec<(PlayerId, ResearchId)> {
+        self.pending.drain(..).collect()
+    }
+
+    /// Whether the player can start the research: it hasn't been completed or disabled,
+    /// and enough of the research it requires has been completed (any of them count)
+    pub fn is_available(&self, player_id: PlayerId, research: &dat::Research) -> bool {
+        let disabled = self.disabled
+            .get(&player_id)
+            .map(|disabled| disabled.contains(&research.id))
+            .unwrap_or(false);
+        let completed_count = research.required_techs
+            .iter()
+            .filter(|&&required| required >= 0 && self.is_complete(player_id, (required as usize).into()))
+            .count();
+        !disabled && !self.is_complete(player_id, research.id) &&
+        completed_count >= research.required_tech_count
+    }
+
+    /// Applies an effect group to the player. Effects that change units are kept to be looked up
+    /// on demand, so only research gained or disabled by the group needs to be handled here.
+    pub fn apply(&mut self, empires: &dat::EmpiresDb, player_id: PlayerId, age_id: AgeId) {
+        let effects = &empires.age(age_id).effects;
+        self.effects.entry(player_id).or_insert(Vec::new()).extend(effects.iter().cloned());
+        for effect in effects {
+            match *effect {
+                ResearchEffect::GainResearch { research_id } => self.complete(player_id, research_id),
+                ResearchEffect::DisableResearch { research_id } => {
+                    self.disabled.entry(player_id).or_insert(HashSet::new()).insert(research_id);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Whether the player is able to train or build the unit
+    pub fn is_unit_enabled(&self, player_id: PlayerId, unit_info: &dat::Unit) -> bool {
+        let mut enabled = unit_info.enabled;
+        for effect in self.effects(player_id) {
+            if let ResearchEffect::SetUnitEnabled { target_unit_id, enabled: set_to } = *effect {
+                if target_unit_id == Some(unit_info.id) {
+                    enabled = set_to;
+                }
+            }
+        }
+        enabled
+    }
+
+    /// The unit that the player trains or builds in place of the given one (e.g., a bronze age house)
+    pub fn upgraded_unit(&self, player_id: PlayerId, unit_id: UnitId) -> UnitId {
+        let mut unit_id = unit_id;
+        for effect in self.effects(player_id) {
+            if let ResearchEffect::UpgradeUnit { source_unit_id, target_unit_id } = *effect {
+                if source_unit_id == unit_id {
+                    unit_id = target_unit_id;
+                }
+            }
+        }
+        unit_id
+    }
+
+    /// Applies the player's research to one of the unit's attributes, starting from its base value
+    pub fn attribute(&self,
+                     player_id: PlayerId,
+                     unit_info: &dat::Unit,
+                     attribute_id: UnitAttributeId,
+                     base: Fixed)
+                     -> Fixed {
+        let mut value = base;
+        for effect in self.attribute_effects(player_id, unit_info, attribute_id) {
+            value = match effect {
+                ResearchEffectValue::SetTo(amount) => Fixed::from(amount),
+                ResearchEffectValue::Add(amount) => value + Fixed::from(amount),
+                ResearchEffectValue::MultiplyBy(amount) => value * Fixed::from(amount),
+            };
+        }
+        value
+    }
+
+    /// The unit's hit points after the player's research has been applied
+    pub fn hit_points(&self, player_id: PlayerId, unit_info: &dat::Unit) -> i32 {
+        let base = Fixed::from(unit_info.hit_points);
+        i32::from(self.attribute(player_id, unit_info, UnitAttributeId::HitPoints, base))
+    }
+
+    /// The unit's attacks (class, amount) after the player's research has been applied
+    pub fn attacks(&self, player_id: PlayerId, unit_info: &dat::Unit) -> Vec<(i16, i16)> {
+        match unit_info.battle_params {
+            Some(ref params) => {
+                let attribute_id = UnitAttributeId::AttackStrength;
+                self.class_values(player_id, unit_info, attribute_id, &params.attacks)
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// The unit's armors (class, amount) after the player's research has been applied
+    pub fn armors(&self, player_id: PlayerId, unit_info: &dat::Unit) -> Vec<(i16, i16)> {
+        match unit_info.battle_params {
+            Some(ref params) => {
+                let attribute_id = UnitAttributeId::ArmorStrength;
+                self.class_values(player_id, unit_info, attribute_id, &params.armors)
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn class_values(&self,
+                    player_id: PlayerId,
+                    unit_info: &dat::Unit,
+                    attribute_id: UnitAttributeId,
+                    base: &[(i16, i16)])
+                    -> Vec<(i16, i16)> {
+        apply_class_effects(base, &self.attribute_effects(player_id, unit_info, attribute_id))
+    }
+
+    fn effects(&self, player_id: PlayerId) -> &[ResearchEffect] {
+        match self.effects.get(&player_id) {
+            Some(effects) => &effects[..],
+            None => &[],
+        }
+    }
+
+    /// The player's effects that change the given attribute of the unit, in order
+    fn attribute_effects(&self,
+                         player_id: PlayerId,
+                         unit_info: &dat::Unit,
+                         attribute_id: UnitAttributeId)
+                         -> Vec<ResearchEffectValue> {
+        let mut result = Vec::new();
+        for effect in self.effects(player_id) {
+            if let ResearchEffect::UnitAttribute { target_unit_id,
+                                                   target_unit_class_id,
+                                                   attribute_id: effect_attribute_id,
+                                                   effect: value } = *effect {
+                // Effects target either a specific unit or every unit of a class
+                let targeted = match (target_unit_id, target_unit_class_id) {
+                    (Some(unit_id), _) => unit_id == unit_info.id,
+                    (None, Some(class_id)) => *class_id as i16 == unit_info.class_id,
+                    (None, None) => false,
+                };
+                if targeted && effect_attribute_id == attribute_id {
+                    result.push(value);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Attack and armor effects pack the class they change into the upper byte of their value,
+/// and the amount into the lower byte
+fn class_amount(effect: ResearchEffectValue) -> (i16, i16) {
+    let value = match effect {
+        ResearchEffectValue::SetTo(value) |
+        ResearchEffectValue::Add(value) |
+        ResearchEffectValue::MultiplyBy(value) => value as i32,
+    };
+    ((value >> 8) as i16, (value & 0xff) as i16)
+}
+
+fn apply_class_effects(base: &[(i16, i16)], effects: &[ResearchEffectValue]) -> Vec<(i16, i16)> {
+    let mut values = base.to_vec();
+    for &effect in effects {
+        let (class, amount) = class_amount(effect);
+        let index = match values.iter().position(|&(value_class, _)| value_class == class) {
+            Some(index) => index,
+            None => {
+                values.push((class, 0));
+                values.len() - 1
+            }
+        };
+        values[index].1 = match effect {
+            ResearchEffectValue::SetTo(_) => amount,
+            ResearchEffectValue::Add(_) => values[index].1 + amount,
+            ResearchEffectValue::MultiplyBy(_) => values[index].1 * amount,
+        };
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PlayerResearch, apply_class_effects, class_amount};
+    use dat::{self, ResearchEffectValue};
+
+    #[test]
+    fn test_research_availability() {
+        let mut player_research = PlayerResearch::new();
+        let mut research: dat::Research = Default::default();
+        research.id = 5.into();
+        research.required_techs = vec![3, -1];
+        research.required_tech_count = 1;
+        assert!(!player_research.is_available(1.into(), &research));
+
+        player_research.complete(1.into(), 3.into());
+        assert!(player_research.is_available(1.into(), &research));
+        assert!(!player_research.is_available(2.into(), &research));
+
+        player_research.complete(1.into(), 5.into());
+        player_research.complete(1.into(), 5.into());
+        assert!(!player_research.is_available(1.into(), &research));
+        assert_eq!(vec![(1.into(), 3.into()), (1.into(), 5.into())], player_research.take_pending());
+        assert!(player_research.take_pending().is_empty());
+    }
+
+    #[test]
+    fn test_research_requiring_any_of() {
+        let mut player_research = PlayerResearch::new();
+        let mut research: dat::Research = Default::default();
+        research.id = 9.into();
+        research.required_techs = vec![3, 4, 5, -1];
+        research.required_tech_count = 2;
+
+        // Any two of the three required techs unlock it
+        player_research.complete(1.into(), 5.into());
+        assert!(!player_research.is_available(1.into(), &research));
+        player_research.complete(1.into(), 3.into());
+        assert!(player_research.is_available(1.into(), &research));
+    }
+
+    #[test]
+    fn test_class_effects() {
+        assert_eq!((4, 2), class_amount(ResearchEffectValue::Add(4.0 * 256.0 + 2.0)));
+
+        let base = [(4, 3), (3, 1)];
+        let effects = [ResearchEffectValue::Add(4.0 * 256.0 + 2.0),
+                       ResearchEffectValue::Add(6.0 * 256.0 + 1.0),
+                       ResearchEffectValue::SetTo(3.0 * 256.0 + 2.0)];
+        assert_eq!(vec![(4, 5), (3, 2), (6, 1)], apply_class_effects(&base, &effects));
+    }
+}
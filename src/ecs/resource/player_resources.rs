@@ -0,0 +1,179 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat::ResourceType;
+use identifier::PlayerId;
+use scn::Scenario;
+use std::collections::HashMap;
+use types::Fixed;
+
+/// Amount of each resource a player has
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stockpile {
+    pub food: Fixed,
+    pub wood: Fixed,
+    pub stone: Fixed,
+    pub gold: Fixed,
+}
+
+impl Stockpile {
+    pub fn new() -> Stockpile {
+        Stockpile {
+            food: 0.into(),
+            wood: 0.into(),
+            stone: 0.into(),
+            gold: 0.into(),
+        }
+    }
+
+    pub fn get(&self, resource_type: ResourceType) -> Fixed {
+        match resource_type {
+            ResourceType::Food => self.food,
+            ResourceType::Wood => self.wood,
+            ResourceType::Stone => self.stone,
+            ResourceType::Gold => self.gold,
+            ResourceType::Unknown(_) => 0.into(),
+        }
+    }
+
+    /// Adds to the given resource; resources that can't be stockpiled are ignored
+    pub fn add(&mut self, resource_type: ResourceType, amount: Fixed) {
+        match resource_type {
+            ResourceType::Food => self.food += amount,
+            ResourceType::Wood => self.wood += amount,
+            ResourceType::Stone => self.stone += amount,
+            ResourceType::Gold => self.gold += amount,
+            ResourceType::Unknown(_) => {}
+        }
+    }
+}
+
+/// Every player's stockpile of food, wood, stone and gold
+pub struct PlayerResources {
+    stockpiles: HashMap<PlayerId, Stockpile>,
+}
+
+impl PlayerResources {
+    pub fn new() -> PlayerResources {
+        PlayerResources { stockpiles: HashMap::new() }
+    }
+
+    pub fn from_scenario(scenario: &Scenario) -> PlayerResources {
+        let mut player_resources = PlayerResources::new();
+        for player_id in scenario.player_ids() {
+            if let Some(starting) = scenario.player_resources(player_id) {
+                let stockpile = player_resources.stockpile_mut(player_id);
+                stockpile.food = starting.food.into();
+                stockpile.wood = starting.wood.into();
+                stockpile.stone = starting.stone.into();
+                stockpile.gold = starting.gold.into();
+            }
+        }
+        player_resources
+    }
+
+    pub fn stockpile(&self, player_id: PlayerId) -> Stockpile {
+        self.stockpiles.get(&player_id).cloned().unwrap_or(Stockpile::new())
+    }
+
+    pub fn amount(&self, player_id: PlayerId, resource_type: ResourceType) -> Fixed {
+        self.stockpile(player_id).get(resource_type)
+    }
+
+    pub fn add(&mut self, player_id: PlayerId, resource_type: ResourceType, amount: Fixed) {
+        self.stockpile_mut(player_id).add(resource_type, amount);
+    }
+
+    /// Whether the player has enough of every resource in the cost
+    pub fn can_afford(&self, player_id: PlayerId, cost: &[(ResourceType, Fixed)]) -> bool {
+        let stockpile = self.stockpile(player_id);
+        cost.iter().all(|&(resource_type, amount)| stockpile.get(resource_type) >= amount)
+    }
+
+    /// Takes the cost out of the player's stockpile; returns false (and takes nothing)
+    /// if the player can't afford it
+    pub fn spend(&mut self, player_id: PlayerId, cost: &[(ResourceType, Fixed)]) -> bool {
+        if !self.can_afford(player_id, cost) {
+            return false;
+        }
+        let stockpile = self.stockpile_mut(player_id);
+        for &(resource_type, amount) in cost {
+            stockpile.add(resource_type, -amount);
+        }
+        true
+    }
+
+    /// Gives back what was spent (e.g., when training is cancelled)
+    pub fn refund(&mut self, player_id: PlayerId, cost: &[(ResourceType, Fixed)]) {
+        let stockpile = self.stockpile_mut(player_id);
+        for &(resource_type, amount) in cost {
+            stockpile.add(resource_type, amount);
+        }
+    }
+
+    fn stockpile_mut(&mut self, player_id: PlayerId) -> &mut Stockpile {
+        self.stockpiles.entry(player_id).or_insert(Stockpile::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dat::ResourceType;
+    use super::PlayerResources;
+    use types::Fixed;
+
+    #[test]
+    fn test_add_resources() {
+        let mut resources = PlayerResources::new();
+        assert_eq!(Fixed::from(0), resources.amount(1.into(), ResourceType::Wood));
+
+        resources.add(1.into(), ResourceType::Wood, 10.into());
+        resources.add(1.into(), ResourceType::Wood, 5.into());
+        resources.add(2.into(), ResourceType::Gold, 3.into());
+        resources.add(2.into(), ResourceType::Unknown(4), 3.into());
+
+        assert_eq!(Fixed::from(15), resources.amount(1.into(), ResourceType::Wood));
+        assert_eq!(Fixed::from(0), resources.amount(1.into(), ResourceType::Gold));
+        assert_eq!(Fixed::from(3), resources.amount(2.into(), ResourceType::Gold));
+        assert_eq!(Fixed::from(0), resources.amount(2.into(), ResourceType::Unknown(4)));
+    }
+
+    #[test]
+    fn test_spend_resources() {
+        let mut resources = PlayerResources::new();
+        resources.add(1.into(), ResourceType::Wood, 30.into());
+        resources.add(1.into(), ResourceType::Food, 10.into());
+
+        let cost = [(ResourceType::Wood, Fixed::from(30)), (ResourceType::Food, Fixed::from(20))];
+        assert!(!resources.can_afford(1.into(), &cost));
+        assert!(!resources.spend(1.into(), &cost));
+        assert_eq!(Fixed::from(30), resources.amount(1.into(), ResourceType::Wood));
+
+        let cost = [(ResourceType::Wood, Fixed::from(30))];
+        assert!(resources.spend(1.into(), &cost));
+        assert_eq!(Fixed::from(0), resources.amount(1.into(), ResourceType::Wood));
+        assert_eq!(Fixed::from(10), resources.amount(1.into(), ResourceType::Food));
+        assert!(!resources.can_afford(2.into(), &cost));
+
+        resources.refund(1.into(), &cost);
+        assert_eq!(Fixed::from(30), resources.amount(1.into(), ResourceType::Wood));
+    }
+}
@@ -48,6 +48,10 @@ impl Player {
 pub struct Players {
     local_player_id: PlayerId,
     players: HashMap<PlayerId, Player>,
+
+    /// Percentage of the map a player has to explore to win (zero if exploring isn't a condition)
+    required_exploration_percent: u32,
+    winner: Option<PlayerId>,
 }
 
 impl Players {
@@ -55,6 +59,8 @@ impl Players {
         Players {
             local_player_id: 0.into(),
             players: HashMap::new(),
+            required_exploration_percent: 0,
+            winner: None,
         }
     }
 
@@ -67,6 +73,9 @@ impl Players {
             let local = player_id == local_player_id;
             players.add_player(Player::new(name, player_id, color_id, civ_id), local);
         }
+        players.required_exploration_percent = scenario.player_data
+            .victory_conditions
+            .required_exploration_percent;
         players
     }
 
@@ -81,4 +90,20 @@ impl Players {
         let local_player_id = self.local_player_id;
         &self.players[&local_player_id]
     }
+
+    pub fn required_exploration_percent(&self) -> u32 {
+        self.required_exploration_percent
+    }
+
+    /// The player who won the scenario, if anyone has yet
+    pub fn winner<'a>(&'a self) -> Option<&'a Player> {
+        self.winner.map(|player_id| &self.players[&player_id])
+    }
+
+    /// Ends the scenario in the player's victory; the first player to win keeps it
+    pub fn declare_winner(&mut self, player_id: PlayerId) {
+        if self.winner.is_none() {
+            self.winner = Some(player_id);
+        }
+    }
 }
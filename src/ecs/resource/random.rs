@@ -0,0 +1,69 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+/// Random numbers for the simulation. Every system draws from this one generator in the
+/// same order on every machine, so the results stay in sync across the network.
+pub struct Random {
+    state: u32,
+}
+
+impl Random {
+    pub fn new(seed: u32) -> Random {
+        // Xorshift gets stuck at zero
+        Random { state: if seed == 0 { 0x2545f491 } else { seed } }
+    }
+
+    /// Xorshift
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// Returns true with the given percent chance
+    pub fn roll_percent(&mut self, percent: i32) -> bool {
+        (self.next_u32() % 100) < (if percent < 0 { 0 } else { percent as u32 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Random;
+
+    #[test]
+    fn test_roll_percent() {
+        let mut random = Random::new(12345);
+        for _ in 0..100 {
+            assert!(!random.roll_percent(0));
+            assert!(random.roll_percent(100));
+        }
+
+        // The same seed always produces the same sequence
+        let (mut a, mut b) = (Random::new(7), Random::new(7));
+        for _ in 0..10 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+        assert!(Random::new(0).next_u32() != 0);
+    }
+}
@@ -20,7 +20,7 @@
 // SOFTWARE.
 
 use media::Renderer;
-use resource::{RenderCommand, ShapeManager};
+use resource::{self, FontManager, RenderCommand, ShapeManager};
 
 pub struct RenderCommands {
     commands: Vec<RenderCommand>,
@@ -35,8 +35,12 @@ impl RenderCommands {
         self.commands.push(render_command);
     }
 
-    pub fn execute(&mut self, renderer: &mut Renderer, shape_manager: &mut ShapeManager) {
-        RenderCommand::render_all(renderer, shape_manager, &mut self.commands);
+    pub fn execute(&mut self,
+                   renderer: &mut Renderer,
+                   shape_manager: &mut ShapeManager,
+                   font_manager: &mut FontManager)
+                   -> resource::Result<()> {
+        RenderCommand::render_all(renderer, shape_manager, font_manager, &mut self.commands)
     }
 
     pub fn clear_rendered(&mut self) {
@@ -0,0 +1,109 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use nalgebra::Vector2;
+use std::cmp;
+use types::Rect;
+
+/// How far the mouse has to move with the select button held before it's a drag instead of a click
+const MIN_DRAG_DISTANCE: i32 = 4;
+
+/// The box being dragged out with the select button to select the units in it. Its corners are
+/// in the screen coordinates the map is drawn in (including the viewport's position), so the
+/// box stays over the same part of the map if the view scrolls during the drag.
+pub struct SelectionBox {
+    start: Option<Vector2<i32>>,
+    end: Vector2<i32>,
+}
+
+impl SelectionBox {
+    pub fn new() -> SelectionBox {
+        SelectionBox {
+            start: None,
+            end: Vector2::new(0, 0),
+        }
+    }
+
+    pub fn start(&mut self, position: Vector2<i32>) {
+        self.start = Some(position);
+        self.end = position;
+    }
+
+    pub fn drag_to(&mut self, position: Vector2<i32>) {
+        if self.start.is_some() {
+            self.end = position;
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.start = None;
+    }
+
+    /// Ends the drag, returning the box if the mouse moved far enough for it to not be a click
+    pub fn finish(&mut self) -> Option<Rect> {
+        let rect = self.rect();
+        self.start = None;
+        rect
+    }
+
+    /// The box being dragged out, once the mouse has moved far enough for it to not be a click
+    pub fn rect(&self) -> Option<Rect> {
+        self.start.and_then(|start| {
+            let (width, height) = ((self.end.x - start.x).abs(), (self.end.y - start.y).abs());
+            if width < MIN_DRAG_DISTANCE && height < MIN_DRAG_DISTANCE {
+                None
+            } else {
+                Some(Rect::of(cmp::min(start.x, self.end.x),
+                              cmp::min(start.y, self.end.y),
+                              width,
+                              height))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector2;
+    use super::SelectionBox;
+    use types::Rect;
+
+    #[test]
+    fn test_selection_box() {
+        let mut selection_box = SelectionBox::new();
+        assert_eq!(None, selection_box.rect());
+
+        // Barely moving the mouse is still a click
+        selection_box.start(Vector2::new(100, 100));
+        selection_box.drag_to(Vector2::new(102, 97));
+        assert_eq!(None, selection_box.rect());
+
+        // Dragging up and to the left puts the box's corner at the end of the drag
+        selection_box.drag_to(Vector2::new(40, 70));
+        assert_eq!(Some(Rect::of(40, 70, 60, 30)), selection_box.rect());
+        assert_eq!(Some(Rect::of(40, 70, 60, 30)), selection_box.finish());
+        assert_eq!(None, selection_box.rect());
+
+        // Nothing is dragged until the drag starts again
+        selection_box.drag_to(Vector2::new(0, 0));
+        assert_eq!(None, selection_box.finish());
+    }
+}
@@ -0,0 +1,54 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use identifier::SoundGroupId;
+use std::mem;
+use types::Vector3;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SoundEvent {
+    pub sound_group_id: SoundGroupId,
+
+    /// Where in the world the sound comes from; sounds without a position are heard everywhere
+    pub position: Option<Vector3>,
+}
+
+/// Sounds that systems want played this update. The game state plays them once the systems are done.
+pub struct SoundQueue {
+    sounds: Vec<SoundEvent>,
+}
+
+impl SoundQueue {
+    pub fn new() -> SoundQueue {
+        SoundQueue { sounds: Vec::new() }
+    }
+
+    pub fn play(&mut self, sound_group_id: SoundGroupId, position: Option<Vector3>) {
+        self.sounds.push(SoundEvent {
+            sound_group_id: sound_group_id,
+            position: position,
+        });
+    }
+
+    pub fn consume_sounds(&mut self) -> Vec<SoundEvent> {
+        mem::replace(&mut self.sounds, Vec::new())
+    }
+}
@@ -51,6 +51,12 @@ impl Viewport {
         self.current_top_left = top_left;
     }
 
+    /// Moves straight to the position without interpolating from the old one
+    pub fn jump_to(&mut self, top_left: Vector2<Fixed>) {
+        self.last_top_left = top_left;
+        self.current_top_left = top_left;
+    }
+
     pub fn lerped_top_left(&self, lerp: Fixed) -> Vector2<i32> {
         let lerped = self.current_top_left + (self.current_top_left - self.last_top_left) * lerp;
         Vector2::new(lerped.x.into(), lerped.y.into())
@@ -0,0 +1,265 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use identifier::{GraphicId, PlayerColorId, PlayerId};
+use specs;
+use std::cmp;
+use std::collections::HashMap;
+use types::Vector3;
+
+/// What a player can see of a tile
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TileVisibility {
+    /// The player has never seen the tile; it's drawn black
+    Unexplored,
+
+    /// The player has seen the tile but none of their units can see it now; it's drawn darkened,
+    /// with buildings shown as they were last seen
+    Explored,
+
+    /// At least one of the player's units can see the tile
+    Visible,
+}
+
+/// A building as a player last saw it, which is drawn in its place while it's out of sight
+#[derive(Clone, Debug)]
+pub struct RememberedBuilding {
+    pub position: Vector3,
+    pub player_color_id: PlayerColorId,
+    pub graphic_id: GraphicId,
+    pub frame: u16,
+    pub flip_horizontal: bool,
+}
+
+struct PlayerVisibility {
+    /// How many of the player's units can see each tile
+    sight_counts: Vec<u16>,
+    explored: Vec<bool>,
+    explored_count: usize,
+    remembered: HashMap<specs::Entity, RememberedBuilding>,
+}
+
+impl PlayerVisibility {
+    fn new(tile_count: usize) -> PlayerVisibility {
+        PlayerVisibility {
+            sight_counts: vec![0; tile_count],
+            explored: vec![false; tile_count],
+            explored_count: 0,
+            remembered: HashMap::new(),
+        }
+    }
+}
+
+/// Each player's fog of war: which tiles they've explored and which are in sight of their units
+pub struct Visibility {
+    width: i32,
+    height: i32,
+    players: HashMap<PlayerId, PlayerVisibility>,
+}
+
+impl Visibility {
+    pub fn new(width: i32, height: i32) -> Visibility {
+        Visibility {
+            width: width,
+            height: height,
+            players: HashMap::new(),
+        }
+    }
+
+    /// Adds a unit's sight of the tiles within the radius of the given tile, exploring them
+    pub fn reveal(&mut self, player_id: PlayerId, row: i32, col: i32, radius: i32) {
+        let tiles = self.tiles_in_sight(row, col, radius);
+        let tile_count = (self.width * self.height) as usize;
+        let player = self.players.entry(player_id).or_insert_with(|| PlayerVisibility::new(tile_count));
+        for index in tiles {
+            player.sight_counts[index] += 1;
+            if !player.explored[index] {
+                player.explored[index] = true;
+                player.explored_count += 1;
+            }
+        }
+    }
+
+    /// Removes a unit's sight that was added with `reveal`; the tiles stay explored
+    pub fn conceal(&mut self, player_id: PlayerId, row: i32, col: i32, radius: i32) {
+        let tiles = self.tiles_in_sight(row, col, radius);
+        if let Some(player) = self.players.get_mut(&player_id) {
+            for index in tiles {
+                if player.sight_counts[index] > 0 {
+                    player.sight_counts[index] -= 1;
+                }
+            }
+        }
+    }
+
+    pub fn visibility(&self, player_id: PlayerId, row: i32, col: i32) -> TileVisibility {
+        match (self.players.get(&player_id), self.tile_index(row, col)) {
+            (Some(player), Some(index)) => {
+                if player.sight_counts[index] > 0 {
+                    TileVisibility::Visible
+                } else if player.explored[index] {
+                    TileVisibility::Explored
+                } else {
+                    TileVisibility::Unexplored
+                }
+            }
+            _ => TileVisibility::Unexplored,
+        }
+    }
+
+    /// Visibility of the tile the world position is in
+    pub fn visibility_at(&self, player_id: PlayerId, position: &Vector3) -> TileVisibility {
+        self.visibility(player_id, position.y.into(), position.x.into())
+    }
+
+    /// How much of the map the player has explored, from 0 to 100 (for exploration victories)
+    pub fn explored_percent(&self, player_id: PlayerId) -> u32 {
+        let tile_count = (self.width * self.height) as usize;
+        match self.players.get(&player_id) {
+            Some(player) if tile_count > 0 => (player.explored_count * 100 / tile_count) as u32,
+            _ => 0,
+        }
+    }
+
+    /// Remembers how a building looks to the player, replacing what they saw of it before
+    pub fn remember(&mut self, player_id: PlayerId, entity: specs::Entity, building: RememberedBuilding) {
+        if let Some(player) = self.players.get_mut(&player_id) {
+            player.remembered.insert(entity, building);
+        }
+    }
+
+    /// Forgets the remembered buildings that are back in sight of the player, since
+    /// whatever is there now (if anything) is drawn instead
+    pub fn forget_visible(&mut self, player_id: PlayerId) {
+        let visible: Vec<specs::Entity> = match self.players.get(&player_id) {
+            Some(player) => {
+                player.remembered
+                    .iter()
+                    .filter(|&(_, building)| {
+                        self.visibility_at(player_id, &building.position) == TileVisibility::Visible
+                    })
+                    .map(|(entity, _)| *entity)
+                    .collect()
+            }
+            None => return,
+        };
+        if let Some(player) = self.players.get_mut(&player_id) {
+            for entity in visible {
+                player.remembered.remove(&entity);
+            }
+        }
+    }
+
+    /// The remembered buildings that are out of the player's sight; buildings in sight are
+    /// drawn as they are instead, so they aren't included
+    pub fn remembered_buildings(&self, player_id: PlayerId) -> Vec<&RememberedBuilding> {
+        match self.players.get(&player_id) {
+            Some(player) => {
+                player.remembered
+                    .values()
+                    .filter(|building| {
+                        self.visibility_at(player_id, &building.position) != TileVisibility::Visible
+                    })
+                    .collect()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn tile_index(&self, row: i32, col: i32) -> Option<usize> {
+        if row >= 0 && row < self.height && col >= 0 && col < self.width {
+            Some((row * self.width + col) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Indices of the tiles within the radius of the given tile (that are on the map)
+    fn tiles_in_sight(&self, row: i32, col: i32, radius: i32) -> Vec<usize> {
+        let mut tiles = Vec::new();
+        for tile_row in cmp::max(0, row - radius)..cmp::min(self.height, row + radius + 1) {
+            for tile_col in cmp::max(0, col - radius)..cmp::min(self.width, col + radius + 1) {
+                let (row_offset, col_offset) = (tile_row - row, tile_col - col);
+                // A little extra radius rounds off the corners of the sight circle
+                if row_offset * row_offset + col_offset * col_offset <= radius * radius + radius {
+                    tiles.push((tile_row * self.width + tile_col) as usize);
+                }
+            }
+        }
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use specs;
+    use super::{RememberedBuilding, TileVisibility, Visibility};
+    use types::Vector3;
+
+    #[test]
+    fn test_visibility() {
+        let mut visibility = Visibility::new(10, 10);
+        assert_eq!(TileVisibility::Unexplored, visibility.visibility(1.into(), 5, 5));
+
+        visibility.reveal(1.into(), 5, 5, 1);
+        visibility.reveal(1.into(), 5, 6, 1);
+        assert_eq!(TileVisibility::Visible, visibility.visibility(1.into(), 4, 5));
+        assert_eq!(TileVisibility::Unexplored, visibility.visibility(1.into(), 3, 5));
+        assert_eq!(TileVisibility::Unexplored, visibility.visibility(2.into(), 5, 5));
+        assert_eq!(TileVisibility::Unexplored, visibility.visibility(1.into(), -1, 5));
+        assert_eq!(12, visibility.explored_percent(1.into()));
+
+        visibility.conceal(1.into(), 5, 5, 1);
+        assert_eq!(TileVisibility::Explored, visibility.visibility(1.into(), 5, 4));
+        assert_eq!(TileVisibility::Visible, visibility.visibility(1.into(), 5, 5));
+        assert_eq!(12, visibility.explored_percent(1.into()));
+
+        // Sight stops at the edges of the map
+        visibility.reveal(1.into(), 0, 0, 2);
+        assert_eq!(TileVisibility::Visible, visibility.visibility(1.into(), 0, 2));
+        assert_eq!(TileVisibility::Unexplored, visibility.visibility(1.into(), 2, 2));
+    }
+
+    #[test]
+    fn test_remembered_buildings() {
+        let mut world = specs::World::new();
+        let entity = world.create_now().build();
+        let building = RememberedBuilding {
+            position: Vector3::new(5.5.into(), 5.5.into(), 0.into()),
+            player_color_id: 2.into(),
+            graphic_id: 10.into(),
+            frame: 0,
+            flip_horizontal: false,
+        };
+
+        // A building in sight is only drawn as it is, not also as it was remembered
+        let mut visibility = Visibility::new(10, 10);
+        visibility.reveal(1.into(), 5, 5, 1);
+        visibility.remember(1.into(), entity, building);
+        assert!(visibility.remembered_buildings(1.into()).is_empty());
+
+        // Once it's out of sight, it's drawn as it was last seen
+        visibility.conceal(1.into(), 5, 5, 1);
+        let remembered = visibility.remembered_buildings(1.into());
+        assert_eq!(1, remembered.len());
+        assert_eq!(Vector3::new(5.5.into(), 5.5.into(), 0.into()), remembered[0].position);
+    }
+}
@@ -0,0 +1,325 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat::{self, EmpiresDbRef, UnitAttributeId};
+use ecs::component::{ActionQueueComponent, AttackActionComponent, GraphicComponent, HealthComponent,
+                     ProjectileComponent, TransformComponent, UnitComponent, VelocityComponent};
+use ecs::resource::{OccupiedTiles, PathFinder, PlayerResearch, Random, Terrain};
+use specs::{self, Join};
+use super::movement::{approach, face, stop};
+use super::super::System;
+use identifier::UnitId;
+use types::{Fixed, Norm, Vector3};
+use util::combat;
+
+/// Projectile speed for projectile units that don't specify one, in tiles per second
+const DEFAULT_PROJECTILE_SPEED: Fixed = fixed_const!(5.0);
+
+pub struct AttackActionSystem {
+    empires: EmpiresDbRef,
+}
+
+impl AttackActionSystem {
+    pub fn new(empires: EmpiresDbRef) -> AttackActionSystem {
+        AttackActionSystem { empires: empires }
+    }
+
+    /// Returns the time into the attack animation at which the damage is dealt,
+    /// and the total length of the attack animation
+    fn attack_timing(&self, battle_params: &dat::BattleParams) -> (Fixed, Fixed) {
+        match battle_params.attack_graphic_id {
+            Some(graphic_id) => {
+                let graphic_info = self.empires.graphic(graphic_id);
+                // Same frame timing as the AnimationSystem: the frame rate is double the seconds per frame
+                let seconds_per_frame = Fixed::from(graphic_info.frame_rate) / 2.into();
+                let hit_time = seconds_per_frame * battle_params.frame_delay.into();
+                let duration = seconds_per_frame * graphic_info.frame_count.into();
+                (hit_time, if duration > hit_time { duration } else { hit_time })
+            }
+            None => (0.into(), 0.into()),
+        }
+    }
+
+    /// Creates a projectile flying from the attacker toward its target.
+    /// Whether the projectile hits is rolled against the attacker's accuracy when it's fired.
+    fn launch_projectile(&self,
+                         unit: &UnitComponent,
+                         attacker: specs::Entity,
+                         battle_params: &dat::BattleParams,
+                         projectile_unit_id: UnitId,
+                         position: &Vector3,
+                         direction: &Vector3,
+                         target: specs::Entity,
+                         target_position: &Vector3,
+                         target_velocity: &Vector3,
+                         random: &mut Random)
+                         -> ProjectileComponent {
+        let projectile_info = self.empires.unit(unit.civilization_id, projectile_unit_id);
+
+        // The displacements are relative to the direction the unit is facing
+        let forward: Fixed = battle_params.graphic_displacements[0].into();
+        let side: Fixed = battle_params.graphic_displacements[1].into();
+        let up: Fixed = battle_params.graphic_displacements[2].into();
+        let start = Vector3::new(position.x + direction.x * forward - direction.y * side,
+                                 position.y + direction.y * forward + direction.x * side,
+                                 position.z + up);
+
+        let speed = match projectile_info.motion_params {
+            Some(ref params) if params.speed > 0.0 => params.speed.into(),
+            _ => DEFAULT_PROJECTILE_SPEED,
+        };
+        let (smart, arc) = match projectile_info.projectile_params {
+            Some(ref params) => (params.smart_mode != 0, Fixed::from(params.projectile_arc)),
+            None => (false, 0.into()),
+        };
+
+        let mut end = *target_position;
+        if smart {
+            // Smart projectiles lead their target by aiming where it'll be when they arrive
+            let mut offset = end - start;
+            offset.z = 0.into();
+            end = end + *target_velocity * (offset.length() / speed);
+        }
+
+        let hit = random.roll_percent(battle_params.accuracy_percent as i32);
+        if !hit {
+            // Misses land somewhere within a tile of where they were aimed
+            let miss_x = Fixed::from((random.next_u32() % 201) as i32 - 100) / 100.into();
+            let miss_y = Fixed::from((random.next_u32() % 201) as i32 - 100) / 100.into();
+            end.x = end.x + miss_x;
+            end.y = end.y + miss_y;
+        }
+
+        let mut offset = end - start;
+        offset.z = 0.into();
+        let distance = offset.length();
+        ProjectileComponent::new(unit.clone(),
+                                 attacker,
+                                 if hit { Some(target) } else { None },
+                                 start,
+                                 end,
+                                 arc * distance,
+                                 distance / speed)
+    }
+}
+
+impl System for AttackActionSystem {
+    fn update(&mut self, arg: specs::RunArg, time_step: Fixed) {
+        fetch_components!(arg, entities, [
+            components(units: UnitComponent),
+            mut components(attacks: AttackActionComponent),
+            mut components(transforms: TransformComponent),
+            mut components(velocities: VelocityComponent),
+            mut components(graphics: GraphicComponent),
+            mut components(healths: HealthComponent),
+            mut components(action_queues: ActionQueueComponent),
+            mut components(projectiles: ProjectileComponent),
+            resource(path_finder: PathFinder),
+            resource(occupied_tiles: OccupiedTiles),
+            resource(terrain: Terrain),
+            resource(player_research: PlayerResearch),
+            mut resource(random: Random),
+        ]);
+
+        // Damage is applied and projectiles are created after every attacker has been
+        // updated so that the storages aren't borrowed while looking up targets
+        let mut hits: Vec<(specs::Entity, i32)> = Vec::new();
+        let mut launches: Vec<(ProjectileComponent, GraphicComponent)> = Vec::new();
+
+        let attackers: Vec<specs::Entity> = (&entities, &attacks).iter().map(|(entity, _)| entity).collect();
+        for attacker in attackers {
+            let (unit, position) = match (units.get(attacker), transforms.get(attacker)) {
+                (Some(unit), Some(transform)) => (unit, *transform.position()),
+                _ => continue,
+            };
+            let unit_info = unit.db(&self.empires);
+            let target = match attacks.get(attacker) {
+                Some(attack) => attack.target,
+                None => continue,
+            };
+            let target_state = match (units.get(target), transforms.get(target), healths.get(target)) {
+                (Some(unit), Some(transform), Some(health)) if health.is_alive() => {
+                    Some((unit.db(&self.empires), unit.player_id, *transform.position()))
+                }
+                _ => None,
+            };
+            let target_velocity = match velocities.get(target) {
+                Some(velocity) => velocity.velocity,
+                None => Vector3::new(0.into(), 0.into(), 0.into()),
+            };
+
+            let (attack, graphic, velocity, action_queue) = match (attacks.get_mut(attacker),
+                                                                   graphics.get_mut(attacker),
+                                                                   velocities.get_mut(attacker),
+                                                                   action_queues.get_mut(attacker)) {
+                (Some(a), Some(g), Some(v), Some(q)) => (a, g, v, q),
+                _ => continue,
+            };
+
+            if attack.reload_timer > 0.into() {
+                attack.reload_timer -= time_step;
+            }
+
+            let (battle_params, target_info, target_player_id, target_position) =
+                match (unit_info.battle_params.as_ref(), target_state) {
+                    (Some(battle_params), Some((target_info, target_player_id, target_position))) => {
+                        (battle_params, target_info, target_player_id, target_position)
+                    }
+                    _ => {
+                        stop(unit_info, graphic, velocity);
+                        action_queue.mark_current_done();
+                        continue;
+                    }
+                };
+            let max_range = player_research.attribute(unit.player_id,
+                                                      unit_info,
+                                                      UnitAttributeId::AttackRange,
+                                                      battle_params.max_range.into());
+
+            let mut direction = target_position - position;
+            direction.z = 0.into();
+
+            // Units standing on top of each other have no direction to face, so leave it zero
+            let center_distance = direction.length();
+            if center_distance > 0.into() {
+                direction /= center_distance;
+            }
+
+            // Ranges are measured between the edges of the units rather than their centers
+            let distance = center_distance - Fixed::from(unit_info.collision_size_x) -
+                           Fixed::from(target_info.collision_size_x);
+
+            if attack.swing_time.is_none() && distance > max_range {
+                if !approach(&*path_finder,
+                             &*terrain,
+                             &*occupied_tiles,
+                             unit_info,
+                             None,
+                             &mut attack.path,
+                             &mut attack.repath_timer,
+                             time_step,
+                             &position,
+                             &target_position,
+                             graphic,
+                             velocity) {
+                    stop(unit_info, graphic, velocity);
+                    action_queue.mark_current_done();
+                }
+            } else if attack.swing_time.is_none() && distance < Fixed::from(battle_params.min_range) {
+                // The target is too close to attack (e.g., for a catapult), so give up like the original
+                stop(unit_info, graphic, velocity);
+                action_queue.mark_current_done();
+            } else {
+                attack.path.clear();
+                attack.repath_timer = 0.into();
+                if let Some(transform) = transforms.get_mut(attacker) {
+                    face(&direction, velocity, &mut transform.rotation);
+                }
+
+                match attack.swing_time {
+                    None => {
+                        if attack.reload_timer <= 0.into() {
+                            graphic.set_graphic(battle_params.attack_graphic_id
+                                .or(unit_info.standing_graphic));
+                            attack.swing_time = Some(0.into());
+                            attack.hit_landed = false;
+                            attack.reload_timer = player_research.attribute(unit.player_id,
+                                                                            unit_info,
+                                                                            UnitAttributeId::ReloadTime,
+                                                                            battle_params.reload_time.into());
+                        } else if graphic.graphic_id != unit_info.standing_graphic {
+                            graphic.set_graphic(unit_info.standing_graphic);
+                        }
+                    }
+                    Some(swing_time) => {
+                        let swing_time = swing_time + time_step;
+                        let (hit_time, duration) = self.attack_timing(battle_params);
+                        if !attack.hit_landed && swing_time >= hit_time {
+                            attack.hit_landed = true;
+                            match battle_params.projectile_unit_id {
+                                Some(projectile_unit_id) => {
+                                    let projectile = self.launch_projectile(unit,
+                                                                            attacker,
+                                                                            battle_params,
+                                                                            projectile_unit_id,
+                                                                            &position,
+                                                                            &direction,
+                                                                            target,
+                                                                            &target_position,
+                                                                            &target_velocity,
+                                                                            &mut *random);
+                                    let projectile_info = self.empires
+                                        .unit(unit.civilization_id, projectile_unit_id);
+                                    let mut projectile_graphic = GraphicComponent::new();
+                                    projectile_graphic.player_color_id = graphic.player_color_id;
+                                    projectile_graphic.graphic_id = projectile_info.standing_graphic;
+                                    launches.push((projectile, projectile_graphic));
+                                }
+                                None => {
+                                    let unit_attacks = player_research.attacks(unit.player_id, unit_info);
+                                    let target_armors = player_research.armors(target_player_id, target_info);
+                                    let damage =
+                                        combat::hit_damage(&unit_attacks, target_info, &target_armors);
+                                    hits.push((target, damage));
+                                    let candidates = (&entities, &units, &transforms, &healths)
+                                        .iter()
+                                        .filter(|&(e, _, _, health)| {
+                                            e != target && e != attacker && health.is_alive()
+                                        })
+                                        .map(|(e, unit, transform, _)| {
+                                            let unit_info = unit.db(&self.empires);
+                                            let armors = player_research.armors(unit.player_id, unit_info);
+                                            (e, unit_info, armors, transform.position())
+                                        });
+                                    hits.extend(combat::blast_hits(unit_info,
+                                                                   &unit_attacks,
+                                                                   &target_position,
+                                                                   candidates));
+                                }
+                            }
+                        }
+
+                        attack.swing_time = if swing_time >= duration {
+                            graphic.set_graphic(unit_info.standing_graphic);
+                            None
+                        } else {
+                            Some(swing_time)
+                        };
+                    }
+                }
+            }
+        }
+
+        for (projectile, graphic) in launches {
+            let entity = arg.create();
+            transforms.insert(entity, TransformComponent::new(projectile.start, 0.into()));
+            velocities.insert(entity, VelocityComponent::new());
+            graphics.insert(entity, graphic);
+            projectiles.insert(entity, projectile);
+        }
+
+        for (victim, damage) in hits {
+            if let Some(health) = healths.get_mut(victim) {
+                health.damage(damage);
+            }
+        }
+    }
+}
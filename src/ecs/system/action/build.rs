@@ -0,0 +1,188 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat::{self, EmpiresDbRef};
+use ecs::component::{ActionQueueComponent, BuildActionComponent, ConstructionComponent, GraphicComponent,
+                     HealthComponent, TransformComponent, UnitComponent, VelocityComponent};
+use ecs::resource::{OccupiedTiles, PathFinder, PlayerResearch, Terrain};
+use specs::{self, Join};
+use super::movement::{approach, face, stop};
+use super::super::System;
+use types::Fixed;
+use util::unit;
+
+/// How close to a foundation (edge to edge) a unit has to be to work on it
+const BUILD_RANGE: Fixed = fixed_const!(0.5);
+
+/// Seconds it takes one unit to construct buildings that don't specify a build time
+const DEFAULT_BUILD_TIME: i16 = 30;
+
+pub struct BuildActionSystem {
+    empires: EmpiresDbRef,
+}
+
+impl BuildActionSystem {
+    pub fn new(empires: EmpiresDbRef) -> BuildActionSystem {
+        BuildActionSystem { empires: empires }
+    }
+}
+
+impl System for BuildActionSystem {
+    fn update(&mut self, arg: specs::RunArg, time_step: Fixed) {
+        fetch_components!(arg, entities, [
+            components(units: UnitComponent),
+            mut components(builds: BuildActionComponent),
+            mut components(constructions: ConstructionComponent),
+            mut components(healths: HealthComponent),
+            mut components(transforms: TransformComponent),
+            mut components(velocities: VelocityComponent),
+            mut components(graphics: GraphicComponent),
+            mut components(action_queues: ActionQueueComponent),
+            resource(path_finder: PathFinder),
+            resource(occupied_tiles: OccupiedTiles),
+            resource(terrain: Terrain),
+            mut resource(player_research: PlayerResearch),
+        ]);
+
+        // Progress is applied after every builder has been updated since several can work on
+        // the same foundation
+        let mut work: Vec<specs::Entity> = Vec::new();
+
+        let builders: Vec<specs::Entity> = (&entities, &builds).iter().map(|(entity, _)| entity).collect();
+        for builder in builders {
+            let (unit, position) = match (units.get(builder), transforms.get(builder)) {
+                (Some(unit), Some(transform)) => (unit, *transform.position()),
+                _ => continue,
+            };
+            let unit_info = unit.db(&self.empires);
+            let target = match builds.get(builder) {
+                Some(build) => build.target,
+                None => continue,
+            };
+            let target_state = match (units.get(target),
+                                      transforms.get(target),
+                                      healths.get(target),
+                                      constructions.get(target)) {
+                (Some(target_unit), Some(transform), Some(health), Some(_))
+                    if health.is_alive() && target_unit.player_id == unit.player_id => {
+                    Some((target_unit.db(&self.empires), *transform.position()))
+                }
+                _ => None,
+            };
+
+            let (build, graphic, velocity, action_queue) = match (builds.get_mut(builder),
+                                                                  graphics.get_mut(builder),
+                                                                  velocities.get_mut(builder),
+                                                                  action_queues.get_mut(builder)) {
+                (Some(b), Some(g), Some(v), Some(q)) => (b, g, v, q),
+                _ => continue,
+            };
+
+            // Stop once the building is finished (or destroyed)
+            let (target_info, target_position) = match target_state {
+                Some(state) => state,
+                None => {
+                    stop(unit_info, graphic, velocity);
+                    action_queue.mark_current_done();
+                    continue;
+                }
+            };
+
+            if unit::edge_distance(unit_info, &position, target_info, &target_position) > BUILD_RANGE {
+                if !approach(&*path_finder,
+                             &*terrain,
+                             &*occupied_tiles,
+                             unit_info,
+                             None,
+                             &mut build.path,
+                             &mut build.repath_timer,
+                             time_step,
+                             &position,
+                             &target_position,
+                             graphic,
+                             velocity) {
+                    stop(unit_info, graphic, velocity);
+                    action_queue.mark_current_done();
+                }
+            } else {
+                build.path.clear();
+                build.repath_timer = 0.into();
+                if let Some(transform) = transforms.get_mut(builder) {
+                    face(&(target_position - position), velocity, &mut transform.rotation);
+                }
+                let build_graphic = unit::build_command(unit_info)
+                    .and_then(|command| command.action_graphic_id)
+                    .or(unit_info.standing_graphic);
+                if graphic.graphic_id != build_graphic {
+                    graphic.set_graphic(build_graphic);
+                }
+                work.push(target);
+            }
+        }
+
+        for target in work {
+            let target_unit = match units.get(target) {
+                Some(unit) => unit,
+                None => continue,
+            };
+            let target_info = target_unit.db(&self.empires);
+            let finished = match (constructions.get_mut(target), healths.get_mut(target)) {
+                (Some(construction), Some(health)) => {
+                    let old_progress = construction.progress;
+                    construction.progress += time_step / build_time(target_info);
+                    if construction.progress > 1.into() {
+                        construction.progress = 1.into();
+                    }
+
+                    // The building's hit points go up as it's constructed
+                    let max_hit_points = Fixed::from(health.max_hit_points);
+                    let gained = i32::from(max_hit_points * construction.progress) -
+                                 i32::from(max_hit_points * old_progress);
+                    health.hit_points += gained;
+                    if health.hit_points > health.max_hit_points {
+                        health.hit_points = health.max_hit_points;
+                    }
+                    construction.progress >= 1.into()
+                }
+                _ => false,
+            };
+
+            if finished {
+                constructions.remove(target);
+                if let Some(graphic) = graphics.get_mut(target) {
+                    graphic.set_graphic(target_info.standing_graphic);
+                }
+                if let Some(research_id) = target_info.building_params
+                    .as_ref()
+                    .and_then(|params| params.research_id) {
+                    player_research.complete(target_unit.player_id, research_id);
+                }
+            }
+        }
+    }
+}
+
+fn build_time(unit_info: &dat::Unit) -> Fixed {
+    match unit_info.trainable_params {
+        Some(ref params) if params.train_time > 0 => params.train_time.into(),
+        _ => DEFAULT_BUILD_TIME.into(),
+    }
+}
@@ -0,0 +1,124 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat::EmpiresDbRef;
+use ecs::component::{ActionQueueComponent, CarriedResourceComponent, DropOffActionComponent, GraphicComponent,
+                     HealthComponent, TransformComponent, UnitComponent, VelocityComponent};
+use ecs::resource::{OccupiedTiles, PathFinder, PlayerResources, SoundQueue, Terrain};
+use specs::{self, Join};
+use super::gather::DROP_OFF_RANGE;
+use super::movement::{approach, stop};
+use super::super::System;
+use types::Fixed;
+use util::unit;
+
+pub struct DropOffActionSystem {
+    empires: EmpiresDbRef,
+}
+
+impl DropOffActionSystem {
+    pub fn new(empires: EmpiresDbRef) -> DropOffActionSystem {
+        DropOffActionSystem { empires: empires }
+    }
+}
+
+impl System for DropOffActionSystem {
+    fn update(&mut self, arg: specs::RunArg, time_step: Fixed) {
+        fetch_components!(arg, entities, [
+            components(units: UnitComponent),
+            components(healths: HealthComponent),
+            components(transforms: TransformComponent),
+            mut components(drop_offs: DropOffActionComponent),
+            mut components(carried_resources: CarriedResourceComponent),
+            mut components(velocities: VelocityComponent),
+            mut components(graphics: GraphicComponent),
+            mut components(action_queues: ActionQueueComponent),
+            resource(path_finder: PathFinder),
+            resource(occupied_tiles: OccupiedTiles),
+            resource(terrain: Terrain),
+            mut resource(player_resources: PlayerResources),
+            mut resource(sounds: SoundQueue),
+        ]);
+
+        for (entity, unit, transform, drop_off) in (&entities, &units, &transforms, &mut drop_offs).iter() {
+            let (graphic, velocity, action_queue) = match (graphics.get_mut(entity),
+                                                           velocities.get_mut(entity),
+                                                           action_queues.get_mut(entity)) {
+                (Some(g), Some(v), Some(q)) => (g, v, q),
+                _ => continue,
+            };
+            let unit_info = unit.db(&self.empires);
+            let position = *transform.position();
+
+            let carried = match carried_resources.get(entity) {
+                Some(carried) => carried.clone(),
+                None => {
+                    // Nothing to drop off
+                    stop(unit_info, graphic, velocity);
+                    action_queue.mark_current_done();
+                    continue;
+                }
+            };
+            let site = match (units.get(drop_off.target),
+                              transforms.get(drop_off.target),
+                              healths.get(drop_off.target)) {
+                (Some(site_unit), Some(site_transform), Some(health))
+                    if health.is_alive() && site_unit.player_id == unit.player_id => {
+                    Some((site_unit.db(&self.empires), *site_transform.position()))
+                }
+                _ => None,
+            };
+            let (site_info, site_position) = match site {
+                Some(site) if unit::is_drop_site(unit_info, site.0) => site,
+                _ => {
+                    stop(unit_info, graphic, velocity);
+                    action_queue.mark_current_done();
+                    continue;
+                }
+            };
+
+            let command = unit::carrying_command(unit_info, carried.resource_type);
+            if unit::edge_distance(unit_info, &position, site_info, &site_position) <= DROP_OFF_RANGE {
+                carried_resources.remove(entity);
+                player_resources.add(unit.player_id, carried.resource_type, carried.amount);
+                if let Some(sound_group_id) = command.and_then(|c| c.resource_deposit_sound_id) {
+                    sounds.play(sound_group_id, Some(position));
+                }
+                stop(unit_info, graphic, velocity);
+                action_queue.mark_current_done();
+            } else if !approach(&*path_finder,
+                                &*terrain,
+                                &*occupied_tiles,
+                                unit_info,
+                                command.and_then(|c| c.carrying_graphic_id),
+                                &mut drop_off.path,
+                                &mut drop_off.repath_timer,
+                                time_step,
+                                &position,
+                                &site_position,
+                                graphic,
+                                velocity) {
+                stop(unit_info, graphic, velocity);
+                action_queue.mark_current_done();
+            }
+        }
+    }
+}
@@ -0,0 +1,340 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat::{self, EmpiresDbRef, ResourceType, UnitAttributeId};
+use ecs::component::{ActionQueueComponent, CarriedResourceComponent, DyingComponent, GatherActionComponent,
+                     GraphicComponent, HealthComponent, ResourceSiteComponent, TransformComponent,
+                     UnitComponent, VelocityComponent};
+use ecs::resource::{OccupiedTiles, PathFinder, PlayerResearch, PlayerResources, SoundQueue, Terrain};
+use identifier::{GraphicId, SoundGroupId};
+use partition::GridPartition;
+use specs::{self, Join};
+use std::cmp;
+use super::movement::{approach, face, stop};
+use super::super::System;
+use types::{Fixed, Norm, Vector3};
+use util::unit;
+
+/// How close to a resource site (edge to edge) a unit has to be to gather from it
+const GATHER_RANGE: Fixed = fixed_const!(0.5);
+
+/// How close to a drop site (edge to edge) a unit has to be to drop off its resources
+pub const DROP_OFF_RANGE: Fixed = fixed_const!(0.5);
+
+/// How far from the last resource site to look for another one once it runs out, in tiles
+const SEARCH_RADIUS: Fixed = fixed_const!(10.0);
+
+/// Resources gathered per second by units that don't specify a work rate
+const DEFAULT_WORK_RATE: Fixed = fixed_const!(0.5);
+
+/// Resources carried by units that don't specify a capacity
+const DEFAULT_CAPACITY: i16 = 10;
+
+/// What a gathering unit does in one update
+enum GatherStep {
+    Approach(Vector3, Option<GraphicId>),
+    Gather(specs::Entity, ResourceType, Vector3, Option<GraphicId>),
+    Deposit(Option<SoundGroupId>),
+    Wait,
+    Finish,
+}
+
+pub struct GatherActionSystem {
+    empires: EmpiresDbRef,
+}
+
+impl GatherActionSystem {
+    pub fn new(empires: EmpiresDbRef) -> GatherActionSystem {
+        GatherActionSystem { empires: empires }
+    }
+}
+
+impl System for GatherActionSystem {
+    fn update(&mut self, arg: specs::RunArg, time_step: Fixed) {
+        fetch_components!(arg, entities, [
+            components(units: UnitComponent),
+            components(healths: HealthComponent),
+            components(dying_units: DyingComponent),
+            mut components(gathers: GatherActionComponent),
+            mut components(carried_resources: CarriedResourceComponent),
+            mut components(resource_sites: ResourceSiteComponent),
+            mut components(transforms: TransformComponent),
+            mut components(velocities: VelocityComponent),
+            mut components(graphics: GraphicComponent),
+            mut components(action_queues: ActionQueueComponent),
+            resource(path_finder: PathFinder),
+            resource(occupied_tiles: OccupiedTiles),
+            resource(terrain: Terrain),
+            resource(player_research: PlayerResearch),
+            mut resource(player_resources: PlayerResources),
+            mut resource(sounds: SoundQueue),
+            mut resource(grid: GridPartition),
+        ]);
+
+        let mut depleted = Vec::new();
+        let gatherers: Vec<specs::Entity> = (&entities, &gathers).iter().map(|(entity, _)| entity).collect();
+        for gatherer in gatherers {
+            let (unit, position) = match (units.get(gatherer), transforms.get(gatherer)) {
+                (Some(unit), Some(transform)) => (unit, *transform.position()),
+                _ => continue,
+            };
+            let unit_info = unit.db(&self.empires);
+            let carried = carried_resources.get(gatherer).cloned();
+            let carried_amount = carried.as_ref().map(|carried| carried.amount).unwrap_or(0.into());
+            let carried_command = carried.as_ref()
+                .and_then(|carried| unit::carrying_command(unit_info, carried.resource_type));
+            let carrying_graphic = carried_command.and_then(|command| command.carrying_graphic_id);
+            let capacity = player_research.attribute(unit.player_id,
+                                                     unit_info,
+                                                     UnitAttributeId::ResourceCarryCapacity,
+                                                     carry_capacity(unit_info));
+            let work_rate = player_research.attribute(unit.player_id,
+                                                      unit_info,
+                                                      UnitAttributeId::WorkRate,
+                                                      work_rate(unit_info));
+
+            let step = {
+                let gather = match gathers.get_mut(gatherer) {
+                    Some(gather) => gather,
+                    None => continue,
+                };
+                if carried_amount >= capacity {
+                    gather.returning = true;
+                }
+
+                if gather.returning {
+                    // Head back to the nearest drop site, unless one has already been picked
+                    let cached_site = gather.drop_site.and_then(|site| {
+                        match (units.get(site), transforms.get(site)) {
+                            (Some(site_unit), Some(transform)) => {
+                                Some((site, site_unit.db(&self.empires), *transform.position()))
+                            }
+                            _ => None,
+                        }
+                    });
+                    let drop_site = cached_site.or_else(|| {
+                        let mut nearest: Option<(specs::Entity, &dat::Unit, Vector3, Fixed)> = None;
+                        let candidates = (&entities, &units, &transforms, &healths);
+                        for (site, site_unit, transform, health) in candidates.iter() {
+                            let site_info = site_unit.db(&self.empires);
+                            if site_unit.player_id != unit.player_id || !health.is_alive() ||
+                               !unit::is_drop_site(unit_info, site_info) {
+                                continue;
+                            }
+                            let distance =
+                                unit::edge_distance(unit_info, &position, site_info, transform.position());
+                            if nearest.as_ref().map(|nearest| distance < nearest.3).unwrap_or(true) {
+                                nearest = Some((site, site_info, *transform.position(), distance));
+                            }
+                        }
+                        nearest.map(|(site, site_info, site_position, _)| (site, site_info, site_position))
+                    });
+
+                    match drop_site {
+                        Some((site, site_info, site_position)) => {
+                            gather.drop_site = Some(site);
+                            if unit::edge_distance(unit_info, &position, site_info, &site_position) <=
+                               DROP_OFF_RANGE {
+                                gather.drop_site = None;
+                                gather.returning = false;
+                                GatherStep::Deposit(carried_command.and_then(|c| c.resource_deposit_sound_id))
+                            } else {
+                                GatherStep::Approach(site_position, carrying_graphic)
+                            }
+                        }
+                        None => GatherStep::Finish,
+                    }
+                } else {
+                    let target_site = gather.target.and_then(|target| {
+                        match (units.get(target), transforms.get(target), resource_sites.get(target)) {
+                            (Some(site_unit), Some(transform), Some(site)) if site.amount > 0.into() => {
+                                let site_info = site_unit.db(&self.empires);
+                                Some((target, site_info, *transform.position(), site.resource_type))
+                            }
+                            _ => None,
+                        }
+                    });
+                    let dying_target = gather.target.and_then(|target| {
+                        match (dying_units.get(target), transforms.get(target)) {
+                            (Some(dying), Some(transform)) => {
+                                Some((dying.unit.db(&self.empires), *transform.position()))
+                            }
+                            _ => None,
+                        }
+                    });
+
+                    if target_site.is_none() && dying_target.is_some() {
+                        // A hunted animal is still dying; wait for it to leave its carcass behind
+                        let (dying_info, dying_position) = dying_target.unwrap();
+                        if gather.resource_type.is_none() {
+                            gather.resource_type = unit::gatherable_resource(dying_info).map(|r| r.0);
+                        }
+                        gather.last_site_position = Some(dying_position);
+                        GatherStep::Wait
+                    } else {
+                        let site = target_site.or_else(|| {
+                            // The site ran out (or was never a site), so look for another one like it nearby
+                            let resource_type = match gather.resource_type {
+                                Some(resource_type) => resource_type,
+                                None => return None,
+                            };
+                            let center = gather.last_site_position.unwrap_or(position);
+                            let mut nearest: Option<(specs::Entity, &dat::Unit, Vector3, Fixed)> = None;
+                            let candidates = (&entities, &units, &transforms, &resource_sites);
+                            for (entity, site_unit, transform, site) in candidates.iter() {
+                                let site_info = site_unit.db(&self.empires);
+                                if site.resource_type != resource_type || site.amount <= 0.into() ||
+                                   unit::gather_command(unit_info, site_info).is_none() {
+                                    continue;
+                                }
+                                let mut offset = *transform.position() - center;
+                                offset.z = 0.into();
+                                let distance = offset.length();
+                                if distance <= SEARCH_RADIUS &&
+                                   nearest.as_ref().map(|nearest| distance < nearest.3).unwrap_or(true) {
+                                    nearest = Some((entity, site_info, *transform.position(), distance));
+                                }
+                            }
+                            nearest.map(|(entity, site_info, site_position, _)| {
+                                (entity, site_info, site_position, resource_type)
+                            })
+                        });
+
+                        match site {
+                            Some((target, site_info, site_position, resource_type)) => {
+                                gather.target = Some(target);
+                                gather.resource_type = Some(resource_type);
+                                gather.last_site_position = Some(site_position);
+                                let command = unit::gather_command(unit_info, site_info);
+                                if unit::edge_distance(unit_info, &position, site_info, &site_position) <=
+                                   GATHER_RANGE {
+                                    GatherStep::Gather(target,
+                                                       resource_type,
+                                                       site_position - position,
+                                                       command.and_then(|c| c.action_graphic_id))
+                                } else {
+                                    GatherStep::Approach(site_position, carrying_graphic)
+                                }
+                            }
+                            None if carried_amount > 0.into() => {
+                                gather.returning = true;
+                                GatherStep::Wait
+                            }
+                            None => GatherStep::Finish,
+                        }
+                    }
+                }
+            };
+
+            let (gather, graphic, velocity, action_queue) = match (gathers.get_mut(gatherer),
+                                                                   graphics.get_mut(gatherer),
+                                                                   velocities.get_mut(gatherer),
+                                                                   action_queues.get_mut(gatherer)) {
+                (Some(a), Some(g), Some(v), Some(q)) => (a, g, v, q),
+                _ => continue,
+            };
+
+            match step {
+                GatherStep::Approach(destination, walking_graphic) => {
+                    if !approach(&*path_finder,
+                                 &*terrain,
+                                 &*occupied_tiles,
+                                 unit_info,
+                                 walking_graphic,
+                                 &mut gather.path,
+                                 &mut gather.repath_timer,
+                                 time_step,
+                                 &position,
+                                 &destination,
+                                 graphic,
+                                 velocity) {
+                        stop(unit_info, graphic, velocity);
+                        action_queue.mark_current_done();
+                    }
+                }
+                GatherStep::Gather(site, resource_type, direction, action_graphic) => {
+                    gather.path.clear();
+                    gather.repath_timer = 0.into();
+                    if let Some(transform) = transforms.get_mut(gatherer) {
+                        face(&direction, velocity, &mut transform.rotation);
+                    }
+                    if action_graphic.is_some() && graphic.graphic_id != action_graphic {
+                        graphic.set_graphic(action_graphic);
+                    }
+
+                    // Whatever else the unit was carrying is thrown away, like in the original
+                    if carried.as_ref().map(|c| c.resource_type != resource_type).unwrap_or(true) {
+                        carried_resources.insert(gatherer, CarriedResourceComponent::new(resource_type));
+                    }
+                    if let (Some(carried), Some(site_resources)) = (carried_resources.get_mut(gatherer),
+                                                                    resource_sites.get_mut(site)) {
+                        let amount = cmp::min(work_rate * time_step,
+                                              cmp::min(site_resources.amount, capacity - carried.amount));
+                        site_resources.amount -= amount;
+                        carried.amount += amount;
+                        if site_resources.amount <= 0.into() {
+                            depleted.push(site);
+                        }
+                    }
+                }
+                GatherStep::Deposit(sound) => {
+                    if let Some(carried) = carried_resources.remove(gatherer) {
+                        player_resources.add(unit.player_id, carried.resource_type, carried.amount);
+                    }
+                    if let Some(sound_group_id) = sound {
+                        sounds.play(sound_group_id, Some(position));
+                    }
+                    gather.path.clear();
+                    gather.repath_timer = 0.into();
+                }
+                GatherStep::Wait => stop(unit_info, graphic, velocity),
+                GatherStep::Finish => {
+                    stop(unit_info, graphic, velocity);
+                    action_queue.mark_current_done();
+                }
+            }
+        }
+
+        for site in depleted {
+            if resource_sites.remove(site).is_some() {
+                // Removing the velocity keeps the VelocitySystem from putting it back in the grid
+                velocities.remove(site);
+                grid.remove_entity(site.get_id());
+                arg.delete(site);
+            }
+        }
+    }
+}
+
+fn carry_capacity(unit_info: &dat::Unit) -> Fixed {
+    if unit_info.resource_capacity > 0 {
+        unit_info.resource_capacity.into()
+    } else {
+        DEFAULT_CAPACITY.into()
+    }
+}
+
+fn work_rate(unit_info: &dat::Unit) -> Fixed {
+    match unit_info.commandable_params {
+        Some(ref params) if params.work_rate > 0.0 => params.work_rate.into(),
+        _ => DEFAULT_WORK_RATE,
+    }
+}
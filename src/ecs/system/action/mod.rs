@@ -19,6 +19,15 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+mod attack;
+mod build;
+mod drop_off;
+mod gather;
 mod move_to_position;
+mod movement;
 
+pub use self::attack::AttackActionSystem;
+pub use self::build::BuildActionSystem;
+pub use self::drop_off::DropOffActionSystem;
+pub use self::gather::GatherActionSystem;
 pub use self::move_to_position::MoveToPositionActionSystem;
@@ -0,0 +1,123 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Movement shared by the action systems that need to walk a unit up to something first
+
+use dat;
+use ecs::component::{GraphicComponent, VelocityComponent};
+use ecs::resource::{OccupiedTiles, PathFinder, Terrain};
+use ecs::resource::path_finder::Path;
+use identifier::GraphicId;
+use types::{Fixed, Norm, Vector3};
+
+const THRESHOLD: Fixed = fixed_const!(0.1);
+
+/// How often the path is recalculated while approaching something, since it may be moving
+const REPATH_INTERVAL: Fixed = fixed_const!(1.0);
+
+/// Walks the unit toward the destination, recalculating its path every so often. The walking
+/// graphic can be overridden (e.g., for villagers carrying resources). Returns false if the
+/// unit isn't able to move.
+pub fn approach(path_finder: &PathFinder,
+                terrain: &Terrain,
+                occupied_tiles: &OccupiedTiles,
+                unit_info: &dat::Unit,
+                walking_graphic: Option<GraphicId>,
+                path: &mut Path,
+                repath_timer: &mut Fixed,
+                time_step: Fixed,
+                position: &Vector3,
+                destination: &Vector3,
+                graphic: &mut GraphicComponent,
+                velocity: &mut VelocityComponent)
+                -> bool {
+    *repath_timer -= time_step;
+    if *repath_timer <= 0.into() {
+        *path = path_finder.find_path(terrain,
+                                      occupied_tiles,
+                                      position,
+                                      destination,
+                                      unit_info.terrain_restriction);
+        *repath_timer = REPATH_INTERVAL;
+    }
+    follow_path(unit_info, walking_graphic, path, position, graphic, velocity)
+}
+
+/// Moves the unit along its path; returns false if the unit isn't able to move
+pub fn follow_path(unit_info: &dat::Unit,
+                   walking_graphic: Option<GraphicId>,
+                   path: &mut Path,
+                   position: &Vector3,
+                   graphic: &mut GraphicComponent,
+                   velocity: &mut VelocityComponent)
+                   -> bool {
+    loop {
+        let node = match path.first() {
+            Some(node) => *node,
+            None => break,
+        };
+
+        let mut direction = node - *position;
+        let distance = direction.length();
+        if distance <= THRESHOLD {
+            path.remove(0);
+            continue;
+        }
+        direction /= distance;
+
+        return match unit_info.motion_params {
+            Some(ref params) => {
+                let walking_graphic = walking_graphic.or(params.walking_graphics[0]);
+                if walking_graphic.is_some() && graphic.graphic_id != walking_graphic {
+                    graphic.set_graphic(walking_graphic);
+                }
+                let speed: Fixed = params.speed.into();
+                velocity.velocity = direction * speed;
+                true
+            }
+            None => false,
+        };
+    }
+
+    // Wait for the next path to the destination
+    velocity.velocity = Vector3::new(0.into(), 0.into(), 0.into());
+    true
+}
+
+/// Stops the unit where it is
+pub fn stop(unit_info: &dat::Unit, graphic: &mut GraphicComponent, velocity: &mut VelocityComponent) {
+    if graphic.graphic_id != unit_info.standing_graphic {
+        graphic.set_graphic(unit_info.standing_graphic);
+    }
+    velocity.velocity = Vector3::new(0.into(), 0.into(), 0.into());
+}
+
+/// Stops the unit and turns it to face the given direction
+pub fn face(direction: &Vector3, velocity: &mut VelocityComponent, rotation: &mut Fixed) {
+    velocity.velocity = Vector3::new(0.into(), 0.into(), 0.into());
+    if direction.x == 0.into() && direction.y == 0.into() {
+        // Nothing to face, so keep the current rotation
+        return;
+    }
+    let dir_x: f32 = direction.x.into();
+    let dir_y: f32 = direction.y.into();
+    *rotation = dir_y.atan2(dir_x).into();
+}
@@ -21,6 +21,7 @@
 
 use dat;
 use ecs::{GraphicComponent, TransformComponent};
+use ecs::resource::SoundQueue;
 use resource::{DrsKey, ShapeMetadataKey, ShapeMetadataStoreRef};
 use specs::{self, Join};
 use std::ops::Rem;
@@ -42,15 +43,16 @@ impl AnimationSystem {
 
     fn update_graphic(&self,
                       graphic: &mut GraphicComponent,
-                      rotation: Fixed,
+                      transform: &TransformComponent,
                       graphic_info: &dat::Graphic,
+                      sounds: &mut SoundQueue,
                       time_step: Fixed) {
         graphic.frame_time += time_step;
 
         if let Some(slp_id) = graphic_info.slp_id {
             let shape_key = ShapeMetadataKey::new(DrsKey::Graphics, slp_id);
             if let Some(shape_metadata) = self.shape_metadata.get(&shape_key) {
-                let (start_frame, flip_horizontal) = start_frame_and_mirroring(rotation,
+                let (start_frame, flip_horizontal) = start_frame_and_mirroring(transform.rotation,
                                                                                shape_metadata.shape_count,
                                                                                graphic_info.frame_count,
                                                                                graphic_info.angle_count);
@@ -64,23 +66,70 @@ impl AnimationSystem {
                 }
                 graphic.frame = current_frame;
                 graphic.flip_horizontal = flip_horizontal;
+
+                let cycle_frame = current_frame - start_frame;
+                if graphic.sound_frame != Some(cycle_frame) {
+                    graphic.sound_frame = Some(cycle_frame);
+                    play_frame_sounds(graphic_info, cycle_frame, transform, sounds);
+                }
             }
         }
     }
 }
 
+/// Graphics that don't animate play their sound once, when they're first shown (e.g., a building)
+fn update_static_graphic(graphic: &mut GraphicComponent,
+                         transform: &TransformComponent,
+                         graphic_info: &dat::Graphic,
+                         sounds: &mut SoundQueue) {
+    if graphic.sound_frame.is_none() {
+        graphic.sound_frame = Some(0);
+        play_frame_sounds(graphic_info, 0, transform, sounds);
+    }
+}
+
+/// Queues the sounds the graphic plays when its animation reaches the given frame: its own sound
+/// at the start of every cycle (e.g., footsteps or hammering), and its attack sounds at their delays
+fn play_frame_sounds(graphic_info: &dat::Graphic,
+                     cycle_frame: u16,
+                     transform: &TransformComponent,
+                     sounds: &mut SoundQueue) {
+    let position = Some(*transform.position());
+    if cycle_frame == 0 {
+        if let Some(sound_group_id) = graphic_info.sound_group_id {
+            sounds.play(sound_group_id, position);
+        }
+    }
+
+    // Each angle has its own attack sounds, but they're the same for every angle in practice
+    let mut played = Vec::new();
+    for attack_sound in &graphic_info.attack_sounds {
+        if attack_sound.sound_delay as i32 == cycle_frame as i32 &&
+           !played.contains(&attack_sound.sound_group_id) {
+            sounds.play(attack_sound.sound_group_id, position);
+            played.push(attack_sound.sound_group_id);
+        }
+    }
+}
+
 impl System for AnimationSystem {
     fn update(&mut self, arg: specs::RunArg, time_step: Fixed) {
         fetch_components!(arg, _entities, [
             components(transforms: TransformComponent),
             mut components(graphics: GraphicComponent),
+            mut resource(sounds: SoundQueue),
         ]);
 
         for (transform, graphic) in (&transforms, &mut graphics).iter() {
             if let Some(graphic_id) = graphic.graphic_id {
                 let graphic_info = self.empires.graphic(graphic_id);
-                if graphic_info.frame_count > 1 {
-                    self.update_graphic(graphic, transform.rotation, graphic_info, time_step);
+                // Single frame graphics still need their angle picked (e.g., arrows), but graphics
+                // without any frames or angles have nothing to pick from
+                let has_frames = graphic_info.frame_count > 0 && graphic_info.angle_count > 0;
+                if has_frames && (graphic_info.frame_count > 1 || graphic_info.angle_count > 1) {
+                    self.update_graphic(graphic, transform, graphic_info, &mut *sounds, time_step);
+                } else {
+                    update_static_graphic(graphic, transform, graphic_info, &mut *sounds);
                 }
             }
         }
@@ -99,9 +148,14 @@ fn start_frame_and_mirroring(rotation: Fixed,
                              frame_count: u16,
                              angle_count: u16)
                              -> (u16, bool) {
+    if shape_count == 0 || frame_count == 0 || angle_count == 0 {
+        return (0, false);
+    }
+
     let rotation = wrap_angle(rotation);
     let angles_in_slp = shape_count as u16 / frame_count;
-    let mirror_count = angle_count - angles_in_slp;
+    // Some SLPs have more angles than the graphic uses, in which case nothing is mirrored
+    let mirror_count = angle_count.saturating_sub(angles_in_slp);
 
     let mut angle_index = u16::from((rotation * angle_count.into() / Fixed::two_pi()).round()) % angle_count;
     let mut mirror = false;
@@ -133,8 +187,11 @@ fn frame_at_time(time: Fixed, frame_rate: Fixed, frame_count: u16, replay_delay:
 
 #[cfg(test)]
 mod tests {
-    use super::{frame_at_time, start_frame_and_mirroring, wrap_angle};
-    use types::Fixed;
+    use dat;
+    use ecs::{GraphicComponent, TransformComponent};
+    use ecs::resource::SoundQueue;
+    use super::{frame_at_time, start_frame_and_mirroring, update_static_graphic, wrap_angle};
+    use types::{Fixed, Vector3};
 
     #[test]
     fn test_start_frame_and_mirroring() {
@@ -162,6 +219,16 @@ mod tests {
                    start_frame_and_mirroring(rad(316), 30, 6, 8));
     }
 
+    #[test]
+    fn test_start_frame_and_mirroring_mismatched_counts() {
+        let rad = |deg: u32| Fixed::from(deg) * Fixed::pi() / Fixed::from(180);
+
+        assert_eq!((0u16, false), start_frame_and_mirroring(rad(91), 30, 0, 8));
+        assert_eq!((0u16, false), start_frame_and_mirroring(rad(91), 30, 6, 0));
+        assert_eq!((0u16, false), start_frame_and_mirroring(rad(91), 0, 6, 8));
+        assert_eq!((5u16, false), start_frame_and_mirroring(rad(91), 40, 5, 4));
+    }
+
     #[test]
     fn test_wrap_angle() {
         assert_eq!(Fixed::from(0), wrap_angle(0.into()));
@@ -180,4 +247,30 @@ mod tests {
         assert_eq!(3u16, frame_at_time(2.0.into(), 1.into(), 4, 1.into()));
         assert_eq!(0u16, frame_at_time(3.0.into(), 1.into(), 4, 1.into()));
     }
+
+    #[test]
+    fn test_static_graphic_sound() {
+        let mut graphic_info: dat::Graphic = Default::default();
+        graphic_info.sound_group_id = Some(7.into());
+        graphic_info.frame_count = 1;
+        graphic_info.angle_count = 1;
+
+        let transform = TransformComponent::new(Vector3::new(1.into(), 2.into(), 0.into()), 0.into());
+        let mut graphic = GraphicComponent::new();
+        graphic.set_graphic(Some(3.into()));
+        let mut sounds = SoundQueue::new();
+
+        // The sound plays when the graphic is first shown, and not again every update
+        update_static_graphic(&mut graphic, &transform, &graphic_info, &mut sounds);
+        update_static_graphic(&mut graphic, &transform, &graphic_info, &mut sounds);
+        let played = sounds.consume_sounds();
+        assert_eq!(1, played.len());
+        assert_eq!(7, *played[0].sound_group_id);
+        assert_eq!(Some(*transform.position()), played[0].position);
+
+        // Switching to the graphic again shows it anew
+        graphic.set_graphic(Some(3.into()));
+        update_static_graphic(&mut graphic, &transform, &graphic_info, &mut sounds);
+        assert_eq!(1, sounds.consume_sounds().len());
+    }
 }
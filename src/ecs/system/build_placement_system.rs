@@ -0,0 +1,173 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This system handles build mode: picking a building with its hotkey, moving the placement
+//! ghost with the mouse, and laying down the foundation that the selected units then construct.
+
+use action::{Action, BuildParams};
+use dat;
+use ecs::{ActionQueueComponent, ConstructionComponent, GraphicComponent, HealthComponent,
+          SelectedUnitComponent, TransformComponent, UnitComponent, VelocityComponent};
+use ecs::resource::{ActionBatcher, BuildPlacement, InputAction, InputActionStates, MouseState,
+                    OccupiedTiles, PlayerResearch, PlayerResources, Players, SoundQueue, Terrain,
+                    ViewProjector, Viewport};
+use media::KeyState;
+use specs::{self, Join};
+use super::System;
+use types::Fixed;
+use util::{placement, unit};
+
+/// The buildings that can be placed with hotkeys, by unit ID
+const BUILD_HOTKEYS: &'static [(InputAction, usize)] = &[(InputAction::BuildHouse, 70),
+                                                         (InputAction::BuildGranary, 68),
+                                                         (InputAction::BuildStoragePit, 103),
+                                                         (InputAction::BuildBarracks, 12),
+                                                         (InputAction::BuildDock, 45)];
+
+pub struct BuildPlacementSystem {
+    empires: dat::EmpiresDbRef,
+}
+
+impl BuildPlacementSystem {
+    pub fn new(empires: dat::EmpiresDbRef) -> BuildPlacementSystem {
+        BuildPlacementSystem { empires: empires }
+    }
+}
+
+impl System for BuildPlacementSystem {
+    fn update(&mut self, arg: specs::RunArg, _time_step: Fixed) {
+        fetch_components!(arg, entities, [
+            components(selected_units: SelectedUnitComponent),
+            mut components(units: UnitComponent),
+            mut components(transforms: TransformComponent),
+            mut components(graphics: GraphicComponent),
+            mut components(velocities: VelocityComponent),
+            mut components(healths: HealthComponent),
+            mut components(action_queues: ActionQueueComponent),
+            mut components(constructions: ConstructionComponent),
+            resource(actions: InputActionStates),
+            resource(mouse_state: MouseState),
+            resource(players: Players),
+            resource(view_projector: ViewProjector),
+            resource(viewport: Viewport),
+            resource(occupied_tiles: OccupiedTiles),
+            resource(terrain: Terrain),
+            resource(player_research: PlayerResearch),
+            mut resource(build_placement: BuildPlacement),
+            mut resource(player_resources: PlayerResources),
+            mut resource(action_batcher: ActionBatcher),
+            mut resource(sounds: SoundQueue),
+        ]);
+
+        let local_player = players.local_player();
+        let player_id = local_player.player_id;
+        let builders: Vec<specs::Entity> = (&entities, &units, &selected_units)
+            .iter()
+            .filter(|&(_, unit, _)| {
+                unit.player_id == player_id &&
+                unit::build_command(unit.db(&self.empires)).is_some()
+            })
+            .map(|(entity, _, _)| entity)
+            .collect();
+
+        // Build mode only lasts as long as something that can build is selected
+        if builders.is_empty() {
+            build_placement.cancel();
+            return;
+        }
+
+        // Research can upgrade buildings (e.g., to their look for the next age)
+        for &(action, unit_id) in BUILD_HOTKEYS {
+            let unit_id = player_research.upgraded_unit(player_id, unit_id.into());
+            let unit_info = self.empires.unit(local_player.civ_id, unit_id);
+            if actions.key_state(action) == KeyState::TransitionUp &&
+               player_research.is_unit_enabled(player_id, unit_info) {
+                build_placement.start(unit_id);
+            }
+        }
+        if actions.key_state(InputAction::CancelBuild) == KeyState::TransitionUp {
+            build_placement.cancel();
+        }
+
+        let unit_id = match build_placement.unit_id {
+            Some(unit_id) => unit_id,
+            None => return,
+        };
+        let building_info = self.empires.unit(local_player.civ_id, unit_id);
+        let cost = unit::cost(building_info);
+
+        let mouse_position = mouse_state.position + viewport.top_left_i32();
+        let position = placement::snap_to_tiles(building_info,
+                                                &view_projector.unproject(&mouse_position, &*terrain));
+        build_placement.position = Some(position);
+        build_placement.valid = placement::can_place(&self.empires,
+                                                     building_info,
+                                                     &position,
+                                                     &*terrain,
+                                                     &*occupied_tiles) &&
+                                player_resources.can_afford(player_id, &cost);
+
+        let stay_in_build_mode = match build_placement.place_requested.take() {
+            Some(stay) => stay,
+            None => return,
+        };
+        if !build_placement.valid || !player_resources.spend(player_id, &cost) {
+            return;
+        }
+
+        let building_params = match building_info.building_params {
+            Some(ref params) => params,
+            None => return,
+        };
+        let mut graphic = GraphicComponent::new();
+        graphic.player_color_id = local_player.player_color_id;
+        graphic.graphic_id = Some(building_params.construction_graphic_id);
+
+        // Foundations start with a single hit point and gain the rest as they're constructed
+        let hit_points = player_research.hit_points(player_id, building_info);
+        let mut health = HealthComponent::new(hit_points);
+        health.hit_points = 1;
+
+        let foundation = arg.create();
+        transforms.insert(foundation, TransformComponent::new(position, 0.into()));
+        graphics.insert(foundation, graphic);
+        velocities.insert(foundation, VelocityComponent::new());
+        action_queues.insert(foundation, ActionQueueComponent::new());
+        units.insert(foundation, UnitComponent::new(player_id, local_player.civ_id, unit_id));
+        healths.insert(foundation, health);
+        constructions.insert(foundation, ConstructionComponent::new());
+
+        for builder in builders {
+            // Enqueue sequential actions by holding control (by default).
+            if actions.is_up(InputAction::QueueCommand) {
+                action_batcher.queue_for_entity(builder.get_id(), Action::ClearQueue);
+            }
+            action_batcher.queue_for_entity(builder.get_id(), Action::Build(BuildParams::new(foundation)));
+        }
+        if let Some(sound_group_id) = building_params.construction_sound {
+            sounds.play(sound_group_id, Some(position));
+        }
+
+        if !stay_in_build_mode {
+            build_placement.cancel();
+        }
+    }
+}
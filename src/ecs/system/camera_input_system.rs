@@ -19,12 +19,12 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use ecs::{CameraComponent, VelocityComponent};
-use ecs::resource::KeyboardKeyStates;
-use media::Key;
+use ecs::{CameraComponent, TransformComponent, VelocityComponent};
+use ecs::resource::{InputAction, InputActionStates, Minimap, MouseState, ViewProjector, Viewport};
 use specs::{self, Join};
 use super::System;
 use types::{Fixed, Norm, Vector3};
+use util::camera;
 
 // TODO: Doesn't currently match the camera speed in the original game
 const CAMERA_SPEED: Fixed = fixed_const!(400);
@@ -43,22 +43,35 @@ impl System for CameraInputSystem {
     fn update(&mut self, arg: specs::RunArg, _time_step: Fixed) {
         fetch_components!(arg, _entities, [
             components(cameras: CameraComponent),
+            mut components(transforms: TransformComponent),
             mut components(velocities: VelocityComponent),
-            resource(keyboard_key_states: KeyboardKeyStates),
+            resource(actions: InputActionStates),
+            resource(minimap: Minimap),
+            resource(mouse_state: MouseState),
+            resource(view_projector: ViewProjector),
+            mut resource(viewport: Viewport),
         ]);
 
+        // Holding the select button over the minimap centers the camera on the spot under the mouse
+        if actions.is_down(InputAction::Select) && minimap.contains(&viewport.size, &mouse_state.position) {
+            let world_coord = minimap.world_coord_at(&viewport.size, &mouse_state.position);
+            for (transform, _camera) in (&mut transforms, &cameras).iter() {
+                camera::center_on(&world_coord, &view_projector, &mut viewport, transform);
+            }
+        }
+
         for (velocity, _camera) in (&mut velocities, &cameras).iter() {
             let mut new_velocity = Vector3::new(0.into(), 0.into(), 0.into());
 
-            if keyboard_key_states.key_state(Key::Up).is_down() {
+            if actions.is_down(InputAction::ScrollNorth) {
                 new_velocity.y = (-1).into();
-            } else if keyboard_key_states.key_state(Key::Down).is_down() {
+            } else if actions.is_down(InputAction::ScrollSouth) {
                 new_velocity.y = 1.into();
             }
 
-            if keyboard_key_states.key_state(Key::Left).is_down() {
+            if actions.is_down(InputAction::ScrollWest) {
                 new_velocity.x = (-1).into();
-            } else if keyboard_key_states.key_state(Key::Right).is_down() {
+            } else if actions.is_down(InputAction::ScrollEast) {
                 new_velocity.x = 1.into();
             }
 
@@ -0,0 +1,108 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat;
+use ecs::{DamageGraphicComponent, GraphicComponent, HealthComponent, TransformComponent, UnitComponent,
+          VelocityComponent};
+use identifier::GraphicId;
+use partition::GridPartition;
+use specs::{self, Join};
+use super::System;
+use types::Fixed;
+
+/// Shows damage graphics (e.g., fire on buildings) over units as they lose hit points
+pub struct DamageGraphicSystem {
+    empires: dat::EmpiresDbRef,
+}
+
+impl DamageGraphicSystem {
+    pub fn new(empires: dat::EmpiresDbRef) -> DamageGraphicSystem {
+        DamageGraphicSystem { empires: empires }
+    }
+}
+
+impl System for DamageGraphicSystem {
+    fn update(&mut self, arg: specs::RunArg, _time_step: Fixed) {
+        fetch_components!(arg, entities, [
+            components(units: UnitComponent),
+            components(healths: HealthComponent),
+            mut components(transforms: TransformComponent),
+            mut components(graphics: GraphicComponent),
+            mut components(velocities: VelocityComponent),
+            mut components(damage_graphics: DamageGraphicComponent),
+            mut resource(grid: GridPartition),
+        ]);
+
+        let mut changes = Vec::new();
+        for (entity, unit, health) in (&entities, &units, &healths).iter() {
+            let unit_info = unit.db(&self.empires);
+            if unit_info.damage_graphics.is_empty() {
+                continue;
+            }
+
+            let wanted = damage_graphic_id(&unit_info.damage_graphics, health);
+            let current = damage_graphics.get(entity).map(|damage_graphic| damage_graphic.graphic_id);
+            if wanted != current {
+                changes.push((entity, wanted));
+            }
+        }
+
+        for (entity, wanted) in changes {
+            if let Some(old) = damage_graphics.remove(entity) {
+                // Removing the velocity keeps the VelocitySystem from putting it back in the grid
+                velocities.remove(old.overlay);
+                grid.remove_entity(old.overlay.get_id());
+                arg.delete(old.overlay);
+            }
+
+            if let Some(graphic_id) = wanted {
+                let transform = match transforms.get(entity) {
+                    Some(transform) => transform.clone(),
+                    None => continue,
+                };
+                let mut graphic = GraphicComponent::new();
+                graphic.graphic_id = Some(graphic_id);
+                if let Some(unit_graphic) = graphics.get(entity) {
+                    graphic.player_color_id = unit_graphic.player_color_id;
+                }
+
+                let overlay = arg.create();
+                transforms.insert(overlay, transform);
+                graphics.insert(overlay, graphic);
+                velocities.insert(overlay, VelocityComponent::new());
+                damage_graphics.insert(entity, DamageGraphicComponent::new(graphic_id, overlay));
+            }
+        }
+    }
+}
+
+/// Picks the damage graphic for the most damage the unit has taken
+fn damage_graphic_id(damage_graphics: &[dat::DamageGraphic], health: &HealthComponent) -> Option<GraphicId> {
+    if health.max_hit_points <= 0 {
+        return None;
+    }
+
+    let damage_percent = 100 - health.hit_points * 100 / health.max_hit_points;
+    damage_graphics.iter()
+        .filter(|damage_graphic| damage_percent >= damage_graphic.damage_percent as i32)
+        .max_by_key(|damage_graphic| damage_graphic.damage_percent)
+        .map(|damage_graphic| damage_graphic.graphic_id)
+}
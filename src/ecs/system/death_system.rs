@@ -0,0 +1,179 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat;
+use ecs::{ActionQueueComponent, AttackActionComponent, BuildActionComponent, ConstructionComponent,
+          CorpseComponent, DamageGraphicComponent, DropOffActionComponent, DyingComponent,
+          GatherActionComponent, GraphicComponent, HealthComponent, MoveToPositionActionComponent,
+          ProductionQueueComponent, ResearchQueueComponent, ResourceSiteComponent, SelectedUnitComponent,
+          TransformComponent, UnitComponent, VelocityComponent};
+use ecs::resource::{PlayerResources, SoundQueue};
+use partition::GridPartition;
+use specs::{self, Join};
+use super::System;
+use types::{Fixed, Vector3};
+use util::unit as unit_util;
+
+/// Turns units that run out of hit points into dying units, and replaces
+/// dying units with their dead units (corpses, rubble) once they've finished dying
+pub struct DeathSystem {
+    empires: dat::EmpiresDbRef,
+}
+
+impl DeathSystem {
+    pub fn new(empires: dat::EmpiresDbRef) -> DeathSystem {
+        DeathSystem { empires: empires }
+    }
+
+    fn dying_time(&self, unit_info: &dat::Unit) -> Fixed {
+        match unit_info.dying_graphic {
+            Some(graphic_id) => {
+                let graphic_info = self.empires.graphic(graphic_id);
+                // Same frame timing as the AnimationSystem: the frame rate is double the seconds per frame
+                let seconds_per_frame = Fixed::from(graphic_info.frame_rate) / 2.into();
+                seconds_per_frame * graphic_info.frame_count.into()
+            }
+            None => 0.into(),
+        }
+    }
+}
+
+impl System for DeathSystem {
+    fn update(&mut self, arg: specs::RunArg, time_step: Fixed) {
+        fetch_components!(arg, entities, [
+            components(healths: HealthComponent),
+            mut components(units: UnitComponent),
+            mut components(dying_units: DyingComponent),
+            mut components(transforms: TransformComponent),
+            mut components(graphics: GraphicComponent),
+            mut components(velocities: VelocityComponent),
+            mut components(selected_units: SelectedUnitComponent),
+            mut components(action_queues: ActionQueueComponent),
+            mut components(mtps: MoveToPositionActionComponent),
+            mut components(attacks: AttackActionComponent),
+            mut components(gathers: GatherActionComponent),
+            mut components(drop_offs: DropOffActionComponent),
+            mut components(builds: BuildActionComponent),
+            mut components(constructions: ConstructionComponent),
+            mut components(production_queues: ProductionQueueComponent),
+            mut components(research_queues: ResearchQueueComponent),
+            mut components(resource_sites: ResourceSiteComponent),
+            mut components(damage_graphics: DamageGraphicComponent),
+            mut components(corpses: CorpseComponent),
+            mut resource(grid: GridPartition),
+            mut resource(player_resources: PlayerResources),
+            mut resource(sounds: SoundQueue),
+        ]);
+
+        let mut died = Vec::new();
+        for (entity, health, unit) in (&entities, &healths, &units).iter() {
+            if !health.is_alive() {
+                died.push((entity, unit.clone()));
+            }
+        }
+
+        for (entity, unit) in died {
+            let unit_info = unit.db(&self.empires);
+
+            // Without a unit component, the unit can no longer be selected, targeted or block tiles
+            units.remove(entity);
+            selected_units.remove(entity);
+            action_queues.remove(entity);
+            mtps.remove(entity);
+            attacks.remove(entity);
+            gathers.remove(entity);
+            drop_offs.remove(entity);
+            builds.remove(entity);
+            constructions.remove(entity);
+            if let Some(mut production_queue) = production_queues.remove(entity) {
+                // Units that were still waiting to be trained are refunded, as if they were cancelled
+                while let Some(unit_id) = production_queue.dequeue() {
+                    let cost = unit_util::cost(self.empires.unit(unit.civilization_id, unit_id));
+                    player_resources.refund(unit.player_id, &cost);
+                }
+            }
+            research_queues.remove(entity);
+            if let Some(damage_graphic) = damage_graphics.remove(entity) {
+                velocities.remove(damage_graphic.overlay);
+                grid.remove_entity(damage_graphic.overlay.get_id());
+                arg.delete(damage_graphic.overlay);
+            }
+
+            if let Some(velocity) = velocities.get_mut(entity) {
+                velocity.velocity = Vector3::new(0.into(), 0.into(), 0.into());
+            }
+            if let Some(graphic) = graphics.get_mut(entity) {
+                graphic.set_graphic(unit_info.dying_graphic);
+            }
+            if let Some(sound_group_id) = unit_info.dying_sound {
+                sounds.play(sound_group_id, transforms.get(entity).map(|t| *t.position()));
+            }
+
+            dying_units.insert(entity, DyingComponent::new(unit, self.dying_time(unit_info)));
+        }
+
+        let mut dead = Vec::new();
+        for (entity, dying) in (&entities, &mut dying_units).iter() {
+            dying.time_left -= time_step;
+            if dying.time_left <= 0.into() {
+                dead.push((entity, dying.unit.clone()));
+            }
+        }
+
+        for (entity, unit) in dead {
+            let unit_info = unit.db(&self.empires);
+            if let Some(dead_unit_id) = unit_info.dead_unit_id {
+                if let Some(transform) = transforms.get(entity).cloned() {
+                    let dead_unit_info = self.empires.unit(unit.civilization_id, dead_unit_id);
+                    let mut graphic = GraphicComponent::new();
+                    graphic.player_color_id = unit.player_id.into();
+                    graphic.graphic_id = dead_unit_info.standing_graphic;
+
+                    let corpse = arg.create();
+                    transforms.insert(corpse, transform);
+                    graphics.insert(corpse, graphic);
+                    velocities.insert(corpse, VelocityComponent::new());
+
+                    // The dead unit is a unit of its own so that it can be selected and (for carcasses)
+                    // gathered from, but it's marked so it doesn't block tiles like a living one might
+                    units.insert(corpse,
+                                 UnitComponent::new(unit.player_id, unit.civilization_id, dead_unit_id));
+                    corpses.insert(corpse, CorpseComponent);
+
+                    // Carcasses of hunted animals can be gathered for food
+                    if dead_unit_info.interaction_mode == dat::InteractionMode::Resource {
+                        let resource = unit_util::gatherable_resource(dead_unit_info)
+                            .or_else(|| unit_util::gatherable_resource(unit_info));
+                        if let Some((resource_type, amount)) = resource {
+                            resource_sites.insert(corpse, ResourceSiteComponent::new(resource_type, amount));
+                        }
+                    }
+                }
+            }
+
+            // Removing the velocity keeps the VelocitySystem from putting it back in the grid
+            dying_units.remove(entity);
+            velocities.remove(entity);
+            grid.remove_entity(entity.get_id());
+            arg.delete(entity);
+        }
+    }
+}
@@ -21,24 +21,38 @@
 
 mod action;
 mod animation_system;
+mod build_placement_system;
 mod camera_input_system;
 mod camera_position_system;
+mod damage_graphic_system;
+mod death_system;
 mod decal_system;
 mod grid_system;
 mod occupied_tile_system;
+mod production_system;
+mod projectile_system;
+mod research_system;
 mod system;
 mod unit_action_system;
 mod unit_selection_system;
 mod velocity_system;
+mod visibility_system;
 
 pub use self::action::*;
 pub use self::animation_system::AnimationSystem;
+pub use self::build_placement_system::BuildPlacementSystem;
 pub use self::camera_input_system::CameraInputSystem;
 pub use self::camera_position_system::CameraPositionSystem;
+pub use self::damage_graphic_system::DamageGraphicSystem;
+pub use self::death_system::DeathSystem;
 pub use self::decal_system::DecalSystem;
 pub use self::grid_system::GridSystem;
 pub use self::occupied_tile_system::OccupiedTileSystem;
+pub use self::production_system::ProductionSystem;
+pub use self::projectile_system::ProjectileSystem;
+pub use self::research_system::ResearchSystem;
 pub use self::system::{System, SystemWrapper};
 pub use self::unit_action_system::UnitActionSystem;
 pub use self::unit_selection_system::UnitSelectionSystem;
 pub use self::velocity_system::VelocitySystem;
+pub use self::visibility_system::VisibilitySystem;
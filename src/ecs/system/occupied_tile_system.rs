@@ -20,7 +20,7 @@
 // SOFTWARE.
 
 use dat;
-use ecs::{TransformComponent, UnitComponent};
+use ecs::{CorpseComponent, TransformComponent, UnitComponent};
 use ecs::resource::OccupiedTiles;
 use specs::{self, Join};
 use super::System;
@@ -39,14 +39,20 @@ impl OccupiedTileSystem {
 
 impl System for OccupiedTileSystem {
     fn update(&mut self, arg: specs::RunArg, _time_step: Fixed) {
-        fetch_components!(arg, _entities, [
+        fetch_components!(arg, entities, [
             components(transforms: TransformComponent),
             components(units: UnitComponent),
+            components(corpses: CorpseComponent),
             mut resource(occupied_tiles: OccupiedTiles),
         ]);
 
         occupied_tiles.tiles.clear();
-        for (transform, unit) in (&transforms, &units).iter() {
+        for (entity, transform, unit) in (&entities, &transforms, &units).iter() {
+            // What's left of dead units (e.g., hunted animals' carcasses) can be walked over
+            if corpses.get(entity).is_some() {
+                continue;
+            }
+
             let unit_info = self.empires.unit(unit.civilization_id, unit.unit_id);
             let unit_blocks_tiles = match unit_info.interaction_mode {
                 dat::InteractionMode::Building => true,
@@ -0,0 +1,259 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This system trains units at buildings: it handles the training hotkeys for the selected
+//! buildings, advances their production queues, and sends finished units to the rally point.
+
+use action::{Action, GatherParams, MoveToPositionParams};
+use dat;
+use ecs::{ActionQueueComponent, ConstructionComponent, GraphicComponent, HealthComponent,
+          ProductionQueueComponent, RallyPoint, ResourceSiteComponent, SelectedUnitComponent,
+          TransformComponent, UnitComponent, VelocityComponent};
+use ecs::resource::{ActionBatcher, InputAction, InputActionStates, OccupiedTiles, PathFinder,
+                    PlayerResearch, PlayerResources, Players, SoundQueue, Terrain};
+use identifier::{PlayerId, UnitId};
+use media::KeyState;
+use specs::{self, Join};
+use std::cmp;
+use std::collections::HashMap;
+use super::System;
+use types::{Fixed, Vector3};
+use util::{placement, unit};
+
+/// No player can have more units than this, no matter how many houses they build
+const MAX_POPULATION: i32 = 50;
+
+/// How many units are queued at once while holding shift (by default)
+const BATCH_SIZE: usize = 5;
+
+const TRAIN_ACTIONS: &'static [InputAction] = &[InputAction::TrainUnit1,
+                                                 InputAction::TrainUnit2,
+                                                 InputAction::TrainUnit3,
+                                                 InputAction::TrainUnit4,
+                                                 InputAction::TrainUnit5];
+
+/// A unit that finished training and is ready to leave its building
+struct TrainedUnit {
+    player_id: PlayerId,
+    building: UnitComponent,
+    unit_id: UnitId,
+    position: Vector3,
+    rally_point: Option<RallyPoint>,
+}
+
+pub struct ProductionSystem {
+    empires: dat::EmpiresDbRef,
+}
+
+impl ProductionSystem {
+    pub fn new(empires: dat::EmpiresDbRef) -> ProductionSystem {
+        ProductionSystem { empires: empires }
+    }
+}
+
+impl System for ProductionSystem {
+    fn update(&mut self, arg: specs::RunArg, time_step: Fixed) {
+        fetch_components!(arg, entities, [
+            components(selected_units: SelectedUnitComponent),
+            components(constructions: ConstructionComponent),
+            components(resource_sites: ResourceSiteComponent),
+            mut components(production_queues: ProductionQueueComponent),
+            mut components(units: UnitComponent),
+            mut components(transforms: TransformComponent),
+            mut components(graphics: GraphicComponent),
+            mut components(velocities: VelocityComponent),
+            mut components(healths: HealthComponent),
+            mut components(action_queues: ActionQueueComponent),
+            resource(actions: InputActionStates),
+            resource(players: Players),
+            resource(path_finder: PathFinder),
+            resource(occupied_tiles: OccupiedTiles),
+            resource(terrain: Terrain),
+            resource(player_research: PlayerResearch),
+            mut resource(player_resources: PlayerResources),
+            mut resource(action_batcher: ActionBatcher),
+            mut resource(sounds: SoundQueue),
+        ]);
+
+        let local_player_id = players.local_player().player_id;
+        for (entity, unit, _selected_unit) in (&entities, &units, &selected_units).iter() {
+            if unit.player_id != local_player_id || constructions.get(entity).is_some() {
+                continue;
+            }
+            let trainable_units = unit::trainable_units(&self.empires,
+                                                        &*player_research,
+                                                        unit.player_id,
+                                                        unit.civilization_id,
+                                                        unit.unit_id);
+            if trainable_units.is_empty() {
+                continue;
+            }
+            if production_queues.get(entity).is_none() {
+                production_queues.insert(entity, ProductionQueueComponent::new());
+            }
+            let production_queue = production_queues.get_mut(entity).unwrap();
+
+            for (index, action) in TRAIN_ACTIONS.iter().enumerate() {
+                if actions.key_state(*action) != KeyState::TransitionUp || index >= trainable_units.len() {
+                    continue;
+                }
+                let unit_info = trainable_units[index];
+                let cost = unit::cost(unit_info);
+                let count = if actions.is_down(InputAction::AddToSelection) { BATCH_SIZE } else { 1 };
+                for _ in 0..count {
+                    if !player_resources.spend(unit.player_id, &cost) {
+                        break;
+                    }
+                    if !production_queue.enqueue(unit_info.id) {
+                        player_resources.refund(unit.player_id, &cost);
+                        break;
+                    }
+                }
+            }
+
+            if actions.key_state(InputAction::CancelTraining) == KeyState::TransitionUp {
+                if let Some(unit_id) = production_queue.dequeue() {
+                    let cost = unit::cost(self.empires.unit(unit.civilization_id, unit_id));
+                    player_resources.refund(unit.player_id, &cost);
+                }
+            }
+        }
+
+        // Houses (and other buildings) make room for population once they're finished
+        let mut headroom: HashMap<PlayerId, i32> = HashMap::new();
+        let mut population: HashMap<PlayerId, i32> = HashMap::new();
+        for (entity, unit, health) in (&entities, &units, &healths).iter() {
+            if !health.is_alive() {
+                continue;
+            }
+            let unit_info = unit.db(&self.empires);
+            if constructions.get(entity).is_none() {
+                *headroom.entry(unit.player_id).or_insert(0) += unit::population_headroom(unit_info);
+            }
+            *population.entry(unit.player_id).or_insert(0) += unit::population(unit_info);
+        }
+
+        let mut trained = Vec::new();
+        let buildings = (&entities, &units, &transforms, &mut production_queues);
+        for (entity, building, transform, production_queue) in buildings.iter() {
+            if constructions.get(entity).is_some() {
+                continue;
+            }
+            // Units that were upgraded while they were queued come out as their upgrade
+            let unit_id = match production_queue.current() {
+                Some(unit_id) => player_research.upgraded_unit(building.player_id, unit_id),
+                None => continue,
+            };
+            let unit_info = self.empires.unit(building.civilization_id, unit_id);
+            let train_time: Fixed = unit_info.trainable_params
+                .as_ref()
+                .map(|params| params.train_time.into())
+                .unwrap_or(0.into());
+            if production_queue.progress < train_time {
+                production_queue.progress = cmp::min(production_queue.progress + time_step, train_time);
+                continue;
+            }
+
+            // Finished units wait in the building until there's room for them
+            let player_population = population.entry(building.player_id).or_insert(0);
+            let player_headroom = cmp::min(MAX_POPULATION, *headroom.get(&building.player_id).unwrap_or(&0));
+            if *player_population + unit::population(unit_info) > player_headroom {
+                continue;
+            }
+
+            let rally_position = match production_queue.rally_point {
+                Some(RallyPoint::Position(position)) => Some(position),
+                Some(RallyPoint::Unit(target)) => transforms.get(target).map(|t| *t.position()),
+                None => None,
+            };
+            let position = placement::spawn_position(&self.empires,
+                                                     building.db(&self.empires),
+                                                     transform.position(),
+                                                     unit_info,
+                                                     rally_position.as_ref(),
+                                                     &*terrain,
+                                                     &*occupied_tiles);
+            if let Some(position) = position {
+                *player_population += unit::population(unit_info);
+                production_queue.finish_current();
+                trained.push(TrainedUnit {
+                    player_id: building.player_id,
+                    building: building.clone(),
+                    unit_id: unit_id,
+                    position: position,
+                    rally_point: production_queue.rally_point,
+                });
+            }
+        }
+
+        for trained_unit in trained {
+            let unit_info = self.empires.unit(trained_unit.building.civilization_id, trained_unit.unit_id);
+            let mut graphic = GraphicComponent::new();
+            graphic.player_color_id = trained_unit.player_id.into();
+            graphic.graphic_id = unit_info.standing_graphic;
+
+            let entity = arg.create();
+            action_queues.insert(entity, ActionQueueComponent::new());
+            transforms.insert(entity, TransformComponent::new(trained_unit.position, 0.into()));
+            graphics.insert(entity, graphic);
+            velocities.insert(entity, VelocityComponent::new());
+            let hit_points = player_research.hit_points(trained_unit.player_id, unit_info);
+            healths.insert(entity, HealthComponent::new(hit_points));
+            units.insert(entity,
+                         UnitComponent::new(trained_unit.player_id,
+                                            trained_unit.building.civilization_id,
+                                            trained_unit.unit_id));
+            if let Some(sound_group_id) = unit_info.train_sound_id {
+                sounds.play(sound_group_id, Some(trained_unit.position));
+            }
+
+            // Head for the rally point, gathering from it if it's a resource the unit can gather
+            let destination = match trained_unit.rally_point {
+                Some(RallyPoint::Unit(target)) => {
+                    let can_gather = match (resource_sites.get(target), units.get(target)) {
+                        (Some(_), Some(target_unit)) => {
+                            unit::gather_command(unit_info, target_unit.db(&self.empires)).is_some()
+                        }
+                        _ => false,
+                    };
+                    if can_gather {
+                        action_batcher.queue_for_entity(entity.get_id(),
+                                                        Action::Gather(GatherParams::new(target)));
+                        None
+                    } else {
+                        transforms.get(target).map(|t| *t.position())
+                    }
+                }
+                Some(RallyPoint::Position(position)) => Some(position),
+                None => None,
+            };
+            if let Some(destination) = destination {
+                let path = path_finder.find_path(&*terrain,
+                                                 &*occupied_tiles,
+                                                 &trained_unit.position,
+                                                 &destination,
+                                                 unit_info.terrain_restriction);
+                action_batcher.queue_for_entity(entity.get_id(),
+                                                Action::MoveToPosition(MoveToPositionParams::new(path)));
+            }
+        }
+    }
+}
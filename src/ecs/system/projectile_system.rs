@@ -0,0 +1,104 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat;
+use ecs::{HealthComponent, ProjectileComponent, TransformComponent, UnitComponent, VelocityComponent};
+use ecs::resource::PlayerResearch;
+use partition::GridPartition;
+use specs::{self, Join};
+use super::System;
+use types::Fixed;
+use util::combat;
+
+/// Flies projectiles along their arcs and applies their damage when they land
+pub struct ProjectileSystem {
+    empires: dat::EmpiresDbRef,
+}
+
+impl ProjectileSystem {
+    pub fn new(empires: dat::EmpiresDbRef) -> ProjectileSystem {
+        ProjectileSystem { empires: empires }
+    }
+}
+
+impl System for ProjectileSystem {
+    fn update(&mut self, arg: specs::RunArg, time_step: Fixed) {
+        fetch_components!(arg, entities, [
+            components(units: UnitComponent),
+            components(transforms: TransformComponent),
+            mut components(projectiles: ProjectileComponent),
+            mut components(velocities: VelocityComponent),
+            mut components(healths: HealthComponent),
+            resource(player_research: PlayerResearch),
+            mut resource(grid: GridPartition),
+        ]);
+
+        let mut landed = Vec::new();
+        for (entity, projectile, velocity) in (&entities, &mut projectiles, &mut velocities).iter() {
+            projectile.elapsed += time_step;
+            if projectile.elapsed >= projectile.flight_time {
+                landed.push((entity, projectile.clone()));
+            } else {
+                velocity.velocity = projectile.velocity_at(projectile.elapsed);
+            }
+        }
+
+        let mut hits: Vec<(specs::Entity, i32)> = Vec::new();
+        for (entity, projectile) in landed {
+            let source_info = projectile.source.db(&self.empires);
+            let source_player_id = projectile.source.player_id;
+            let source_attacks = player_research.attacks(source_player_id, source_info);
+            if let Some(target) = projectile.target {
+                if let (Some(unit), Some(health)) = (units.get(target), healths.get(target)) {
+                    if health.is_alive() {
+                        let target_info = unit.db(&self.empires);
+                        let armors = player_research.armors(unit.player_id, target_info);
+                        hits.push((target, combat::hit_damage(&source_attacks, target_info, &armors)));
+                    }
+                }
+            }
+
+            let candidates = (&entities, &units, &transforms, &healths)
+                .iter()
+                .filter(|&(e, _, _, health)| {
+                    Some(e) != projectile.target && e != projectile.source_entity && health.is_alive()
+                })
+                .map(|(e, unit, transform, _)| {
+                    let unit_info = unit.db(&self.empires);
+                    let armors = player_research.armors(unit.player_id, unit_info);
+                    (e, unit_info, armors, transform.position())
+                });
+            hits.extend(combat::blast_hits(source_info, &source_attacks, &projectile.end, candidates));
+
+            // Removing the velocity keeps the VelocitySystem from putting it back in the grid
+            velocities.remove(entity);
+            projectiles.remove(entity);
+            grid.remove_entity(entity.get_id());
+            arg.delete(entity);
+        }
+
+        for (victim, damage) in hits {
+            if let Some(health) = healths.get_mut(victim) {
+                health.damage(damage);
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This system handles research at buildings: it handles the research hotkeys for the selected
+//! buildings, advances their research queues, and applies the effects of completed research
+//! (including research completed by finishing a building, and advancing to the next age).
+
+use dat;
+use ecs::{ConstructionComponent, GraphicComponent, HealthComponent, ResearchQueueComponent,
+          SelectedUnitComponent, UnitComponent};
+use ecs::resource::{InputAction, InputActionStates, PlayerResearch, PlayerResources, Players};
+use identifier::{PlayerId, ResearchId};
+use media::KeyState;
+use specs::{self, Join};
+use std::cmp;
+use std::collections::HashSet;
+use super::System;
+use types::Fixed;
+use util::research;
+
+const RESEARCH_ACTIONS: &'static [InputAction] = &[InputAction::Research1,
+                                                    InputAction::Research2,
+                                                    InputAction::Research3,
+                                                    InputAction::Research4,
+                                                    InputAction::Research5];
+
+pub struct ResearchSystem {
+    empires: dat::EmpiresDbRef,
+}
+
+impl ResearchSystem {
+    pub fn new(empires: dat::EmpiresDbRef) -> ResearchSystem {
+        ResearchSystem { empires: empires }
+    }
+}
+
+impl System for ResearchSystem {
+    fn update(&mut self, arg: specs::RunArg, time_step: Fixed) {
+        fetch_components!(arg, entities, [
+            components(selected_units: SelectedUnitComponent),
+            components(constructions: ConstructionComponent),
+            mut components(research_queues: ResearchQueueComponent),
+            mut components(units: UnitComponent),
+            mut components(graphics: GraphicComponent),
+            mut components(healths: HealthComponent),
+            resource(actions: InputActionStates),
+            resource(players: Players),
+            mut resource(player_research: PlayerResearch),
+            mut resource(player_resources: PlayerResources),
+        ]);
+
+        // The same research can't be in progress at two buildings at once
+        let mut queued: HashSet<(PlayerId, ResearchId)> = HashSet::new();
+        for (unit, research_queue) in (&units, &research_queues).iter() {
+            queued.extend(research_queue.queued().iter().map(|&research_id| (unit.player_id, research_id)));
+        }
+
+        let local_player_id = players.local_player().player_id;
+        for (entity, unit, _selected_unit) in (&entities, &units, &selected_units).iter() {
+            if unit.player_id != local_player_id || constructions.get(entity).is_some() {
+                continue;
+            }
+            let available: Vec<&dat::Research> =
+                research::available_research(&self.empires, &*player_research, unit.player_id, unit.unit_id)
+                    .into_iter()
+                    .filter(|research| !queued.contains(&(unit.player_id, research.id)))
+                    .collect();
+            if research_queues.get(entity).is_none() {
+                if available.is_empty() {
+                    continue;
+                }
+                research_queues.insert(entity, ResearchQueueComponent::new());
+            }
+            let research_queue = research_queues.get_mut(entity).unwrap();
+
+            for (index, action) in RESEARCH_ACTIONS.iter().enumerate() {
+                if actions.key_state(*action) != KeyState::TransitionUp || index >= available.len() {
+                    continue;
+                }
+                let cost = research::cost(available[index]);
+                if !player_resources.spend(unit.player_id, &cost) {
+                    continue;
+                }
+                if !research_queue.enqueue(available[index].id) {
+                    player_resources.refund(unit.player_id, &cost);
+                }
+            }
+
+            if actions.key_state(InputAction::CancelResearch) == KeyState::TransitionUp {
+                if let Some(research_id) = research_queue.dequeue() {
+                    let cost = research::cost(self.empires.research(research_id));
+                    player_resources.refund(unit.player_id, &cost);
+                }
+            }
+        }
+
+        for (entity, unit, research_queue) in (&entities, &units, &mut research_queues).iter() {
+            if constructions.get(entity).is_some() {
+                continue;
+            }
+            let research_id = match research_queue.current() {
+                Some(research_id) => research_id,
+                None => continue,
+            };
+            let research_time = Fixed::from(self.empires.research(research_id).time_seconds);
+            if research_queue.progress < research_time {
+                research_queue.progress = cmp::min(research_queue.progress + time_step, research_time);
+                continue;
+            }
+            research_queue.finish_current();
+            player_research.complete(unit.player_id, research_id);
+        }
+
+        for (player_id, research_id) in player_research.take_pending() {
+            let age_id = match self.empires.research(research_id).age_id {
+                Some(age_id) => age_id,
+                None => continue,
+            };
+            player_research.apply(&self.empires, player_id, age_id);
+
+            // The player's existing units take on their upgrades (e.g., buildings changing their look
+            // for the new age) and any change to their hit points
+            let player_units = (&entities, &mut units, &mut graphics, &mut healths);
+            for (entity, unit, graphic, health) in player_units.iter() {
+                if unit.player_id != player_id || !health.is_alive() {
+                    continue;
+                }
+                let under_construction = constructions.get(entity).is_some();
+                let old_info = unit.db(&self.empires);
+                let unit_id = player_research.upgraded_unit(player_id, unit.unit_id);
+                let unit_info = self.empires.unit(unit.civilization_id, unit_id);
+                if unit_id != unit.unit_id {
+                    unit.unit_id = unit_id;
+                    if under_construction {
+                        let construction_graphic_id = unit_info.building_params
+                            .as_ref()
+                            .map(|params| params.construction_graphic_id);
+                        graphic.set_graphic(construction_graphic_id);
+                    } else if graphic.graphic_id == old_info.standing_graphic {
+                        graphic.set_graphic(unit_info.standing_graphic);
+                    }
+                }
+
+                // Foundations gain their hit points as they're constructed instead
+                let max_hit_points = player_research.hit_points(player_id, unit_info);
+                if max_hit_points != health.max_hit_points {
+                    if !under_construction {
+                        health.hit_points += max_hit_points - health.max_hit_points;
+                    }
+                    health.max_hit_points = max_hit_points;
+                    health.hit_points = cmp::max(1, cmp::min(max_hit_points, health.hit_points));
+                }
+            }
+        }
+    }
+}
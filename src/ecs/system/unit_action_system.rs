@@ -20,7 +20,8 @@
 // SOFTWARE.
 
 use action::Action;
-use ecs::component::{MoveToPositionActionComponent, ActionQueueComponent};
+use ecs::component::{AttackActionComponent, BuildActionComponent, DropOffActionComponent,
+                     GatherActionComponent, MoveToPositionActionComponent, ActionQueueComponent};
 use ecs::resource::ActionBatcher;
 use specs::{self, Join};
 use super::System;
@@ -31,20 +32,38 @@ use types::Fixed;
 const TURN_LENGTH_SECONDS: Fixed = fixed_const!(0.1);
 
 macro_rules! detach_action_component {
-    ($action:expr, $entity:expr, $mtps:expr) => {
+    ($action:expr, $entity:expr, $mtps:expr, $attacks:expr, $gathers:expr, $drop_offs:expr,
+     $builds:expr) => {
         match $action {
             Action::MoveToPosition(_) => { $mtps.remove($entity); }
+            Action::Attack(_) => { $attacks.remove($entity); }
+            Action::Gather(_) => { $gathers.remove($entity); }
+            Action::DropOff(_) => { $drop_offs.remove($entity); }
+            Action::Build(_) => { $builds.remove($entity); }
             _ => panic!("Failed to detach unknown action: {:?}", $action)
         }
     }
 }
 
 macro_rules! attach_action_component {
-    ($action:expr, $entity:expr, $mtps:expr) => {
+    ($action:expr, $entity:expr, $mtps:expr, $attacks:expr, $gathers:expr, $drop_offs:expr,
+     $builds:expr) => {
         match $action {
             Action::MoveToPosition(ref params) => {
                 $mtps.insert($entity, MoveToPositionActionComponent::new(params.path.clone()));
             }
+            Action::Attack(ref params) => {
+                $attacks.insert($entity, AttackActionComponent::new(params.target));
+            }
+            Action::Gather(ref params) => {
+                $gathers.insert($entity, GatherActionComponent::new(params.target));
+            }
+            Action::DropOff(ref params) => {
+                $drop_offs.insert($entity, DropOffActionComponent::new(params.target));
+            }
+            Action::Build(ref params) => {
+                $builds.insert($entity, BuildActionComponent::new(params.target));
+            }
             _ => panic!("Failed to attach unknown action: {:?}", $action)
         }
     }
@@ -68,6 +87,10 @@ impl System for UnitActionSystem {
         fetch_components!(arg, entities, [
             mut components(action_queues: ActionQueueComponent),
             mut components(mtps: MoveToPositionActionComponent),
+            mut components(attacks: AttackActionComponent),
+            mut components(gathers: GatherActionComponent),
+            mut components(drop_offs: DropOffActionComponent),
+            mut components(builds: BuildActionComponent),
             mut resource(action_batcher: ActionBatcher),
         ]);
 
@@ -95,12 +118,24 @@ impl System for UnitActionSystem {
             // Handle the actual action via separate systems.
             if action_queue.current_action_done() {
                 if let &Some(ref action) = action_queue.current_action() {
-                    detach_action_component!(*action, entity, &mut mtps);
+                    detach_action_component!(*action,
+                                             entity,
+                                             &mut mtps,
+                                             &mut attacks,
+                                             &mut gathers,
+                                             &mut drop_offs,
+                                             &mut builds);
                 }
                 action_queue.next_action();
 
                 if let &Some(ref action) = action_queue.current_action() {
-                    attach_action_component!(*action, entity, &mut mtps);
+                    attach_action_component!(*action,
+                                             entity,
+                                             &mut mtps,
+                                             &mut attacks,
+                                             &mut gathers,
+                                             &mut drop_offs,
+                                             &mut builds);
                 }
             }
         }
@@ -20,117 +20,454 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-//! This system is responsible for unit selection and queuing up a MoveToPosition action.
+//! This system is responsible for unit selection (by clicking, dragging a box, double clicking
+//! and control groups) and queuing up MoveToPosition, Attack, Gather, DropOff and Build actions,
+//! and setting rally points for buildings.
 
-use action::{Action, MoveToPositionParams};
+use action::{Action, AttackParams, BuildParams, DropOffParams, GatherParams, MoveToPositionParams};
 use dat;
-use ecs::{DecalComponent, OnScreenComponent, SelectedUnitComponent, TransformComponent, UnitComponent};
+use ecs::{CameraComponent, CarriedResourceComponent, ConstructionComponent, DecalComponent, HealthComponent,
+          OnScreenComponent, ProductionQueueComponent, RallyPoint, ResourceSiteComponent,
+          SelectedUnitComponent, TransformComponent, UnitComponent};
 
 use ecs::resource::{
     MouseState,
-    KeyboardKeyStates,
+    InputAction,
+    InputActionStates,
+    Minimap,
     PathFinder,
     Players,
     ViewProjector,
     Viewport,
     OccupiedTiles,
     Terrain,
+    PlayerResearch,
     ActionBatcher,
+    BuildPlacement,
+    SelectionBox,
+    SoundQueue,
+    TileVisibility,
+    Visibility,
 };
 
-use media::{KeyState, MouseButton, Key};
-use resource::DrsKey;
+use identifier::{PlayerId, UnitId};
+use media::KeyState;
+use nalgebra::Vector2;
+use partition::GridPartition;
+use resource::{DEFAULT_PALETTE_FILE_ID, DrsKey};
 use specs::{self, Join};
+use std::cmp;
+use std::collections::HashSet;
 use super::System;
-use types::{Fixed, Vector3};
-use util::unit;
+use types::{AABox, Fixed, Rect, Vector3};
+use util::{camera, unit};
+
+const CONTROL_GROUP_ACTIONS: &'static [InputAction] = &[InputAction::ControlGroup1,
+                                                         InputAction::ControlGroup2,
+                                                         InputAction::ControlGroup3,
+                                                         InputAction::ControlGroup4,
+                                                         InputAction::ControlGroup5,
+                                                         InputAction::ControlGroup6,
+                                                         InputAction::ControlGroup7,
+                                                         InputAction::ControlGroup8,
+                                                         InputAction::ControlGroup9];
+
+/// How soon a second click (or press of a control group's key) has to follow the first
+/// for them to count as a double click
+const DOUBLE_CLICK_TIME: Fixed = fixed_const!(0.5);
+
+/// How far (in tiles) past a selection box to look for units, since big selection boxes
+/// (e.g., of buildings) can reach into the box from units whose positions are outside of it
+const SELECTION_BOX_MARGIN: i32 = 3;
 
 pub struct UnitSelectionSystem {
     empires: dat::EmpiresDbRef,
+
+    /// The units put in each control group, some of which may have died since
+    control_groups: Vec<Vec<specs::Entity>>,
+
+    /// The player and type of the unit that was last clicked, and how long ago
+    last_click: Option<(PlayerId, UnitId, Fixed)>,
+
+    /// The control group that was last selected with its key, and how long ago
+    last_recall: Option<(usize, Fixed)>,
 }
 
 impl UnitSelectionSystem {
     pub fn new(empires: dat::EmpiresDbRef) -> UnitSelectionSystem {
-        UnitSelectionSystem { empires: empires }
+        UnitSelectionSystem {
+            empires: empires,
+            control_groups: vec![Vec::new(); CONTROL_GROUP_ACTIONS.len()],
+            last_click: None,
+            last_recall: None,
+        }
     }
 }
 
 impl System for UnitSelectionSystem {
-    fn update(&mut self, arg: specs::RunArg, _time_step: Fixed) {
+    fn update(&mut self, arg: specs::RunArg, time_step: Fixed) {
         fetch_components!(arg, entities, [
+            components(cameras: CameraComponent),
             components(on_screen: OnScreenComponent),
             components(units: UnitComponent),
+            components(healths: HealthComponent),
+            components(resource_sites: ResourceSiteComponent),
+            components(carried_resources: CarriedResourceComponent),
+            components(constructions: ConstructionComponent),
             mut components(decals: DecalComponent),
             mut components(selected_units: SelectedUnitComponent),
+            mut components(production_queues: ProductionQueueComponent),
             mut components(transforms: TransformComponent),
-            resource(keyboard_state: KeyboardKeyStates),
+            resource(actions: InputActionStates),
+            resource(minimap: Minimap),
             resource(mouse_state: MouseState),
             resource(path_finder: PathFinder),
             resource(players: Players),
             resource(view_projector: ViewProjector),
-            resource(viewport: Viewport),
             resource(occupied_tiles: OccupiedTiles),
             resource(terrain: Terrain),
+            resource(player_research: PlayerResearch),
+            resource(grid: GridPartition),
+            resource(visibility: Visibility),
             mut resource(action_batcher: ActionBatcher),
+            mut resource(build_placement: BuildPlacement),
+            mut resource(selection_box: SelectionBox),
+            mut resource(sounds: SoundQueue),
+            mut resource(viewport: Viewport),
         ]);
 
-        if mouse_state.key_states.key_state(MouseButton::Left) == KeyState::TransitionUp {
-            // Holding the left shift key while left clicking a unit will add them to the current selection.
-            if keyboard_state.is_up(Key::ShiftLeft) {
+        if let Some((_, _, ref mut elapsed)) = self.last_click {
+            *elapsed += time_step;
+        }
+        if let Some((_, ref mut elapsed)) = self.last_recall {
+            *elapsed += time_step;
+        }
+        let local_player_id = players.local_player().player_id;
+
+        // Selecting over the minimap moves the camera instead (see CameraInputSystem), and
+        // commanding there sends the selected units to that spot on the map
+        let over_minimap = minimap.contains(&viewport.size, &mouse_state.position);
+
+        // In build mode, clicking places the building (holding shift keeps placing more)
+        // and commanding leaves build mode
+        if build_placement.is_active() {
+            selection_box.cancel();
+            if over_minimap {
+                return;
+            } else if actions.key_state(InputAction::Select) == KeyState::TransitionUp {
+                build_placement.place_requested = Some(actions.is_down(InputAction::AddToSelection));
+            } else if actions.key_state(InputAction::Command) == KeyState::TransitionUp {
+                build_placement.cancel();
+            }
+            return;
+        }
+
+        // Holding control (by default) while pressing a number puts the selected units in that
+        // control group, and pressing the number alone selects them again
+        for (group, action) in CONTROL_GROUP_ACTIONS.iter().enumerate() {
+            if actions.key_state(*action) != KeyState::TransitionUp {
+                continue;
+            }
+            if actions.is_down(InputAction::AssignControlGroup) {
+                self.control_groups[group] = (&entities, &units, &selected_units)
+                    .iter()
+                    .filter(|&(_, unit, _)| unit.player_id == local_player_id)
+                    .map(|(entity, _, _)| entity)
+                    .collect();
+                continue;
+            }
+
+            let members: Vec<specs::Entity> = self.control_groups[group]
+                .iter()
+                .cloned()
+                .filter(|&entity| healths.get(entity).map(|health| health.is_alive()).unwrap_or(false))
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            selected_units.clear();
+            for &entity in &members {
+                selected_units.insert(entity, SelectedUnitComponent);
+            }
+
+            // Selecting the same group twice in a row moves the view to it
+            let recalled_twice = match self.last_recall {
+                Some((last_group, elapsed)) => last_group == group && elapsed <= DOUBLE_CLICK_TIME,
+                None => false,
+            };
+            if recalled_twice {
+                let position = transforms.get(members[0]).map(|transform| *transform.position());
+                if let Some(position) = position {
+                    for (transform, _camera) in (&mut transforms, &cameras).iter() {
+                        camera::center_on(&position, &view_projector, &mut viewport, transform);
+                    }
+                }
+                self.last_recall = None;
+            } else {
+                self.last_recall = Some((group, 0.into()));
+            }
+        }
+
+        // Dragging with the select button held selects the units in the box instead of clicking
+        let mouse_position = mouse_state.position + viewport.top_left_i32();
+        match actions.key_state(InputAction::Select) {
+            KeyState::TransitionDown if !over_minimap => selection_box.start(mouse_position),
+            _ => selection_box.drag_to(mouse_position),
+        }
+
+        if actions.key_state(InputAction::Select) == KeyState::TransitionUp {
+            let dragged_box = selection_box.finish();
+
+            // Holding shift (by default) while selecting units will add them to the current selection.
+            if actions.is_up(InputAction::AddToSelection) && (dragged_box.is_some() || !over_minimap) {
                 selected_units.clear();
             }
 
+            if let Some(dragged_box) = dragged_box {
+                // Only the player's own units are box selected, and only the kind that's
+                // preferred out of the ones in the box (e.g., soldiers over villagers)
+                let candidates = selection_candidates(&dragged_box, &view_projector, &terrain, &grid);
+                let mut hits = Vec::new();
+                for (entity, unit, transform, health) in (&entities, &units, &transforms, &healths).iter() {
+                    if unit.player_id != local_player_id || !health.is_alive() ||
+                       !candidates.contains(&entity.get_id()) {
+                        continue;
+                    }
+                    let unit_info = unit.db(&self.empires);
+                    let unit_box = unit::selection_box(unit_info, transform);
+                    if projected_rect(&view_projector, &unit_box).intersection(&dragged_box).is_some() {
+                        hits.push((entity, unit::selection_priority(unit_info), unit_info));
+                    }
+                }
+
+                let preferred = hits.iter().map(|&(_, priority, _)| priority).min();
+                let mut selection_sound = None;
+                for &(entity, priority, unit_info) in &hits {
+                    if Some(priority) == preferred {
+                        selected_units.insert(entity, SelectedUnitComponent);
+                        selection_sound = selection_sound.or(unit_info.selection_sound);
+                    }
+                }
+                if let Some(sound_group_id) = selection_sound {
+                    sounds.play(sound_group_id, None);
+                }
+            } else if !over_minimap {
+                // Cast a ray from the mouse position through to the terrain and select the unit whose
+                // axis-aligned box intersects the ray. When several do, the preferred kind of unit
+                // wins, and then the one in front (further down the screen).
+                let mouse_ray = calculate_mouse_ray(&viewport, &mouse_state, &view_projector, &terrain);
+                let mut clicked = None;
+                for (entity, _, unit, transform) in (&entities, &on_screen, &units, &transforms).iter() {
+                    let unit_info = unit.db(&self.empires);
+                    if unit_info.interaction_mode == dat::InteractionMode::NonInteracting ||
+                       !is_seen(&visibility, local_player_id, unit, unit_info, transform.position()) {
+                        continue;
+                    }
+                    let unit_box = unit::selection_box(unit_info, transform);
+                    if !unit_box.intersects_ray(&mouse_ray.origin, &mouse_ray.direction) {
+                        continue;
+                    }
+                    let rank = (unit::selection_priority(unit_info),
+                                -view_projector.project(transform.position()).y);
+                    if clicked.map(|(_, _, best_rank)| rank < best_rank).unwrap_or(true) {
+                        clicked = Some((entity, unit, rank));
+                    }
+                }
+
+                if let Some((entity, unit, _)) = clicked {
+                    // Double clicking a unit selects all of the units of its type that are on screen
+                    let double_click = match self.last_click {
+                        Some((player_id, unit_id, elapsed)) => {
+                            player_id == unit.player_id && unit_id == unit.unit_id &&
+                            elapsed <= DOUBLE_CLICK_TIME
+                        }
+                        None => false,
+                    };
+                    if double_click {
+                        let same_type = (&entities, &on_screen, &units, &transforms);
+                        for (entity, _, other, transform) in same_type.iter() {
+                            if other.player_id != unit.player_id || other.unit_id != unit.unit_id {
+                                continue;
+                            }
+                            let other_info = other.db(&self.empires);
+                            let position = transform.position();
+                            if is_seen(&visibility, local_player_id, other, other_info, position) {
+                                selected_units.insert(entity, SelectedUnitComponent);
+                            }
+                        }
+                        self.last_click = None;
+                    } else {
+                        selected_units.insert(entity, SelectedUnitComponent);
+                        self.last_click = Some((unit.player_id, unit.unit_id, 0.into()));
+                    }
+
+                    if unit.player_id == local_player_id {
+                        if let Some(sound_group_id) = unit.db(&self.empires).selection_sound {
+                            sounds.play(sound_group_id, None);
+                        }
+                    }
+                }
+            }
+        }
+
+        if actions.key_state(InputAction::Command) == KeyState::TransitionUp {
             let mouse_ray = calculate_mouse_ray(&viewport, &mouse_state, &view_projector, &terrain);
+            let target_coord = if over_minimap {
+                let mut world_coord = minimap.world_coord_at(&viewport.size, &mouse_state.position);
+                world_coord.z = terrain.tile_at(world_coord).elevation.into();
+                world_coord
+            } else {
+                mouse_ray.world_coord
+            };
+
+            // What commanding units does depends on what's being pointed at
+            let mut command_target = None;
             for (entity, _, unit, transform) in (&entities, &on_screen, &units, &transforms).iter() {
                 let unit_info = self.empires.unit(unit.civilization_id, unit.unit_id);
-                if unit_info.interaction_mode != dat::InteractionMode::NonInteracting {
+                if !over_minimap && unit_info.interaction_mode != dat::InteractionMode::NonInteracting &&
+                   is_seen(&visibility, local_player_id, unit, unit_info, transform.position()) {
                     let unit_box = unit::selection_box(unit_info, transform);
-
-                    // Cast a ray from the mouse position through to the terrain and select any unit
-                    // whose axis-aligned box intersects the ray.
                     if unit_box.intersects_ray(&mouse_ray.origin, &mouse_ray.direction) {
-                        selected_units.insert(entity, SelectedUnitComponent);
+                        let alive = healths.get(entity).map(|health| health.is_alive()).unwrap_or(false);
+                        command_target = Some((entity, unit.player_id, unit_info, alive));
                         break;
                     }
                 }
             }
-        }
 
-        if mouse_state.key_states.key_state(MouseButton::Right) == KeyState::TransitionUp {
-            let mouse_ray = calculate_mouse_ray(&viewport, &mouse_state, &view_projector, &terrain);
             let mut moving_unit = false;
+            let mut command_sound = None;
             for (entity, transform, unit, _selected_unit) in (&entities, &transforms, &units, &selected_units).iter() {
-                if unit.player_id != players.local_player().player_id {
+                if unit.player_id != local_player_id {
                     continue;
                 }
 
                 let unit_info = self.empires.unit(unit.civilization_id, unit.unit_id);
-                let path = path_finder.find_path(&*terrain,
-                                                    &*occupied_tiles,
-                                                    transform.position(),
-                                                    &mouse_ray.world_coord,
-                                                    unit_info.terrain_restriction);
-                // Enqueue sequential actions by holding left-control.
-                if keyboard_state.is_up(Key::CtrlLeft) {
+
+                // Commanding a building that trains units sets where they go once they're trained
+                let trainable_units = unit::trainable_units(&self.empires,
+                                                            &*player_research,
+                                                            unit.player_id,
+                                                            unit.civilization_id,
+                                                            unit.unit_id);
+                if !trainable_units.is_empty() {
+                    let rally_point = match command_target {
+                        Some((target, _, _, _)) if target != entity => RallyPoint::Unit(target),
+                        _ => RallyPoint::Position(target_coord),
+                    };
+                    if production_queues.get(entity).is_none() {
+                        production_queues.insert(entity, ProductionQueueComponent::new());
+                    }
+                    if let Some(production_queue) = production_queues.get_mut(entity) {
+                        production_queue.rally_point = Some(rally_point);
+                    }
+                    moving_unit = true;
+                    continue;
+                }
+
+                // Enqueue sequential actions by holding control (by default).
+                if actions.is_up(InputAction::QueueCommand) {
                     action_batcher.queue_for_entity(entity.get_id(), Action::ClearQueue);
                 }
 
-                action_batcher.queue_for_entity(entity.get_id(),
-                                                Action::MoveToPosition(MoveToPositionParams::new(path)));
-                moving_unit = true;
+                let command = command_target.and_then(|(target, player_id, target_info, alive)| {
+                    let can_gather = unit::gather_command(unit_info, target_info).is_some();
+                    let can_attack = unit_info.battle_params.is_some() && alive &&
+                                     player_id != unit.player_id;
+                    if player_id == unit.player_id && constructions.get(target).is_some() &&
+                       unit::build_command(unit_info).is_some() {
+                        Some(Command::Build(target))
+                    } else if can_gather && resource_sites.get(target).is_some() {
+                        Some(Command::Gather(target))
+                    } else if can_gather && can_attack {
+                        // Animals have to be hunted down before they can be gathered from
+                        Some(Command::Hunt(target))
+                    } else if player_id == unit.player_id && carried_resources.get(entity).is_some() &&
+                              unit::is_drop_site(unit_info, target_info) {
+                        Some(Command::DropOff(target))
+                    } else if can_attack && target_info.battle_params.is_some() {
+                        Some(Command::Attack(target))
+                    } else {
+                        None
+                    }
+                });
+
+                let commandable_params = unit_info.commandable_params.as_ref();
+                let sound = match command {
+                    Some(Command::Gather(target)) => {
+                        action_batcher.queue_for_entity(entity.get_id(),
+                                                        Action::Gather(GatherParams::new(target)));
+                        commandable_params.and_then(|params| params.move_sound)
+                    }
+                    Some(Command::Hunt(target)) => {
+                        action_batcher.queue_for_entity(entity.get_id(),
+                                                        Action::Attack(AttackParams::new(target)));
+                        action_batcher.queue_for_entity(entity.get_id(),
+                                                        Action::Gather(GatherParams::new(target)));
+                        commandable_params.and_then(|params| params.attack_sound)
+                    }
+                    Some(Command::DropOff(target)) => {
+                        action_batcher.queue_for_entity(entity.get_id(),
+                                                        Action::DropOff(DropOffParams::new(target)));
+                        commandable_params.and_then(|params| params.move_sound)
+                    }
+                    Some(Command::Build(target)) => {
+                        action_batcher.queue_for_entity(entity.get_id(),
+                                                        Action::Build(BuildParams::new(target)));
+                        commandable_params.and_then(|params| params.move_sound)
+                    }
+                    Some(Command::Attack(target)) => {
+                        action_batcher.queue_for_entity(entity.get_id(),
+                                                        Action::Attack(AttackParams::new(target)));
+                        commandable_params.and_then(|params| params.attack_sound)
+                    }
+                    None => {
+                        let path = path_finder.find_path(&*terrain,
+                                                         &*occupied_tiles,
+                                                         transform.position(),
+                                                         &target_coord,
+                                                         unit_info.terrain_restriction);
+                        let action = Action::MoveToPosition(MoveToPositionParams::new(path));
+                        action_batcher.queue_for_entity(entity.get_id(), action);
+                        moving_unit = true;
+                        commandable_params.and_then(|params| params.move_sound)
+                    }
+                };
+
+                // Only the first unit to acknowledge the command is heard
+                if command_sound.is_none() {
+                    command_sound = sound;
+                }
+            }
+
+            if let Some(sound_group_id) = command_sound {
+                sounds.play(sound_group_id, None);
             }
 
             if moving_unit {
                 let decal = arg.create();
                 transforms.insert(decal,
-                                  TransformComponent::new(mouse_ray.world_coord, 0.into()));
+                                  TransformComponent::new(target_coord, 0.into()));
                 decals.insert(decal,
-                              DecalComponent::new(0.into(), DrsKey::Interfac, 50405.into()));
+                              DecalComponent::new(0.into(),
+                                                  DrsKey::Interfac,
+                                                  50405.into(),
+                                                  (DEFAULT_PALETTE_FILE_ID as usize).into()));
             }
         }
     }
 }
 
+/// What a commanded unit does with the unit being pointed at
+#[derive(Copy, Clone)]
+enum Command {
+    Gather(specs::Entity),
+    Hunt(specs::Entity),
+    DropOff(specs::Entity),
+    Attack(specs::Entity),
+    Build(specs::Entity),
+}
+
 struct MouseRay {
     world_coord: Vector3,
     origin: Vector3,
@@ -157,3 +494,67 @@ fn calculate_mouse_ray(viewport: &Viewport,
         direction: direction,
     }
 }
+
+/// Whether the player can see the unit to point at it: their own units always, and other players'
+/// units where they're drawn, i.e., in sight or (for those that stay visible in the fog) explored
+fn is_seen(visibility: &Visibility,
+           player_id: PlayerId,
+           unit: &UnitComponent,
+           unit_info: &dat::Unit,
+           position: &Vector3)
+           -> bool {
+    if unit.player_id == player_id {
+        return true;
+    }
+    match visibility.visibility_at(player_id, position) {
+        TileVisibility::Visible => true,
+        TileVisibility::Explored => unit_info.visible_in_fog,
+        TileVisibility::Unexplored => false,
+    }
+}
+
+/// The IDs of the units that might be in the selection box, which is in screen coordinates
+fn selection_candidates(selection_box: &Rect,
+                        view_projector: &ViewProjector,
+                        terrain: &Terrain,
+                        grid: &GridPartition)
+                        -> HashSet<u32> {
+    // Units higher up on the terrain are drawn further up the screen, so the box covers
+    // a different part of the map at each elevation
+    let (min_elevation, max_elevation) = terrain.elevation_range();
+    let corners = [Vector2::new(selection_box.x, selection_box.y),
+                   Vector2::new(selection_box.x + selection_box.w, selection_box.y),
+                   Vector2::new(selection_box.x, selection_box.y + selection_box.h),
+                   Vector2::new(selection_box.x + selection_box.w, selection_box.y + selection_box.h)];
+
+    let mut start = Vector2::new(i32::max_value(), i32::max_value());
+    let mut end = Vector2::new(i32::min_value(), i32::min_value());
+    for corner in &corners {
+        for &elevation in &[min_elevation, max_elevation] {
+            let world_coord = view_projector.unproject_at_elevation(corner, elevation.into());
+            let (x, y): (i32, i32) = (world_coord.x.into(), world_coord.y.into());
+            start = Vector2::new(cmp::min(start.x, x - SELECTION_BOX_MARGIN),
+                                 cmp::min(start.y, y - SELECTION_BOX_MARGIN));
+            end = Vector2::new(cmp::max(end.x, x + SELECTION_BOX_MARGIN),
+                               cmp::max(end.y, y + SELECTION_BOX_MARGIN));
+        }
+    }
+    grid.query(&start, &end)
+}
+
+/// The area of the screen that a unit's selection box covers
+fn projected_rect(view_projector: &ViewProjector, unit_box: &AABox) -> Rect {
+    let mut corners = Vec::with_capacity(8);
+    for &x in &[unit_box.min.x, unit_box.max.x] {
+        for &y in &[unit_box.min.y, unit_box.max.y] {
+            for &z in &[unit_box.min.z, unit_box.max.z] {
+                corners.push(view_projector.project(&Vector3::new(x, y, z)));
+            }
+        }
+    }
+    let (min_x, max_x) = (corners.iter().map(|corner| corner.x).min().unwrap(),
+                          corners.iter().map(|corner| corner.x).max().unwrap());
+    let (min_y, max_y) = (corners.iter().map(|corner| corner.y).min().unwrap(),
+                          corners.iter().map(|corner| corner.y).max().unwrap());
+    Rect::of(min_x, min_y, max_x - min_x, max_y - min_y)
+}
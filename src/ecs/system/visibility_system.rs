@@ -0,0 +1,145 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! This system keeps each player's fog of war up to date with what their units can see,
+//! and has players remember the buildings they've seen so they're still drawn out of sight.
+
+use dat::{self, UnitAttributeId};
+use ecs::{GraphicComponent, HealthComponent, TransformComponent, UnitComponent};
+use ecs::resource::{PlayerResearch, Players, RememberedBuilding, TileVisibility, Visibility};
+use identifier::PlayerId;
+use specs::{self, Join};
+use std::collections::{HashMap, HashSet};
+use super::System;
+use types::Fixed;
+
+/// The tiles a unit can see from: its line of sight around the tile it's standing on
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Sight {
+    player_id: PlayerId,
+    row: i32,
+    col: i32,
+    radius: i32,
+}
+
+pub struct VisibilitySystem {
+    empires: dat::EmpiresDbRef,
+
+    /// Each unit's sight as of the last update
+    sights: HashMap<specs::Entity, Sight>,
+}
+
+impl VisibilitySystem {
+    pub fn new(empires: dat::EmpiresDbRef) -> VisibilitySystem {
+        VisibilitySystem {
+            empires: empires,
+            sights: HashMap::new(),
+        }
+    }
+}
+
+impl System for VisibilitySystem {
+    fn update(&mut self, arg: specs::RunArg, _time_step: Fixed) {
+        fetch_components!(arg, entities, [
+            components(units: UnitComponent),
+            components(transforms: TransformComponent),
+            components(healths: HealthComponent),
+            components(graphics: GraphicComponent),
+            resource(player_research: PlayerResearch),
+            mut resource(players: Players),
+            mut resource(visibility: Visibility),
+        ]);
+
+        let mut sights = HashMap::new();
+        for (entity, unit, transform, health) in (&entities, &units, &transforms, &healths).iter() {
+            if !health.is_alive() {
+                continue;
+            }
+            let unit_info = unit.db(&self.empires);
+            let line_of_sight = player_research.attribute(unit.player_id,
+                                                          unit_info,
+                                                          UnitAttributeId::LineOfSight,
+                                                          unit_info.line_of_sight.into());
+            let position = transform.position();
+            sights.insert(entity,
+                          Sight {
+                              player_id: unit.player_id,
+                              row: position.y.into(),
+                              col: position.x.into(),
+                              radius: line_of_sight.into(),
+                          });
+        }
+
+        // Only the sight that changed since the last update (units that moved to another tile,
+        // died or appeared) needs to be taken out of the fog of war and put back in
+        for (entity, sight) in &self.sights {
+            if sights.get(entity) != Some(sight) {
+                visibility.conceal(sight.player_id, sight.row, sight.col, sight.radius);
+            }
+        }
+        for (entity, sight) in &sights {
+            if self.sights.get(entity) != Some(sight) {
+                visibility.reveal(sight.player_id, sight.row, sight.col, sight.radius);
+            }
+        }
+        self.sights = sights;
+
+        let player_ids: HashSet<PlayerId> = self.sights.values().map(|sight| sight.player_id).collect();
+
+        // Scenarios can be won by exploring enough of the map
+        let required_exploration_percent = players.required_exploration_percent();
+        if required_exploration_percent > 0 {
+            for player_id in &player_ids {
+                if visibility.explored_percent(*player_id) >= required_exploration_percent {
+                    players.declare_winner(*player_id);
+                }
+            }
+        }
+
+        for player_id in &player_ids {
+            visibility.forget_visible(*player_id);
+        }
+        for (entity, unit, transform, graphic) in (&entities, &units, &transforms, &graphics).iter() {
+            let unit_info = unit.db(&self.empires);
+            if unit_info.building_params.is_none() || unit_info.visible_in_fog {
+                continue;
+            }
+            let graphic_id = match graphic.graphic_id {
+                Some(graphic_id) => graphic_id,
+                None => continue,
+            };
+            for player_id in &player_ids {
+                if *player_id != unit.player_id &&
+                   visibility.visibility_at(*player_id, transform.position()) == TileVisibility::Visible {
+                    visibility.remember(*player_id,
+                                        entity,
+                                        RememberedBuilding {
+                                            position: *transform.position(),
+                                            player_color_id: graphic.player_color_id,
+                                            graphic_id: graphic_id,
+                                            frame: graphic.frame,
+                                            flip_horizontal: graphic.flip_horizontal,
+                                        });
+                }
+            }
+        }
+    }
+}
@@ -19,22 +19,27 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
-use dat::EmpiresDbRef;
-use ecs::render_system::{TerrainRenderSystem, GraphicRenderSystem, UnitSelectionRenderSystem, RenderSystemWrapper, DecalRenderSystem, TileDebugRenderSystem};
-use ecs::resource::{ViewProjector, RenderCommands, PathFinder, KeyboardKeyStates, Players, OccupiedTiles, ActionBatcher, Viewport, Terrain, MouseState};
-use ecs::system::{VelocitySystem, SystemWrapper, DecalSystem, AnimationSystem, UnitSelectionSystem, OccupiedTileSystem, CameraPositionSystem, MoveToPositionActionSystem, UnitActionSystem, GridSystem, CameraInputSystem};
+use dat::{self, EmpiresDbRef};
+use ecs::render_system::{BuildPlacementRenderSystem, TerrainRenderSystem, GraphicRenderSystem, MinimapRenderSystem, UnitSelectionRenderSystem, RenderSystemWrapper, DecalRenderSystem, TileDebugRenderSystem};
+use ecs::resource::{ViewProjector, RenderCommands, PathFinder, KeyboardKeyStates, InputActionStates, Players, OccupiedTiles, ActionBatcher, Viewport, Terrain, MouseState, SoundQueue, Random, PlayerResources, PlayerResearch, BuildPlacement, Visibility, Minimap, SelectionBox};
+use ecs::system::{BuildActionSystem, BuildPlacementSystem, ProductionSystem, ResearchSystem, VelocitySystem, VisibilitySystem, SystemWrapper, DecalSystem, AnimationSystem, UnitSelectionSystem, OccupiedTileSystem, CameraPositionSystem, MoveToPositionActionSystem, AttackActionSystem, GatherActionSystem, DropOffActionSystem, ProjectileSystem, DeathSystem, DamageGraphicSystem, UnitActionSystem, GridSystem, CameraInputSystem};
 use media::MediaRef;
 use partition::GridPartition;
 use resource::ShapeMetadataStoreRef;
 use scn;
 use specs;
 use std::collections::HashMap;
-use super::component::{DecalComponent, UnitComponent, OnScreenComponent, CameraComponent, MoveToPositionActionComponent, TransformComponent, GraphicComponent, ActionQueueComponent, VelocityComponent, SelectedUnitComponent};
+use std::sync::Arc;
+use super::component::{ProductionQueueComponent, ResearchQueueComponent, BuildActionComponent, ConstructionComponent, CorpseComponent, CarriedResourceComponent, DropOffActionComponent, GatherActionComponent, ResourceSiteComponent, DamageGraphicComponent, DyingComponent, DecalComponent, UnitComponent, OnScreenComponent, ProjectileComponent, CameraComponent, MoveToPositionActionComponent, AttackActionComponent, TransformComponent, GraphicComponent, HealthComponent, ActionQueueComponent, VelocityComponent, SelectedUnitComponent};
 use types::{Fixed, Vector3};
+use util::unit;
 
 const NUM_THREADS: usize = 4;
 const GRID_CELL_SIZE: i32 = 10; // in tiles
 
+// TODO: Should be agreed upon by all players when starting a multiplayer game
+const RANDOM_SEED: u32 = 0x5eed1997;
+
 pub type WorldPlanner = specs::Planner<(SystemGroup, Fixed)>;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -46,6 +51,7 @@ pub enum SystemGroup {
 pub fn create_world_planner(media: MediaRef,
                             empires: EmpiresDbRef,
                             shape_metadata: ShapeMetadataStoreRef,
+                            palette: Arc<Vec<u32>>,
                             scenario: &scn::Scenario)
                             -> WorldPlanner {
     let mut world = specs::World::new();
@@ -69,29 +75,49 @@ pub fn create_world_planner(media: MediaRef,
             graphic_component.graphic_id = unit_info.standing_graphic;
 
             // TODO: Use the bulk creation iterator for better performance
-            world.create_now()
+            let mut builder = world.create_now()
                 .with(ActionQueueComponent::new())
                 .with(transform_component)
                 .with(graphic_component)
                 .with(UnitComponent::new(player_id, civ_id, unit.unit_id))
                 .with(VelocityComponent::new())
-                .build();
+                .with(HealthComponent::new(unit_info.hit_points.into()));
+            if unit_info.interaction_mode == dat::InteractionMode::Resource {
+                if let Some((resource_type, amount)) = unit::gatherable_resource(unit_info) {
+                    builder = builder.with(ResourceSiteComponent::new(resource_type, amount));
+                }
+            }
+            builder.build();
         }
     }
 
     let mut planner = WorldPlanner::new(world, NUM_THREADS);
     attach_systems(&mut planner, &empires, &shape_metadata);
-    attach_render_systems(&mut planner, &empires);
+    attach_render_systems(&mut planner, &empires, palette);
     planner
 }
 
 fn register_components(world: &mut specs::World) {
     world.register::<ActionQueueComponent>();
+    world.register::<AttackActionComponent>();
+    world.register::<BuildActionComponent>();
     world.register::<CameraComponent>();
+    world.register::<CarriedResourceComponent>();
+    world.register::<ConstructionComponent>();
+    world.register::<CorpseComponent>();
+    world.register::<DamageGraphicComponent>();
     world.register::<DecalComponent>();
+    world.register::<DropOffActionComponent>();
+    world.register::<DyingComponent>();
+    world.register::<GatherActionComponent>();
     world.register::<GraphicComponent>();
+    world.register::<HealthComponent>();
     world.register::<MoveToPositionActionComponent>();
     world.register::<OnScreenComponent>();
+    world.register::<ProductionQueueComponent>();
+    world.register::<ProjectileComponent>();
+    world.register::<ResearchQueueComponent>();
+    world.register::<ResourceSiteComponent>();
     world.register::<SelectedUnitComponent>();
     world.register::<TransformComponent>();
     world.register::<UnitComponent>();
@@ -108,11 +134,13 @@ fn add_resources(world: &mut specs::World,
     // Input resources
     world.add_resource(KeyboardKeyStates::new(HashMap::new()));
     world.add_resource(MouseState::new());
+    world.add_resource(InputActionStates::new(HashMap::new()));
 
     // Render resources
     world.add_resource(RenderCommands::new());
     world.add_resource(ViewProjector::new(tile_half_width, tile_half_height));
     world.add_resource(GridPartition::new(GRID_CELL_SIZE, GRID_CELL_SIZE));
+    world.add_resource(Minimap::new(scenario.map.width as i32, scenario.map.height as i32));
 
     // Camera resources and entity
     world.add_resource(Viewport::new(viewport_size.x as i32, viewport_size.y as i32));
@@ -125,14 +153,21 @@ fn add_resources(world: &mut specs::World,
     // Players
     // TODO: Local player currently hardcoded to 1; should be determined in menu
     world.add_resource(Players::from_scenario(scenario, 1.into()));
+    world.add_resource(PlayerResources::from_scenario(scenario));
+    world.add_resource(PlayerResearch::new());
 
     // Unit resources
     world.add_resource(ActionBatcher::new());
+    world.add_resource(BuildPlacement::new());
+    world.add_resource(SelectionBox::new());
+    world.add_resource(SoundQueue::new());
+    world.add_resource(Random::new(RANDOM_SEED));
 
     // Terrain resources
     world.add_resource(OccupiedTiles::new());
     world.add_resource(PathFinder::new(empires.clone()));
     world.add_resource(Terrain::from(&scenario.map, empires.clone()));
+    world.add_resource(Visibility::new(scenario.map.width as i32, scenario.map.height as i32));
 }
 
 macro_rules! system {
@@ -165,14 +200,58 @@ fn attach_systems(planner: &mut WorldPlanner,
             UnitSelectionSystem,
             UnitSelectionSystem::new(empires.clone()),
             1000);
+    system!(planner,
+            BuildPlacementSystem,
+            BuildPlacementSystem::new(empires.clone()),
+            1000);
     system!(planner,
             MoveToPositionActionSystem,
             MoveToPositionActionSystem::new(empires.clone()),
             1000);
+    system!(planner,
+            AttackActionSystem,
+            AttackActionSystem::new(empires.clone()),
+            1000);
+    system!(planner,
+            GatherActionSystem,
+            GatherActionSystem::new(empires.clone()),
+            1000);
+    system!(planner,
+            DropOffActionSystem,
+            DropOffActionSystem::new(empires.clone()),
+            1000);
+    system!(planner,
+            BuildActionSystem,
+            BuildActionSystem::new(empires.clone()),
+            1000);
+    system!(planner,
+            ProductionSystem,
+            ProductionSystem::new(empires.clone()),
+            1000);
+    system!(planner,
+            ResearchSystem,
+            ResearchSystem::new(empires.clone()),
+            1000);
+    system!(planner,
+            ProjectileSystem,
+            ProjectileSystem::new(empires.clone()),
+            1000);
+    system!(planner,
+            DeathSystem,
+            DeathSystem::new(empires.clone()),
+            1000);
+    system!(planner,
+            DamageGraphicSystem,
+            DamageGraphicSystem::new(empires.clone()),
+            1000);
     system!(planner,
             OccupiedTileSystem,
             OccupiedTileSystem::new(empires.clone()),
             1000);
+    system!(planner,
+            VisibilitySystem,
+            VisibilitySystem::new(empires.clone()),
+            1000);
 }
 
 macro_rules! render_system {
@@ -184,7 +263,7 @@ macro_rules! render_system {
     };
 }
 
-fn attach_render_systems(planner: &mut WorldPlanner, empires: &EmpiresDbRef) {
+fn attach_render_systems(planner: &mut WorldPlanner, empires: &EmpiresDbRef, palette: Arc<Vec<u32>>) {
     render_system!(planner,
                    TerrainRenderSystem,
                    TerrainRenderSystem::new(empires.clone()),
@@ -194,9 +273,17 @@ fn attach_render_systems(planner: &mut WorldPlanner, empires: &EmpiresDbRef) {
                    GraphicRenderSystem,
                    GraphicRenderSystem::new(empires.clone()),
                    1000);
+    render_system!(planner,
+                   BuildPlacementRenderSystem,
+                   BuildPlacementRenderSystem::new(empires.clone()),
+                   1000);
     render_system!(planner,
                    UnitSelectionRenderSystem,
                    UnitSelectionRenderSystem::new(empires.clone()),
                    1000);
+    render_system!(planner,
+                   MinimapRenderSystem,
+                   MinimapRenderSystem::new(empires.clone(), palette),
+                   1000);
     render_system!(planner, TileDebugRenderSystem, 1000);
 }
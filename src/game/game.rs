@@ -20,32 +20,42 @@
 // SOFTWARE.
 
 use dat::{EmpiresDb, EmpiresDbRef};
-use media::{self, MediaRef};
-use resource::{DrsManager, DrsManagerRef, GameDir, ShapeManager, ShapeManagerRef, ShapeMetadataStore,
-               ShapeMetadataStoreRef};
+use ecs::resource::InputBindings;
+use media::{self, AudioBackend, InputScript, MediaRef, NullAudioBackend, WavAudioBackend};
+use resource::{DrsManager, DrsManagerRef, FontManager, FontManagerRef, GameDir, ShapeManager, ShapeManagerRef,
+               ShapeMetadataStore, ShapeMetadataStoreRef, SoundManager, SoundManagerRef};
+use super::game_options::GameOptions;
 use super::state::GameState;
+use std::fs;
+use std::path::{Path, PathBuf};
 use time;
 use types::Fixed;
 
 const WINDOW_TITLE: &'static str = "Chariot";
-const WINDOW_WIDTH: u32 = 1024;
-const WINDOW_HEIGHT: u32 = 768;
 
 pub struct Game {
     game_dir: GameDir,
     drs_manager: DrsManagerRef,
     shape_manager: ShapeManagerRef,
     shape_metadata: ShapeMetadataStoreRef,
+    sound_manager: SoundManagerRef,
+    font_manager: FontManagerRef,
     empires: EmpiresDbRef,
     media: MediaRef,
+    input_bindings: InputBindings,
+    headless: bool,
+    frame_dump_dir: Option<PathBuf>,
+    frame_dump_interval: u64,
+    frames_rendered: u64,
     states: Vec<Box<GameState>>,
 }
 
 impl Game {
-    pub fn new(game_data_dir: &str) -> Game {
-        let game_dir = GameDir::new(game_data_dir).unwrap_or_else(|err| {
+    pub fn new(options: &GameOptions) -> Game {
+        let game_dir = GameDir::new(&options.game_data_dir).unwrap_or_else(|err| {
             unrecoverable!("{}", err);
         });
+        print!("{}", game_dir.capabilities());
 
         let drs_manager = DrsManager::new(&game_dir);
         if let Err(err) = drs_manager.borrow_mut().preload() {
@@ -55,26 +65,90 @@ impl Game {
         let shape_manager = ShapeManager::new(drs_manager.clone()).unwrap_or_else(|err| {
             unrecoverable!("Failed to initialize the shape manager: {}", err);
         });
+        shape_manager.borrow_mut().set_texture_budget(options.texture_budget);
+        shape_manager.borrow_mut().set_missing_shape_policy(options.missing_shape_policy);
+        // Frames that get compared between runs shouldn't depend on how fast shapes decode
+        let wait_for_decoding = options.headless || options.frame_dump_dir.is_some();
+        shape_manager.borrow_mut().set_wait_for_decoding(wait_for_decoding);
 
         let shape_metadata = ShapeMetadataStoreRef::new(ShapeMetadataStore::load(&*drs_manager.borrow()));
 
+        let sound_manager = SoundManager::new(drs_manager.clone());
+
+        let font_manager = FontManager::new(&game_dir, options.font_file.as_ref().map(Path::new))
+            .unwrap_or_else(|err| {
+                unrecoverable!("Failed to load fonts: {}", err);
+            });
+
         let empires_dat_location = game_dir.find_file("data/empires.dat").unwrap();
         let empires = EmpiresDbRef::new(EmpiresDb::read_from_file(empires_dat_location)
             .unwrap_or_else(|err| {
                 unrecoverable!("Failed to load empires.dat: {}", err);
             }));
 
-        let media = media::create_media(WINDOW_WIDTH, WINDOW_HEIGHT, WINDOW_TITLE).unwrap_or_else(|err| {
-            unrecoverable!("Failed to create media window: {}", err);
-        });
+        let media = if options.headless {
+            let input_script = match options.input_script {
+                Some(ref file_name) => {
+                    InputScript::read_from_file(file_name).unwrap_or_else(|err| {
+                        unrecoverable!("Failed to load input script \"{}\": {}", file_name, err);
+                    })
+                }
+                None => InputScript::new(),
+            };
+            let resolution = options.display.resolution;
+            media::create_headless_media(resolution.x, resolution.y, input_script, options.max_frames)
+        } else {
+            media::create_media(&options.display, WINDOW_TITLE).unwrap_or_else(|err| {
+                unrecoverable!("Failed to create media window: {}", err);
+            })
+        };
+
+        let audio_backend: Option<Box<AudioBackend>> = match options.audio_dump_file {
+            Some(ref file_name) => {
+                let backend = WavAudioBackend::create(file_name).unwrap_or_else(|err| {
+                    unrecoverable!("Failed to create audio dump \"{}\": {}", file_name, err);
+                });
+                Some(Box::new(backend) as Box<AudioBackend>)
+            }
+            None if !options.audio => Some(Box::new(NullAudioBackend::new()) as Box<AudioBackend>),
+            None => None,
+        };
+        if let Some(audio_backend) = audio_backend {
+            if let Err(err) = media.borrow_mut().audio().set_backend(audio_backend) {
+                unrecoverable!("Failed to start audio: {}", err);
+            }
+        }
+
+        let input_bindings = match options.key_bindings_file {
+            Some(ref file_name) => {
+                InputBindings::read_from_file(file_name).unwrap_or_else(|err| {
+                    unrecoverable!("Failed to load key bindings from \"{}\": {}", file_name, err);
+                })
+            }
+            None => InputBindings::default(),
+        };
+
+        let frame_dump_dir = options.frame_dump_dir.as_ref().map(PathBuf::from);
+        if let Some(ref dir) = frame_dump_dir {
+            if let Err(err) = fs::create_dir_all(dir) {
+                unrecoverable!("Failed to create frame dump directory \"{}\": {}", dir.display(), err);
+            }
+        }
 
         Game {
             game_dir: game_dir,
             drs_manager: drs_manager,
             shape_manager: shape_manager,
             shape_metadata: shape_metadata,
+            sound_manager: sound_manager,
+            font_manager: font_manager,
             empires: empires,
             media: media,
+            input_bindings: input_bindings,
+            headless: options.headless,
+            frame_dump_dir: frame_dump_dir,
+            frame_dump_interval: options.frame_dump_interval,
+            frames_rendered: 0,
             states: Vec::new(),
         }
     }
@@ -97,12 +171,20 @@ impl Game {
         while self.media.borrow().is_open() {
             self.media.borrow_mut().renderer().present();
 
-            let new_time = time::precise_time_ns();
-            accumulator += new_time - last_time;
-            last_time = new_time;
+            if self.headless {
+                // Step exactly one frame at a time so that headless runs are deterministic
+                accumulator += time_step_nanos;
+            } else {
+                let new_time = time::precise_time_ns();
+                accumulator += new_time - last_time;
+                last_time = new_time;
+            }
 
             while accumulator >= time_step_nanos {
                 self.media.borrow_mut().update();
+                if let Err(err) = self.media.borrow_mut().audio().update(time_step_seconds) {
+                    unrecoverable!("Failed to output audio: {}", err);
+                }
                 self.update(time_step_seconds);
                 accumulator -= time_step_nanos;
             }
@@ -111,6 +193,29 @@ impl Game {
             if let Some(state) = self.current_state() {
                 state.render(lerp);
             }
+            self.dump_frame();
+            self.frames_rendered += 1;
+        }
+
+        let shape_manager = self.shape_manager.borrow();
+        println!("Shape manager: {}", shape_manager.stats());
+        if !shape_manager.missing_shapes().is_empty() {
+            print!("{}", shape_manager.missing_shapes());
+        }
+    }
+
+    /// Writes the frame that was just rendered to the frame dump directory if it's time to
+    fn dump_frame(&mut self) {
+        let file_name = match self.frame_dump_dir {
+            Some(ref dir) if self.frames_rendered % self.frame_dump_interval == 0 => {
+                dir.join(format!("frame_{:06}.png", self.frames_rendered))
+            }
+            _ => return,
+        };
+
+        let capture = self.media.borrow_mut().renderer().capture();
+        if let Err(err) = capture.and_then(|image| image.write_png(&file_name)) {
+            unrecoverable!("Failed to write frame to \"{}\": {}", file_name.display(), err);
         }
     }
 
@@ -166,6 +271,14 @@ impl Game {
         self.shape_metadata.clone()
     }
 
+    pub fn sound_manager(&self) -> SoundManagerRef {
+        self.sound_manager.clone()
+    }
+
+    pub fn font_manager(&self) -> FontManagerRef {
+        self.font_manager.clone()
+    }
+
     pub fn empires_db(&self) -> EmpiresDbRef {
         self.empires.clone()
     }
@@ -173,4 +286,8 @@ impl Game {
     pub fn media(&self) -> MediaRef {
         self.media.clone()
     }
+
+    pub fn input_bindings<'a>(&'a self) -> &'a InputBindings {
+        &self.input_bindings
+    }
 }
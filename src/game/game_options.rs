@@ -0,0 +1,87 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use media::DisplaySettings;
+use resource::{DEFAULT_TEXTURE_BUDGET, MissingShapePolicy};
+
+const DEFAULT_WIDTH: u32 = 1024;
+const DEFAULT_HEIGHT: u32 = 768;
+
+/// Settings that control how the game is started
+pub struct GameOptions {
+    pub game_data_dir: String,
+
+    /// Resolution, window mode, and how to scale the game up to the window's size
+    pub display: DisplaySettings,
+
+    /// Bytes of texture memory shapes can use before the least recently used ones are evicted
+    pub texture_budget: usize,
+
+    /// What to draw when a unit or terrain graphic is missing from the game data
+    pub missing_shape_policy: MissingShapePolicy,
+
+    /// Plays sound effects when sounds.drs is available
+    pub audio: bool,
+
+    /// WAV file to record the game's audio to instead of playing it
+    pub audio_dump_file: Option<String>,
+
+    /// TOML file to load key bindings from; the original game's bindings are used if not set
+    pub key_bindings_file: Option<String>,
+
+    /// Bitmap font (.fon or .fnt) to draw text with; looked for in the game data directory if not set
+    pub font_file: Option<String>,
+
+    /// Runs without a window, rendering offscreen with a fixed time step (for automated testing)
+    pub headless: bool,
+
+    /// File with scripted input to play back when running headless
+    pub input_script: Option<String>,
+
+    /// Number of frames to run before quitting; runs until the window is closed if not set
+    pub max_frames: Option<u64>,
+
+    /// Directory to write captured frames to as PNG files; frames aren't captured if not set
+    pub frame_dump_dir: Option<String>,
+
+    /// Captures every Nth rendered frame when `frame_dump_dir` is set
+    pub frame_dump_interval: u64,
+}
+
+impl Default for GameOptions {
+    fn default() -> GameOptions {
+        GameOptions {
+            game_data_dir: "game".into(),
+            display: DisplaySettings::new(DEFAULT_WIDTH, DEFAULT_HEIGHT),
+            texture_budget: DEFAULT_TEXTURE_BUDGET,
+            missing_shape_policy: MissingShapePolicy::Placeholder,
+            audio: true,
+            audio_dump_file: None,
+            key_bindings_file: None,
+            font_file: None,
+            headless: false,
+            input_script: None,
+            max_frames: None,
+            frame_dump_dir: None,
+            frame_dump_interval: 1,
+        }
+    }
+}
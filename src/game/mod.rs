@@ -20,7 +20,11 @@
 // SOFTWARE.
 
 mod game;
+mod game_options;
+mod sound_player;
 mod state;
 
 pub use self::game::Game;
+pub use self::game_options::GameOptions;
+pub use self::sound_player::SoundPlayer;
 pub use self::state::{GameState, ScenarioGameState};
@@ -0,0 +1,132 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat;
+use ecs::resource::SoundEvent;
+use identifier::WavFileId;
+use media::Audio;
+use nalgebra::Vector2;
+use resource::SoundManagerRef;
+use std::collections::HashSet;
+
+const SOUND_VOLUME: f32 = 1.0;
+
+/// Turns the sound events emitted by the systems into sounds in the mixer by picking one of
+/// each sound group's effects and loading its wav out of sounds.drs
+pub struct SoundPlayer {
+    empires: dat::EmpiresDbRef,
+    sound_manager: SoundManagerRef,
+    failed_wavs: HashSet<WavFileId>,
+    random_state: u32,
+}
+
+impl SoundPlayer {
+    pub fn new(empires: dat::EmpiresDbRef, sound_manager: SoundManagerRef) -> SoundPlayer {
+        SoundPlayer {
+            empires: empires,
+            sound_manager: sound_manager,
+            failed_wavs: HashSet::new(),
+            random_state: 0x2545f491,
+        }
+    }
+
+    /// Plays the given event; position is the event's position in screen space, if it has one
+    pub fn play(&mut self, audio: &mut Audio, event: &SoundEvent, position: Option<Vector2<i32>>) {
+        if !self.sound_manager.borrow().is_available() {
+            return;
+        }
+
+        let wav_id = {
+            let sound_group = self.empires.sound_effect_group(event.sound_group_id);
+            let roll = self.next_random();
+            let probabilities: Vec<u16> = sound_group.sound_effects.iter().map(|e| e.probability).collect();
+            match pick_weighted(&probabilities, roll) {
+                Some(index) => sound_group.sound_effects[index].resource_id,
+                None => None,
+            }
+        };
+
+        if let Some(wav_id) = wav_id {
+            if self.failed_wavs.contains(&wav_id) {
+                return;
+            }
+            let sound = self.sound_manager.borrow_mut().get(wav_id);
+            match sound {
+                Ok(sound) => {
+                    audio.play(sound, position, SOUND_VOLUME);
+                }
+                Err(err) => {
+                    // Only complain once per missing sound rather than every time it's played
+                    println!("Failed to load sound {:?}: {}", wav_id, err);
+                    self.failed_wavs.insert(wav_id);
+                }
+            }
+        }
+    }
+
+    /// Xorshift; sound variation doesn't need anything better
+    fn next_random(&mut self) -> u32 {
+        let mut x = self.random_state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.random_state = x;
+        x
+    }
+}
+
+/// Picks an index out of a list of relative probabilities using the given random number.
+/// Returns None if there's nothing to pick from.
+fn pick_weighted(probabilities: &[u16], roll: u32) -> Option<usize> {
+    let total: u32 = probabilities.iter().map(|p| *p as u32).sum();
+    if total == 0 {
+        return if probabilities.is_empty() { None } else { Some(roll as usize % probabilities.len()) };
+    }
+
+    let mut remaining = roll % total;
+    for (index, probability) in probabilities.iter().enumerate() {
+        let probability = *probability as u32;
+        if remaining < probability {
+            return Some(index);
+        }
+        remaining -= probability;
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::pick_weighted;
+
+    #[test]
+    fn test_pick_weighted() {
+        assert_eq!(None, pick_weighted(&[], 5));
+        assert_eq!(Some(0), pick_weighted(&[100], 12345));
+        assert_eq!(Some(1), pick_weighted(&[0, 0], 3));
+
+        assert_eq!(Some(0), pick_weighted(&[30, 70], 0));
+        assert_eq!(Some(0), pick_weighted(&[30, 70], 29));
+        assert_eq!(Some(1), pick_weighted(&[30, 70], 30));
+        assert_eq!(Some(1), pick_weighted(&[30, 70], 99));
+        assert_eq!(Some(0), pick_weighted(&[30, 70], 100));
+        assert_eq!(Some(2), pick_weighted(&[30, 0, 70], 30));
+    }
+}
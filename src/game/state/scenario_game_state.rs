@@ -19,53 +19,125 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use dat;
 use ecs;
-use ecs::resource::{KeyboardKeyStates, MouseState, RenderCommands, Viewport};
-use game::{Game, GameState};
+use ecs::resource::{InputActionStates, InputBindings, KeyboardKeyStates, MouseState, Players,
+                    RenderCommands, SoundQueue, ViewProjector, Viewport};
+use game::{Game, GameState, SoundPlayer};
+use identifier::{GraphicId, PlayerColorId};
 use media::MediaRef;
 use nalgebra::{Vector2, convert};
-use resource::ShapeManagerRef;
+use resource::{DEFAULT_PALETTE_FILE_ID, DrsKey, FontManagerRef, LAVA_PALETTE_CYCLE, ShapeKey, ShapeManagerRef,
+               WATER_PALETTE_CYCLE};
 use scn;
+use std::collections::HashSet;
 use types::Fixed;
 
 pub struct ScenarioGameState {
     media: MediaRef,
     shape_manager: ShapeManagerRef,
+    font_manager: FontManagerRef,
+    input_bindings: InputBindings,
+    sound_player: SoundPlayer,
     planner: ecs::WorldPlanner,
 }
 
 impl ScenarioGameState {
     pub fn new(g: &Game, scenario: scn::Scenario) -> ScenarioGameState {
+        // Water and lava are animated by rotating their colors in the palette. The cycles are
+        // added before anything is loaded, since shapes using the palette are reloaded for them.
+        let default_palette_id = (DEFAULT_PALETTE_FILE_ID as usize).into();
+        for cycle in &[WATER_PALETTE_CYCLE, LAVA_PALETTE_CYCLE] {
+            if let Err(err) = g.shape_manager().borrow_mut().add_palette_cycle(default_palette_id, *cycle) {
+                unrecoverable!("Failed to animate the default palette: {}", err);
+            }
+        }
+
+        let shape_keys = scenario_shape_keys(&*g.empires_db(), &scenario);
+        if let Err(err) = g.shape_manager().borrow_mut().prefetch(&shape_keys) {
+            unrecoverable!("Failed to load the scenario's graphics: {}", err);
+        }
+
+        let palette = g.shape_manager()
+            .borrow_mut()
+            .palette_colors(default_palette_id)
+            .unwrap_or_else(|err| {
+                unrecoverable!("Failed to load the default palette: {}", err);
+            });
+
         ScenarioGameState {
             media: g.media(),
             shape_manager: g.shape_manager(),
-            planner: ecs::create_world_planner(g.media(), g.empires_db(), g.shape_metadata(), &scenario),
+            font_manager: g.font_manager(),
+            input_bindings: g.input_bindings().clone(),
+            sound_player: SoundPlayer::new(g.empires_db(), g.sound_manager()),
+            planner: ecs::create_world_planner(g.media(),
+                                               g.empires_db(),
+                                               g.shape_metadata(),
+                                               palette,
+                                               &scenario),
         }
     }
 
     fn update_viewport(&mut self, lerp: Fixed) {
         let viewport = self.planner.mut_world().read_resource::<Viewport>();
         let top_left: Vector2<i32> = convert(viewport.lerped_top_left(lerp));
-        self.media.borrow_mut().renderer().set_camera_position(&top_left);
+        let mut media = self.media.borrow_mut();
+        media.renderer().set_camera_position(&top_left);
+
+        // Positional sounds are heard relative to the center of the screen, fading out a
+        // screen's width away
+        let viewport_size = media.viewport_size();
+        let center = top_left + Vector2::new(viewport_size.x as i32 / 2, viewport_size.y as i32 / 2);
+        media.audio().set_listener(center, viewport_size.x as i32);
     }
 
     fn update_input_resources(&mut self) {
         let world = self.planner.mut_world();
-        let (mut keys, mut mouse_state) = {
-            (world.write_resource::<KeyboardKeyStates>(), world.write_resource::<MouseState>())
+        let (mut keys, mut mouse_state, mut actions, mut viewport) = {
+            (world.write_resource::<KeyboardKeyStates>(),
+             world.write_resource::<MouseState>(),
+             world.write_resource::<InputActionStates>(),
+             world.write_resource::<Viewport>())
         };
 
         let media = self.media.borrow();
+
+        // The window may have been resized (or rescaled) since the last update
+        let viewport_size = media.viewport_size();
+        viewport.size = Vector2::new(viewport_size.x as i32, viewport_size.y as i32);
+
         *keys = media.key_states().clone();
         (*mouse_state).position = media.mouse_position().clone();
         (*mouse_state).key_states = media.mouse_button_states().clone();
+        (*mouse_state).wheel = media.mouse_wheel();
+        *actions = self.input_bindings.resolve(media.key_states(), media.mouse_button_states());
+    }
+
+    fn play_sounds(&mut self) {
+        let world = self.planner.mut_world();
+        let sounds = world.write_resource::<SoundQueue>().consume_sounds();
+        if sounds.is_empty() {
+            return;
+        }
+
+        let view_projector = world.read_resource::<ViewProjector>();
+        let mut media = self.media.borrow_mut();
+        for sound in &sounds {
+            let position = sound.position.map(|position| view_projector.project(&position));
+            self.sound_player.play(media.audio(), sound, position);
+        }
     }
 }
 
 impl GameState for ScenarioGameState {
     fn start(&mut self) {}
 
-    fn stop(&mut self) {}
+    fn stop(&mut self) {
+        // Other states' shapes shouldn't animate
+        let default_palette_id = (DEFAULT_PALETTE_FILE_ID as usize).into();
+        self.shape_manager.borrow_mut().clear_palette_cycles(default_palette_id);
+    }
 
     fn update(&mut self, time_step: Fixed) -> bool {
         self.update_input_resources();
@@ -79,7 +151,18 @@ impl GameState for ScenarioGameState {
         self.planner.dispatch((ecs::SystemGroup::Normal, time_step));
         self.planner.wait();
 
-        true
+        self.play_sounds();
+        self.shape_manager.borrow_mut().update_palette_cycles(time_step);
+
+        // The scenario is over once someone has won it
+        let players = self.planner.mut_world().read_resource::<Players>();
+        match players.winner() {
+            Some(winner) => {
+                println!("{} has won the scenario", winner.name);
+                false
+            }
+            None => true,
+        }
     }
 
     fn render(&mut self, lerp: Fixed) {
@@ -90,8 +173,53 @@ impl GameState for ScenarioGameState {
 
         let world = self.planner.mut_world();
         let mut render_commands = world.write_resource::<RenderCommands>();
-        render_commands.execute(self.media.borrow_mut().renderer(),
-                                &mut *self.shape_manager.borrow_mut());
+        if let Err(err) = render_commands.execute(self.media.borrow_mut().renderer(),
+                                                  &mut *self.shape_manager.borrow_mut(),
+                                                  &mut *self.font_manager.borrow_mut()) {
+            unrecoverable!("Failed to render: {}", err);
+        }
         render_commands.clear_rendered();
     }
 }
+
+/// Finds the shapes that will be needed right away when the scenario starts: its terrain
+/// and the standing, walking, and dying graphics for every unit type that's in it
+fn scenario_shape_keys(empires: &dat::EmpiresDb, scenario: &scn::Scenario) -> Vec<ShapeKey> {
+    let mut shape_keys = HashSet::new();
+
+    for tile in &scenario.map.tiles {
+        if let Some(slp_id) = empires.terrain(tile.terrain_id).slp_id {
+            shape_keys.insert(ShapeKey::new(DrsKey::Terrain, slp_id, 0.into()));
+        }
+    }
+
+    for player_id in scenario.player_ids() {
+        let civ_id = scenario.player_civilization_id(player_id);
+        let player_color_id: PlayerColorId = player_id.into();
+        for unit in scenario.player_units(player_id) {
+            let unit_info = empires.unit(civ_id, unit.unit_id);
+            let mut graphic_ids = vec![unit_info.standing_graphic, unit_info.dying_graphic];
+            if let Some(ref motion_params) = unit_info.motion_params {
+                graphic_ids.extend(motion_params.walking_graphics.iter().cloned());
+            }
+            for graphic_id in graphic_ids.into_iter().filter_map(|id| id) {
+                add_graphic_shape_keys(empires, graphic_id, player_color_id, &mut shape_keys);
+            }
+        }
+    }
+
+    shape_keys.into_iter().collect()
+}
+
+fn add_graphic_shape_keys(empires: &dat::EmpiresDb,
+                          graphic_id: GraphicId,
+                          player_color_id: PlayerColorId,
+                          shape_keys: &mut HashSet<ShapeKey>) {
+    let graphic = empires.graphic(graphic_id);
+    if let Some(slp_id) = graphic.slp_id {
+        shape_keys.insert(ShapeKey::new(DrsKey::Graphics, slp_id, player_color_id));
+    }
+    for delta in &graphic.deltas {
+        add_graphic_shape_keys(empires, delta.graphic_id, player_color_id, shape_keys);
+    }
+}
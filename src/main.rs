@@ -40,6 +40,7 @@ extern crate nalgebra;
 extern crate num;
 extern crate specs;
 extern crate time;
+extern crate toml;
 
 #[macro_use]
 mod macros;
@@ -50,7 +51,7 @@ mod game;
 mod partition;
 mod util;
 
-use game::{Game, GameState, ScenarioGameState};
+use game::{Game, GameOptions, GameState, ScenarioGameState};
 
 fn main() {
     let arg_matches = clap::App::new("Chariot")
@@ -61,12 +62,138 @@ fn main() {
             .value_name("GAME_DATA_DIR")
             .help("Sets the directory to look in for game data. Defaults to \"game\".")
             .takes_value(true))
+        .arg(clap::Arg::with_name("resolution")
+            .long("resolution")
+            .value_name("WIDTHxHEIGHT")
+            .help("Sets the game's resolution. Defaults to 1024x768.")
+            .takes_value(true))
+        .arg(clap::Arg::with_name("fullscreen")
+            .long("fullscreen")
+            .help("Switches the display to the game's resolution and runs fullscreen"))
+        .arg(clap::Arg::with_name("borderless")
+            .long("borderless")
+            .conflicts_with("fullscreen")
+            .help("Covers the whole display with a borderless window"))
+        .arg(clap::Arg::with_name("scaling")
+            .long("scaling")
+            .value_name("MODE")
+            .possible_values(&["none", "integer", "fractional"])
+            .help("Sets how the game is scaled up to fill larger windows. Defaults to \"none\", which shows \
+                   more of the map instead.")
+            .takes_value(true))
+        .arg(clap::Arg::with_name("texture_budget")
+            .long("texture-budget")
+            .value_name("MEGABYTES")
+            .help("Sets how much texture memory can be used for unit and terrain graphics. Defaults to 256.")
+            .takes_value(true))
+        .arg(clap::Arg::with_name("missing_shapes")
+            .long("missing-shapes")
+            .value_name("POLICY")
+            .possible_values(&["placeholder", "skip", "error"])
+            .help("Sets what to do when a graphic is missing. Defaults to \"placeholder\".")
+            .takes_value(true))
+        .arg(clap::Arg::with_name("no_audio")
+            .long("no-audio")
+            .help("Disables sound"))
+        .arg(clap::Arg::with_name("dump_audio")
+            .long("dump-audio")
+            .value_name("FILE")
+            .conflicts_with("no_audio")
+            .help("Records the game's audio to the given WAV file instead of playing it")
+            .takes_value(true))
+        .arg(clap::Arg::with_name("bindings")
+            .long("bindings")
+            .value_name("FILE")
+            .help("Loads key bindings from the given TOML file (see data/bindings.toml)")
+            .takes_value(true))
+        .arg(clap::Arg::with_name("font")
+            .long("font")
+            .value_name("FILE")
+            .help("Draws text with the given Windows bitmap font (.fon or .fnt)")
+            .takes_value(true))
+        .arg(clap::Arg::with_name("headless")
+            .long("headless")
+            .help("Runs without a window, rendering offscreen with a fixed time step"))
+        .arg(clap::Arg::with_name("input_script")
+            .long("input-script")
+            .value_name("FILE")
+            .requires("headless")
+            .help("Plays back the input events in the given file when running headless")
+            .takes_value(true))
+        .arg(clap::Arg::with_name("frames")
+            .long("frames")
+            .value_name("FRAMES")
+            .requires("headless")
+            .help("Quits after the given number of frames when running headless")
+            .takes_value(true))
+        .arg(clap::Arg::with_name("dump_frames")
+            .long("dump-frames")
+            .value_name("DIR")
+            .help("Writes rendered frames to the given directory as PNG files")
+            .takes_value(true))
+        .arg(clap::Arg::with_name("dump_interval")
+            .long("dump-interval")
+            .value_name("N")
+            .requires("dump_frames")
+            .help("Only writes every Nth frame when dumping frames. Defaults to 1.")
+            .takes_value(true))
         .arg(clap::Arg::with_name("SCENARIO")
             .required(true)
             .help("Scenario file to load (temporary while there's no menu)"))
         .get_matches();
 
-    let game_data_dir = arg_matches.value_of("game_data_dir").unwrap_or("game");
+    let mut options = GameOptions::default();
+    if let Some(game_data_dir) = arg_matches.value_of("game_data_dir") {
+        options.game_data_dir = game_data_dir.into();
+    }
+    if let Some(resolution) = arg_matches.value_of("resolution") {
+        options.display.resolution = parse_resolution(resolution).unwrap_or_else(|| {
+            unrecoverable!("Invalid resolution \"{}\"; expected something like 1024x768", resolution);
+        });
+    }
+    if arg_matches.is_present("fullscreen") {
+        options.display.window_mode = media::WindowMode::Fullscreen;
+    } else if arg_matches.is_present("borderless") {
+        options.display.window_mode = media::WindowMode::Borderless;
+    }
+    if let Some(scaling) = arg_matches.value_of("scaling") {
+        options.display.scaling = scaling.parse().unwrap_or_else(|err| {
+            unrecoverable!("{}", err);
+        });
+    }
+    if let Some(texture_budget) = arg_matches.value_of("texture_budget") {
+        let megabytes: usize = texture_budget.parse().unwrap_or_else(|_| {
+            unrecoverable!("Invalid texture budget \"{}\"; expected a number of megabytes",
+                           texture_budget);
+        });
+        options.texture_budget = megabytes * 1024 * 1024;
+    }
+    if let Some(missing_shapes) = arg_matches.value_of("missing_shapes") {
+        options.missing_shape_policy = missing_shapes.parse().unwrap_or_else(|err| {
+            unrecoverable!("{}", err);
+        });
+    }
+    options.audio = !arg_matches.is_present("no_audio");
+    options.audio_dump_file = arg_matches.value_of("dump_audio").map(|s| s.into());
+    options.key_bindings_file = arg_matches.value_of("bindings").map(|s| s.into());
+    options.font_file = arg_matches.value_of("font").map(|s| s.into());
+    options.headless = arg_matches.is_present("headless");
+    options.input_script = arg_matches.value_of("input_script").map(|s| s.into());
+    if let Some(frames) = arg_matches.value_of("frames") {
+        options.max_frames = Some(frames.parse().unwrap_or_else(|_| {
+            unrecoverable!("Invalid frame count \"{}\"", frames);
+        }));
+    }
+    options.frame_dump_dir = arg_matches.value_of("dump_frames").map(|s| s.into());
+    if let Some(dump_interval) = arg_matches.value_of("dump_interval") {
+        options.frame_dump_interval = match dump_interval.parse() {
+            Ok(interval) if interval > 0 => interval,
+            _ => {
+                unrecoverable!("Invalid dump interval \"{}\"; expected a positive number", dump_interval);
+            }
+        };
+    }
+
     let scenario_file_name = arg_matches.value_of("SCENARIO").unwrap();
 
     let scenario = scn::Scenario::read_from_file(scenario_file_name).unwrap_or_else(|err| {
@@ -75,9 +202,20 @@ fn main() {
                        err);
     });
 
-    let mut game = Game::new(game_data_dir);
+    let mut game = Game::new(&options);
     let initial_state = Box::new(ScenarioGameState::new(&game, scenario));
     game.push_state(initial_state as Box<GameState>);
 
     game.game_loop();
 }
+
+/// Parses a resolution like "1024x768"
+fn parse_resolution(resolution: &str) -> Option<nalgebra::Vector2<u32>> {
+    let mut parts = resolution.split('x');
+    match (parts.next().map(str::parse::<u32>), parts.next().map(str::parse::<u32>), parts.next()) {
+        (Some(Ok(width)), Some(Ok(height)), None) if width > 0 && height > 0 => {
+            Some(nalgebra::Vector2::new(width, height))
+        }
+        _ => None,
+    }
+}
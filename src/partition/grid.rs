@@ -99,10 +99,7 @@ impl GridPartition {
     /// Tells the grid where an entity is so that it can be queried later
     pub fn update_entity(&mut self, entity_id: u32, position: &Vector2<i32>) {
         let cell_key = self.cell_key(&position);
-        if !self.entities.contains_key(&entity_id) {
-            self.entities.insert(entity_id, cell_key);
-        } else {
-            let old_cell_key = *self.entities.get(&entity_id).unwrap();
+        if let Some(old_cell_key) = self.entities.insert(entity_id, cell_key) {
             self.remove_from_cell(old_cell_key, entity_id);
         }
         self.add_to_cell(cell_key, GridEntity::new(entity_id, *position));
@@ -131,6 +128,13 @@ impl GridPartition {
         entities
     }
 
+    /// Forgets about an entity, e.g., when it's deleted
+    pub fn remove_entity(&mut self, entity_id: u32) {
+        if let Some(cell_key) = self.entities.remove(&entity_id) {
+            self.remove_from_cell(cell_key, entity_id);
+        }
+    }
+
     pub fn contains(&self, entity_id: u32) -> bool {
         self.entities.contains_key(&entity_id)
     }
@@ -234,6 +238,19 @@ mod tests {
         assert_eq!(&vec![entity1], grid.cell_mut(CellKey::new(1, 2)).entities());
     }
 
+    #[test]
+    fn test_grid_remove_entity() {
+        let mut grid = GridPartition::new(10, 10);
+        grid.update_entity(1, &v(5, 5));
+        grid.update_entity(1, &v(15, 5));
+        grid.update_entity(2, &v(16, 5));
+
+        grid.remove_entity(1);
+        grid.remove_entity(3); // shouldn't panic
+        assert!(!grid.contains(1));
+        assert_eq!(ids![2], grid.query(&v(0, 0), &v(20, 20)));
+    }
+
     #[test]
     fn test_grid_query() {
         let mut grid = GridPartition::new(10, 10);
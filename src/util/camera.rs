@@ -0,0 +1,36 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use ecs::TransformComponent;
+use ecs::resource::{ViewProjector, Viewport};
+use nalgebra::Vector2;
+use types::Vector3;
+
+/// Moves the camera straight to the world position so that it's in the middle of the screen
+pub fn center_on(world_coord: &Vector3,
+                 view_projector: &ViewProjector,
+                 viewport: &mut Viewport,
+                 camera_transform: &mut TransformComponent) {
+    let half_size = Vector2::new(viewport.size.x / 2, viewport.size.y / 2);
+    let top_left = view_projector.project(world_coord) - half_size;
+    camera_transform.jump_to(Vector3::new(top_left.x.into(), top_left.y.into(), 0.into()));
+    viewport.jump_to(Vector2::new(top_left.x.into(), top_left.y.into()));
+}
@@ -0,0 +1,132 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat;
+use specs;
+use types::{Fixed, Norm, Vector3};
+
+/// Calculates the damage a single hit with the given attacks does to the defender, who has the given
+/// armors. Both come from the units' battle params with their players' research applied.
+pub fn hit_damage(attacks: &[(i16, i16)], defender: &dat::Unit, armors: &[(i16, i16)]) -> i32 {
+    if attacks.is_empty() {
+        return 0;
+    }
+    let default_armor = defender.battle_params
+        .as_ref()
+        .map(|params| params.default_armor as i16)
+        .unwrap_or(0);
+    damage(attacks, armors, default_armor)
+}
+
+/// Finds the units caught in the blast of an attack landing at the given position, and the damage
+/// each of them takes. Candidates are (entity, unit info, armors, position) for every unit that could be hit.
+pub fn blast_hits<'a, I>(attacker: &dat::Unit,
+                         attacks: &[(i16, i16)],
+                         impact: &Vector3,
+                         candidates: I)
+                         -> Vec<(specs::Entity, i32)>
+    where I: Iterator<Item = (specs::Entity, &'a dat::Unit, Vec<(i16, i16)>, &'a Vector3)>
+{
+    let mut hits = Vec::new();
+    if let Some(ref battle_params) = attacker.battle_params {
+        if battle_params.blast_width > 0.0 {
+            let blast_width: Fixed = battle_params.blast_width.into();
+            for (entity, unit_info, armors, position) in candidates {
+                // Units are only affected by blasts of their blast defense level or lower
+                if battle_params.blast_attack_level > unit_info.blast_defense_level {
+                    continue;
+                }
+
+                let mut offset = *position - *impact;
+                offset.z = 0.into();
+                if offset.length() <= blast_width {
+                    hits.push((entity, hit_damage(attacks, unit_info, &armors)));
+                }
+            }
+        }
+    }
+    hits
+}
+
+/// The original game's damage formula: each of the attack's classes is reduced by the defender's
+/// armor of the same class (or its default armor if it has none for that class), and the results
+/// are summed. Every hit does at least one point of damage, no matter how heavy the armor.
+pub fn damage(attacks: &[(i16, i16)], armors: &[(i16, i16)], default_armor: i16) -> i32 {
+    let mut total = 0i32;
+    for &(class, amount) in attacks {
+        let armor = armors.iter()
+            .find(|&&(armor_class, _)| armor_class == class)
+            .map(|&(_, armor_amount)| armor_amount)
+            .unwrap_or(default_armor);
+        if amount > armor {
+            total += (amount - armor) as i32;
+        }
+    }
+    if total < 1 { 1 } else { total }
+}
+
+#[cfg(test)]
+mod tests {
+    use dat;
+    use specs;
+    use super::{blast_hits, damage};
+    use types::Vector3;
+
+    #[test]
+    fn test_damage() {
+        // Melee attack against matching melee armor
+        assert_eq!(3, damage(&[(4, 5)], &[(4, 2)], 0));
+
+        // Classes the defender has no armor for fall back to the default armor
+        assert_eq!(4, damage(&[(4, 5)], &[(3, 2)], 1));
+
+        // Each attack class is reduced separately and then summed
+        assert_eq!(6, damage(&[(4, 5), (3, 4)], &[(4, 1), (3, 2)], 0));
+
+        // Armor can't make an attack class heal the defender
+        assert_eq!(2, damage(&[(4, 1), (3, 4)], &[(4, 10), (3, 2)], 0));
+
+        // Every hit does at least one point of damage
+        assert_eq!(1, damage(&[(4, 2)], &[(4, 10)], 0));
+        assert_eq!(1, damage(&[], &[], 0));
+    }
+
+    #[test]
+    fn test_blast_hits_at_impact_point() {
+        let mut world = specs::World::new();
+        let entity = world.create_now().build();
+
+        let mut attacker = dat::Unit::default();
+        let mut battle_params = dat::BattleParams::default();
+        battle_params.attacks = vec![(4, 5)];
+        battle_params.blast_width = 1.0;
+        attacker.battle_params = Some(battle_params);
+
+        // A unit standing exactly where the attack lands is hit, rather than dividing by zero
+        let defender = dat::Unit::default();
+        let position = Vector3::new(2.into(), 3.into(), 0.into());
+        let candidates = vec![(entity, &defender, Vec::new(), &position)];
+        let hits = blast_hits(&attacker, &[(4, 5)], &position, candidates.into_iter());
+        assert_eq!(1, hits.len());
+        assert_eq!(entity, hits[0].0);
+        assert_eq!(5, hits[0].1);
+    }
+}
@@ -19,4 +19,8 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+pub mod camera;
+pub mod combat;
+pub mod placement;
+pub mod research;
 pub mod unit;
@@ -0,0 +1,193 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+//! Where buildings can be placed
+
+use dat;
+use ecs::resource::{OccupiedTiles, Terrain};
+use identifier::TerrainId;
+use types::{Fixed, Norm, Vector3};
+
+/// Tiles a building covers: (first row, first col, rows, cols)
+pub type Footprint = (i32, i32, i32, i32);
+
+/// Moves the position so that the building lines up with the tiles under it
+pub fn snap_to_tiles(unit_info: &dat::Unit, position: &Vector3) -> Vector3 {
+    Vector3::new(snap_coord(position.x, unit_info.collision_size_x.into()),
+                 snap_coord(position.y, unit_info.collision_size_y.into()),
+                 position.z)
+}
+
+/// Buildings an odd number of tiles across are centered on a tile, and buildings
+/// an even number of tiles across are centered on the corner between tiles
+fn snap_coord(coord: Fixed, size: Fixed) -> Fixed {
+    if tile_count(size) % 2 == 1 {
+        Fixed::from(i32::from(coord)) + fixed_const!(0.5)
+    } else {
+        coord.round()
+    }
+}
+
+/// Number of tiles covered by something with the given collision size (which is half its width)
+fn tile_count(size: Fixed) -> i32 {
+    let count: i32 = (size * 2.into()).round().into();
+    if count < 1 { 1 } else { count }
+}
+
+pub fn footprint(unit_info: &dat::Unit, position: &Vector3) -> Footprint {
+    let cols = tile_count(unit_info.collision_size_x.into());
+    let rows = tile_count(unit_info.collision_size_y.into());
+    let start_col: i32 = (position.x - Fixed::from(cols) / 2.into()).round().into();
+    let start_row: i32 = (position.y - Fixed::from(rows) / 2.into()).round().into();
+    (start_row, start_col, rows, cols)
+}
+
+/// Whether the building can be placed at the (snapped) position: every tile under it has to be
+/// on the map, unoccupied, level, and of a terrain the building can be placed on. Buildings with
+/// side terrains (e.g., docks) also need one of those terrains next to them.
+pub fn can_place(empires: &dat::EmpiresDb,
+                 unit_info: &dat::Unit,
+                 position: &Vector3,
+                 terrain: &Terrain,
+                 occupied_tiles: &OccupiedTiles)
+                 -> bool {
+    let (start_row, start_col, rows, cols) = footprint(unit_info, position);
+    if start_row < 0 || start_col < 0 || start_row + rows > terrain.height() ||
+       start_col + cols > terrain.width() {
+        return false;
+    }
+
+    let restrictions = empires.terrain_restrictions(unit_info.terrain_restriction);
+    let elevation = terrain.tile_at_row_col(start_row, start_col).elevation;
+    for row in start_row..(start_row + rows) {
+        for col in start_col..(start_col + cols) {
+            let tile = terrain.tile_at_row_col(row, col);
+            if occupied_tiles.tiles.contains(&(row, col)) || tile.elevation != elevation {
+                return false;
+            }
+            let terrain_allowed = if has_any(&unit_info.placement_terrain_ids) {
+                matches(&unit_info.placement_terrain_ids, tile.terrain_id)
+            } else {
+                restrictions.passability(tile.terrain_id) > 0.0
+            };
+            if !terrain_allowed {
+                return false;
+            }
+        }
+    }
+
+    if has_any(&unit_info.placement_side_terrain_ids) {
+        return surrounding_tiles((start_row, start_col, rows, cols), terrain)
+            .iter()
+            .any(|&(row, col)| {
+                matches(&unit_info.placement_side_terrain_ids,
+                        terrain.tile_at_row_col(row, col).terrain_id)
+            });
+    }
+    true
+}
+
+/// Finds a free tile next to the building for a unit it trained to appear on, preferring the one
+/// closest to where the unit is headed. Returns the center of the tile.
+pub fn spawn_position(empires: &dat::EmpiresDb,
+                      building_info: &dat::Unit,
+                      building_position: &Vector3,
+                      unit_info: &dat::Unit,
+                      destination: Option<&Vector3>,
+                      terrain: &Terrain,
+                      occupied_tiles: &OccupiedTiles)
+                      -> Option<Vector3> {
+    let restrictions = empires.terrain_restrictions(unit_info.terrain_restriction);
+    let tile_center = |&(row, col): &(i32, i32)| {
+        Vector3::new(Fixed::from(col) + fixed_const!(0.5),
+                     Fixed::from(row) + fixed_const!(0.5),
+                     building_position.z)
+    };
+    let distance = |position: &Vector3| {
+        match destination {
+            Some(destination) => {
+                let mut offset = *destination - *position;
+                offset.z = 0.into();
+                offset.length_squared()
+            }
+            None => 0.into(),
+        }
+    };
+
+    let mut best: Option<(Vector3, Fixed)> = None;
+    for tile in surrounding_tiles(footprint(building_info, building_position), terrain) {
+        let terrain_id = terrain.tile_at_row_col(tile.0, tile.1).terrain_id;
+        if occupied_tiles.tiles.contains(&tile) || restrictions.passability(terrain_id) <= 0.0 {
+            continue;
+        }
+        let position = tile_center(&tile);
+        let tile_distance = distance(&position);
+        if best.as_ref().map(|best| tile_distance < best.1).unwrap_or(true) {
+            best = Some((position, tile_distance));
+        }
+    }
+    best.map(|(position, _)| position)
+}
+
+/// The tiles on the map bordering the footprint, starting along its bottom edge
+fn surrounding_tiles(footprint: Footprint, terrain: &Terrain) -> Vec<(i32, i32)> {
+    let (start_row, start_col, rows, cols) = footprint;
+    let mut tiles = Vec::new();
+    for col in (start_col - 1)..(start_col + cols + 1) {
+        tiles.push((start_row + rows, col));
+        tiles.push((start_row - 1, col));
+    }
+    for row in start_row..(start_row + rows) {
+        tiles.push((row, start_col - 1));
+        tiles.push((row, start_col + cols));
+    }
+    tiles.retain(|&(row, col)| row >= 0 && col >= 0 && row < terrain.height() && col < terrain.width());
+    tiles
+}
+
+fn has_any(terrain_ids: &[Option<TerrainId>; 2]) -> bool {
+    terrain_ids.iter().any(|id| id.is_some())
+}
+
+fn matches(terrain_ids: &[Option<TerrainId>; 2], terrain_id: TerrainId) -> bool {
+    terrain_ids.iter().any(|id| *id == Some(terrain_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{snap_coord, tile_count};
+    use types::Fixed;
+
+    #[test]
+    fn test_snap_coord() {
+        assert_eq!(2, tile_count(1.into()));
+        assert_eq!(3, tile_count(fixed_const!(1.5)));
+        assert_eq!(1, tile_count(fixed_const!(0.2)));
+
+        // Even sizes snap to tile corners
+        assert_eq!(Fixed::from(5), snap_coord(fixed_const!(5.3), 1.into()));
+        assert_eq!(Fixed::from(6), snap_coord(fixed_const!(5.6), 1.into()));
+
+        // Odd sizes snap to tile centers
+        assert_eq!(fixed_const!(5.5), snap_coord(fixed_const!(5.9), fixed_const!(1.5)));
+        assert_eq!(fixed_const!(5.5), snap_coord(fixed_const!(5.1), fixed_const!(0.5)));
+    }
+}
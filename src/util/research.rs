@@ -0,0 +1,52 @@
+// Chariot: An open source reimplementation of Age of Empires (1997)
+// Copyright (c) 2016 Kevin Fuller
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+
+use dat;
+use ecs::resource::PlayerResearch;
+use identifier::{PlayerId, UnitId};
+use types::Fixed;
+
+/// The research the player can start at the building, in the order of their buttons
+pub fn available_research<'a>(empires: &'a dat::EmpiresDb,
+                              player_research: &PlayerResearch,
+                              player_id: PlayerId,
+                              building_id: UnitId)
+                              -> Vec<&'a dat::Research> {
+    let mut research: Vec<&dat::Research> = empires.all_research()
+        .iter()
+        .filter(|research| {
+            // Research is still done at buildings that have been upgraded (e.g., for a new age)
+            let location = research.location
+                .map(|location_id| player_research.upgraded_unit(player_id, location_id));
+            location == Some(building_id) && player_research.is_available(player_id, research)
+        })
+        .collect();
+    research.sort_by_key(|research| (research.button_id, research.id));
+    research
+}
+
+/// What it costs to perform the research
+pub fn cost(research: &dat::Research) -> Vec<(dat::ResourceType, Fixed)> {
+    research.resource_costs
+        .iter()
+        .map(|cost| (cost.resource_type, Fixed::from(cost.amount)))
+        .collect()
+}
@@ -22,9 +22,24 @@
 
 use dat;
 use ecs::TransformComponent;
+use ecs::resource::PlayerResearch;
+use identifier::{CivilizationId, PlayerId, UnitId};
 
 use nalgebra::Vector3;
-use types::AABox;
+use std::collections::HashSet;
+use types::{AABox, Fixed, Norm};
+
+/// Unit command type for gathering resources
+const GATHER_COMMAND: i16 = 5;
+
+/// Unit command type for constructing buildings
+const BUILD_COMMAND: i16 = 101;
+
+/// Resource stored by houses (and town centers) that allows more units to be trained
+const POPULATION_HEADROOM: i16 = 4;
+
+/// Resource stored by units that counts toward their player's population
+const CURRENT_POPULATION: i16 = 11;
 
 pub fn selection_box(unit_info: &dat::Unit, transform: &TransformComponent) -> AABox {
     let position = transform.position();
@@ -45,3 +60,138 @@ pub fn collision_box(unit_info: &dat::Unit, transform: &TransformComponent) -> A
                             position.y + unit_info.collision_size_y.into(),
                             position.z))
 }
+
+/// Distance between the edges of two units, ignoring elevation
+pub fn edge_distance(unit_info: &dat::Unit,
+                     position: &Vector3<Fixed>,
+                     target_info: &dat::Unit,
+                     target_position: &Vector3<Fixed>)
+                     -> Fixed {
+    let mut offset = *target_position - *position;
+    offset.z = 0.into();
+    offset.length() - unit_info.collision_size_x.into() - target_info.collision_size_x.into()
+}
+
+/// Returns the type and amount of resource that can be gathered from a unit (e.g., wood for a tree)
+pub fn gatherable_resource(unit_info: &dat::Unit) -> Option<(dat::ResourceType, Fixed)> {
+    unit_info.resource_storage
+        .iter()
+        .find(|storage| {
+            match storage.resource_type {
+                dat::ResourceType::Unknown(_) => false,
+                _ => storage.amount > 0.0,
+            }
+        })
+        .map(|storage| (storage.resource_type, storage.amount.into()))
+}
+
+/// Finds the command a unit uses to gather from the target, if it's able to
+pub fn gather_command<'a>(unit_info: &'a dat::Unit, target_info: &dat::Unit) -> Option<&'a dat::UnitCommand> {
+    commands(unit_info).iter().find(|command| {
+        command.type_id == GATHER_COMMAND &&
+        (command.unit_id == Some(target_info.id) || command.class_id == target_info.class_id)
+    })
+}
+
+/// Finds the command a unit uses to gather the given type of resource, if it's able to
+pub fn carrying_command(unit_info: &dat::Unit,
+                        resource_type: dat::ResourceType)
+                        -> Option<&dat::UnitCommand> {
+    commands(unit_info).iter().find(|command| {
+        command.type_id == GATHER_COMMAND &&
+        dat::ResourceType::from_i16(command.resource_out) == resource_type
+    })
+}
+
+/// Finds the command a unit uses to construct buildings, if it's able to
+pub fn build_command(unit_info: &dat::Unit) -> Option<&dat::UnitCommand> {
+    commands(unit_info).iter().find(|command| command.type_id == BUILD_COMMAND)
+}
+
+/// What it costs to train or build the unit
+pub fn cost(unit_info: &dat::Unit) -> Vec<(dat::ResourceType, Fixed)> {
+    match unit_info.trainable_params {
+        Some(ref params) => {
+            params.resource_costs
+                .iter()
+                .map(|cost| (cost.resource_type, Fixed::from(cost.amount)))
+                .collect()
+        }
+        None => Vec::new(),
+    }
+}
+
+/// How much room for population the unit provides (e.g., four for a house)
+pub fn population_headroom(unit_info: &dat::Unit) -> i32 {
+    stored_amount(unit_info, POPULATION_HEADROOM)
+}
+
+/// How much the unit counts toward its player's population
+pub fn population(unit_info: &dat::Unit) -> i32 {
+    stored_amount(unit_info, CURRENT_POPULATION)
+}
+
+fn stored_amount(unit_info: &dat::Unit, resource_type: i16) -> i32 {
+    unit_info.resource_storage
+        .iter()
+        .filter(|storage| storage.resource_type == dat::ResourceType::Unknown(resource_type))
+        .map(|storage| storage.amount as i32)
+        .sum()
+}
+
+/// The units the player can train at the building, in the order of their buttons. Units the player
+/// has upgraded (e.g., with research) are replaced by what they were upgraded to.
+pub fn trainable_units<'a>(empires: &'a dat::EmpiresDb,
+                           player_research: &PlayerResearch,
+                           player_id: PlayerId,
+                           civilization_id: CivilizationId,
+                           building_id: UnitId)
+                           -> Vec<&'a dat::Unit> {
+    let mut unit_ids = HashSet::new();
+    let mut units: Vec<&dat::Unit> = empires.civilization(civilization_id)
+        .units()
+        .filter(|unit_info| {
+            player_research.is_unit_enabled(player_id, unit_info) &&
+            unit_info.trainable_params
+                .as_ref()
+                .map(|params| params.train_location_id == Some(building_id))
+                .unwrap_or(false)
+        })
+        .map(|unit_info| player_research.upgraded_unit(player_id, unit_info.id))
+        .filter(|unit_id| unit_ids.insert(*unit_id))
+        .map(|unit_id| empires.unit(civilization_id, unit_id))
+        .collect();
+    units.sort_by_key(|unit_info| {
+        (unit_info.trainable_params.as_ref().map(|params| params.button_id), unit_info.id)
+    });
+    units
+}
+
+fn commands(unit_info: &dat::Unit) -> &[dat::UnitCommand] {
+    match unit_info.commandable_params {
+        Some(ref params) => &params.commands,
+        None => &[],
+    }
+}
+
+/// Whether the target is somewhere the unit can drop off the resources it's carrying
+pub fn is_drop_site(unit_info: &dat::Unit, target_info: &dat::Unit) -> bool {
+    match unit_info.commandable_params {
+        Some(ref params) => params.drop_sites.iter().any(|&id| id >= 0 && id as u32 == *target_info.id),
+        None => false,
+    }
+}
+
+/// Which units are picked when a selection covers several kinds: military units come first,
+/// then villagers, then buildings, then everything else (lower is picked first)
+pub fn selection_priority(unit_info: &dat::Unit) -> u8 {
+    if unit_info.building_params.is_some() {
+        2
+    } else if build_command(unit_info).is_some() {
+        1
+    } else if unit_info.battle_params.is_some() {
+        0
+    } else {
+        3
+    }
+}